@@ -0,0 +1,454 @@
+use crate::{Expr, FieldType, StructMatcher, StructProperties, Value, Visitor, AST};
+use std::cmp::Ordering;
+use std::fmt;
+use std::iter;
+use std::ops::Bound;
+
+/// The most clauses a query is expanded into when looking for contradictions. Queries that expand
+/// into more are assumed to match something.
+const MAX_CLAUSES: usize = 64;
+
+/// The most integers a range of an `Int` field is narrowed down to before each of them is checked
+/// against the other conditions on the field.
+const MAX_CANDIDATES: i64 = 64;
+
+/// A part of a query that is likely a mistake, found by `AST::warnings`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Warning<P> {
+    /// A part of the query that no item can match, such as `(age > 30) && (age < 20)`.
+    Contradiction(AST<P>),
+    /// A part of the query that every item matches, such as `(name == "a") || (name != "a")`.
+    Tautology(AST<P>),
+}
+
+impl<P: fmt::Display> fmt::Display for Warning<P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Warning::Contradiction(ast) => write!(f, "{} never matches", ast),
+            Warning::Tautology(ast) => write!(f, "{} always matches", ast),
+        }
+    }
+}
+
+impl<P: StructProperties + PartialEq> AST<P> {
+    /// Finds the parts of the query that can never match or that always match, by reasoning about
+    /// the ranges and values each field is narrowed down to. A part is reported as a whole, and the
+    /// parts within it are not reported again.
+    ///
+    /// Only what can be proven is reported, so a query without warnings may still match nothing.
+    /// A field that an item has no value for only matches negated conditions, so `(age > 30) ||
+    /// (age <= 30)` is not reported as always matching. Conditions on unbound parameters are
+    /// ignored.
+    pub fn warnings(&self) -> Vec<Warning<P>> {
+        let mut warnings = Vec::new();
+        collect(self, &mut warnings);
+        warnings
+    }
+
+    /// Returns whether every item this query matches is also matched by `other`, e.g. `age > 40`
+    /// implies `age > 30`. This is `Some(true)` if it can be proven, `Some(false)` if an item that
+    /// matches this query but not `other` can be found, and `None` if neither can be shown.
+    ///
+    /// Items are only looked for among values that every field of their type can hold, so the
+    /// integers tried are not negative, as the field may be a `u32`, and the floats tried fit in
+    /// an `f32`.
+    pub fn implies(&self, other: &AST<P>) -> Option<bool> {
+        let counterexample = AST::And(
+            Box::new(self.clone()),
+            Box::new(AST::Not(Box::new(other.clone()))),
+        );
+        if !satisfiable(&counterexample, true) {
+            Some(true)
+        } else if has_example(&counterexample) {
+            Some(false)
+        } else {
+            None
+        }
+    }
+}
+
+fn collect<P: StructProperties + PartialEq>(ast: &AST<P>, warnings: &mut Vec<Warning<P>>) {
+    // `true` and `false` are written on purpose, and so is anything made only of them.
+    if ast.predicates().next().is_none() {
+        return;
+    }
+    if !satisfiable(ast, true) {
+        warnings.push(Warning::Contradiction(ast.clone()));
+    } else if !satisfiable(ast, false) {
+        warnings.push(Warning::Tautology(ast.clone()));
+    } else {
+        match ast {
+            AST::And(left, right) | AST::Or(left, right) => {
+                collect(left, warnings);
+                collect(right, warnings);
+            }
+            AST::Not(ast) => collect(ast, warnings),
+            _ => {}
+        }
+    }
+}
+
+/// A predicate, and whether it has to match (`true`) or not match (`false`).
+type Literal<'a, P> = (bool, &'a AST<P>);
+
+/// Returns `false` if no item can match `ast`, or if every item matches it when `positive` is
+/// unset. Returns `true` whenever this cannot be proven.
+fn satisfiable<P: StructProperties + PartialEq>(ast: &AST<P>, positive: bool) -> bool {
+    match clauses(ast, positive) {
+        Some(clauses) => clauses.iter().any(|clause| clause_satisfiable(clause)),
+        None => true,
+    }
+}
+
+/// Expands `ast`, or its negation if `positive` is unset, into disjunctive normal form: an item
+/// matches if it satisfies all the literals of any of the clauses. Returns `None` if there would be
+/// more than `MAX_CLAUSES` clauses.
+fn clauses<P>(ast: &AST<P>, positive: bool) -> Option<Vec<Vec<Literal<'_, P>>>> {
+    match (ast, positive) {
+        (AST::Not(ast), _) => clauses(ast, !positive),
+        // A single clause without literals, which every item satisfies, or no clauses at all.
+        (AST::True, true) | (AST::False, false) => Some(vec![vec![]]),
+        (AST::True, false) | (AST::False, true) => Some(vec![]),
+        (AST::And(left, right), true) | (AST::Or(left, right), false) => {
+            let (left, right) = (clauses(left, positive)?, clauses(right, positive)?);
+            if left.len() * right.len() > MAX_CLAUSES {
+                return None;
+            }
+            let mut product = Vec::with_capacity(left.len() * right.len());
+            for left in &left {
+                for right in &right {
+                    product.push(left.iter().chain(right).copied().collect());
+                }
+            }
+            Some(product)
+        }
+        (AST::Or(left, right), true) | (AST::And(left, right), false) => {
+            let mut left = clauses(left, positive)?;
+            left.extend(clauses(right, positive)?);
+            match left.len() > MAX_CLAUSES {
+                true => None,
+                false => Some(left),
+            }
+        }
+        (ast, _) => Some(vec![vec![(positive, ast)]]),
+    }
+}
+
+fn clause_satisfiable<P: StructProperties + PartialEq>(clause: &[Literal<P>]) -> bool {
+    // A predicate that has to both match and not match, whatever it compares.
+    let complementary = clause.iter().any(|(positive, ast)| {
+        *positive
+            && clause
+                .iter()
+                .any(|(other_positive, other)| !other_positive && other == ast)
+    });
+    if complementary {
+        return false;
+    }
+
+    let mut fields: Vec<&P> = Vec::new();
+    for (_, ast) in clause {
+        match field(ast) {
+            Some(field) if !fields.contains(&field) => fields.push(field),
+            _ => {}
+        }
+    }
+    fields.into_iter().all(|target| {
+        let literals: Vec<Literal<P>> = clause
+            .iter()
+            .copied()
+            .filter(|(_, ast)| field(ast) == Some(target))
+            .collect();
+        field_satisfiable(target, &literals)
+    })
+}
+
+/// Returns the field a predicate compares, or `None` if it compares an expression or against an
+/// operand that has no value yet.
+fn field<P>(ast: &AST<P>) -> Option<&P> {
+    match ast {
+        AST::Equals { field, .. }
+        | AST::NotEquals { field, .. }
+        | AST::In { field, .. }
+        | AST::NotIn { field, .. }
+        | AST::Contains { field, .. }
+        | AST::NotContains { field, .. }
+        | AST::GreaterThan { field, .. }
+        | AST::LessThan { field, .. }
+        | AST::GreaterThanOrEqual { field, .. }
+        | AST::LessThanOrEqual { field, .. }
+        | AST::StartsWith { field, .. }
+        | AST::NotStartsWith { field, .. }
+        | AST::EndsWith { field, .. }
+        | AST::NotEndsWith { field, .. }
+        | AST::RegexMatch { field, .. }
+        | AST::NotRegexMatch { field, .. }
+        | AST::Like { field, .. }
+        | AST::NotLike { field, .. }
+        | AST::Between { field, .. }
+        | AST::Compare {
+            expr: Expr::Field(field),
+            ..
+        } => Some(field),
+        _ => None,
+    }
+}
+
+/// An item with a value for a single field, used to check a value against the literals on it.
+struct Single<'a, P> {
+    field: &'a P,
+    value: Option<&'a Value>,
+}
+
+impl<P: PartialEq> StructMatcher<P> for Single<'_, P> {
+    fn get_property_value(&self, property: &P) -> Option<Value> {
+        match property == self.field {
+            true => self.value.cloned(),
+            false => None,
+        }
+    }
+}
+
+/// Whether some value of `field`, or the lack of one, satisfies all the literals on it.
+fn field_satisfiable<P: StructProperties + PartialEq>(field: &P, literals: &[Literal<P>]) -> bool {
+    if holds(field, literals, None) {
+        return true;
+    }
+    match candidates(field.get_value_type().field_type(), literals) {
+        Some(values) => values
+            .iter()
+            .any(|value| holds(field, literals, Some(value))),
+        None => true,
+    }
+}
+
+/// Whether an item with `value` for `field` satisfies all the literals, which are on that field.
+fn holds<P: PartialEq>(field: &P, literals: &[Literal<P>], value: Option<&Value>) -> bool {
+    let item = Single { field, value };
+    literals
+        .iter()
+        .all(|(positive, ast)| item.matches_ast(ast) == *positive)
+}
+
+/// Returns every value the literals narrow a field of type `field_type` down to, or `None` if
+/// they do not narrow it down to a few values.
+fn candidates<P>(field_type: FieldType, literals: &[Literal<P>]) -> Option<Vec<Value>> {
+    for literal in literals {
+        match literal {
+            (true, AST::Equals { value, .. }) | (false, AST::NotEquals { value, .. }) => {
+                return Some(vec![value.clone()])
+            }
+            (true, AST::In { value, .. }) if field_type == FieldType::Int => match value {
+                Value::Int(n) => return Some(vec![Value::Int(*n)]),
+                Value::VecInt(values) => {
+                    return Some(values.iter().copied().map(Value::Int).collect())
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    // Only floats can be NaN, and a float in a range is not.
+    let comparable = field_type != FieldType::Float
+        || literals.iter().any(|literal| {
+            literal.0 && bounds(field_type, *literal, false) != (Bound::Unbounded, Bound::Unbounded)
+        });
+    let (mut lower, mut upper) = (Bound::Unbounded, Bound::Unbounded);
+    for literal in literals {
+        let (literal_lower, literal_upper) = bounds(field_type, *literal, comparable);
+        lower = tighter(lower, literal_lower, Ordering::Greater);
+        upper = tighter(upper, literal_upper, Ordering::Less);
+    }
+
+    if field_type == FieldType::Int {
+        let lower = match lower {
+            Bound::Included(Value::Int(n)) => *n as i64,
+            Bound::Excluded(Value::Int(n)) => *n as i64 + 1,
+            _ => i32::MIN as i64,
+        };
+        let upper = match upper {
+            Bound::Included(Value::Int(n)) => *n as i64,
+            Bound::Excluded(Value::Int(n)) => *n as i64 - 1,
+            _ => i32::MAX as i64,
+        };
+        return match upper - lower < MAX_CANDIDATES {
+            true => Some((lower..=upper).map(|n| Value::Int(n as i32)).collect()),
+            false => None,
+        };
+    }
+    match (lower, upper) {
+        (Bound::Included(lower), Bound::Included(upper)) if lower == upper => {
+            Some(vec![lower.clone()])
+        }
+        (
+            Bound::Included(lower) | Bound::Excluded(lower),
+            Bound::Included(upper) | Bound::Excluded(upper),
+        ) if lower >= upper => Some(vec![]),
+        _ => None,
+    }
+}
+
+/// Returns the range of values of a field of type `field_type` that satisfy a literal, or an
+/// unbounded range if it is not a range. A comparison that must not match only bounds the field
+/// from the other side if `comparable` is set, as a NaN is neither greater nor less than anything.
+fn bounds<P>(
+    field_type: FieldType,
+    (positive, ast): Literal<'_, P>,
+    comparable: bool,
+) -> (Bound<&Value>, Bound<&Value>) {
+    use Bound::{Excluded, Included, Unbounded};
+
+    let ordered = !matches!(field_type, FieldType::VecString | FieldType::VecInt);
+    let complement = positive || comparable;
+    let same_type = |bound: &Bound<Value>| match bound {
+        Included(value) | Excluded(value) => value.field_type() == field_type,
+        Unbounded => true,
+    };
+    match ast {
+        AST::Between { lower, upper, .. } if positive && same_type(lower) && same_type(upper) => {
+            (lower.as_ref(), upper.as_ref())
+        }
+        AST::GreaterThan { value, .. }
+        | AST::GreaterThanOrEqual { value, .. }
+        | AST::LessThan { value, .. }
+        | AST::LessThanOrEqual { value, .. }
+            if ordered && complement && value.field_type() == field_type =>
+        {
+            match (ast, positive) {
+                (AST::GreaterThan { .. }, true) => (Excluded(value), Unbounded),
+                (AST::GreaterThanOrEqual { .. }, true) => (Included(value), Unbounded),
+                (AST::LessThan { .. }, true) => (Unbounded, Excluded(value)),
+                (AST::LessThanOrEqual { .. }, true) => (Unbounded, Included(value)),
+                (AST::GreaterThan { .. }, false) => (Unbounded, Included(value)),
+                (AST::GreaterThanOrEqual { .. }, false) => (Unbounded, Excluded(value)),
+                (AST::LessThan { .. }, false) => (Included(value), Unbounded),
+                (_, false) => (Excluded(value), Unbounded),
+                _ => unreachable!(),
+            }
+        }
+        _ => (Unbounded, Unbounded),
+    }
+}
+
+/// Returns the tighter of two bounds: the one whose value is further in the direction of `toward`,
+/// or the excluded one of two bounds on the same value.
+fn tighter<'a>(a: Bound<&'a Value>, b: Bound<&'a Value>, toward: Ordering) -> Bound<&'a Value> {
+    let value = |bound: &Bound<&'a Value>| match bound {
+        Bound::Included(value) | Bound::Excluded(value) => Some(*value),
+        Bound::Unbounded => None,
+    };
+    match (value(&a), value(&b)) {
+        (None, _) => b,
+        (_, None) => a,
+        (Some(x), Some(y)) => match x.partial_cmp(y) {
+            Some(Ordering::Equal) | None => match a {
+                Bound::Excluded(_) => a,
+                _ => b,
+            },
+            Some(ordering) if ordering == toward => a,
+            Some(_) => b,
+        },
+    }
+}
+
+/// Whether an item that matches `ast` can be built, by trying values taken from the query for each
+/// field it compares.
+fn has_example<P: StructProperties + PartialEq>(ast: &AST<P>) -> bool {
+    let clauses = match clauses(ast, true) {
+        Some(clauses) => clauses,
+        None => return false,
+    };
+    clauses.iter().any(|clause| {
+        let mut fields: Vec<&P> = Vec::new();
+        for (_, ast) in clause {
+            match field(ast) {
+                Some(field) => {
+                    if !fields.contains(&field) {
+                        fields.push(field);
+                    }
+                }
+                // The value of an expression or a parameter cannot be chosen.
+                _ => return false,
+            }
+        }
+        fields.into_iter().all(|target| {
+            let literals: Vec<Literal<P>> = clause
+                .iter()
+                .copied()
+                .filter(|(_, ast)| field(ast) == Some(target))
+                .collect();
+            examples(target.get_value_type().field_type(), &literals)
+                .iter()
+                .any(|value| holds(target, &literals, Some(value)))
+        })
+    })
+}
+
+/// Collects the values a query compares against.
+struct Values<'a>(Vec<&'a Value>);
+
+impl<'a, P: 'a> Visitor<'a, P> for Values<'a> {
+    fn visit_value(&mut self, value: &'a Value) {
+        self.0.push(value);
+    }
+}
+
+/// Returns values of type `field_type` to try for a field: the values the literals compare it
+/// against, and values next to them. Integers are kept to those that fit in both an `i32` and a
+/// `u32`, and floats to those that fit in an `f32`, so that any field of the type can hold them.
+fn examples<P>(field_type: FieldType, literals: &[Literal<P>]) -> Vec<Value> {
+    let mut values = Values(Vec::new());
+    for (_, ast) in literals {
+        values.visit_ast(ast);
+    }
+
+    let (mut strings, mut ints, mut floats) =
+        (vec![String::new()], vec![0, i32::MAX as i64], vec![0.0]);
+    for value in &values.0 {
+        match value {
+            Value::String(s) => strings.extend(around(s)),
+            Value::VecString(v) => strings.extend(v.iter().flat_map(|s| around(s))),
+            Value::Int(n) => ints.extend([*n as i64 - 1, *n as i64, *n as i64 + 1]),
+            Value::VecInt(v) => ints.extend(v.iter().map(|n| *n as i64)),
+            Value::Float(n) => floats.extend([n - 1.0, *n, n + 1.0]),
+            #[cfg(feature = "chrono")]
+            Value::DateTime(_) | Value::Date(_) | Value::Duration(_) => {}
+        }
+    }
+
+    let ints = ints
+        .into_iter()
+        .filter(|n| (0..=i32::MAX as i64).contains(n))
+        .map(|n| n as i32);
+    match field_type {
+        FieldType::String => strings.into_iter().map(Value::String).collect(),
+        FieldType::VecString => iter::once(vec![])
+            .chain(strings.into_iter().map(|s| vec![s]))
+            .map(Value::VecString)
+            .collect(),
+        FieldType::Int => ints.map(Value::Int).collect(),
+        FieldType::VecInt => iter::once(vec![])
+            .chain(ints.map(|n| vec![n]))
+            .map(Value::VecInt)
+            .collect(),
+        FieldType::Float => floats
+            .into_iter()
+            .filter(|n| *n as f32 as f64 == *n)
+            .map(Value::Float)
+            .collect(),
+        // Dates, times and durations are only tried with the values they are compared against.
+        #[cfg(feature = "chrono")]
+        FieldType::DateTime | FieldType::Date | FieldType::Duration => values
+            .0
+            .into_iter()
+            .filter(|value| value.field_type() == field_type)
+            .cloned()
+            .collect(),
+    }
+}
+
+/// Returns a string and strings with a character added before or after it.
+fn around(s: &str) -> [String; 3] {
+    [s.to_string(), format!("{}a", s), format!("a{}", s)]
+}
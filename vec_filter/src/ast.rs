@@ -1,6 +1,7 @@
-use crate::{StructMatcher, StructProperties};
+use crate::{CustomOperator, Function, Pattern, StructMatcher, StructProperties};
 use std::convert::TryInto;
-use std::fmt::Debug;
+use std::fmt::{self, Debug};
+use std::ops::Bound;
 use std::str::FromStr;
 
 /// An enumeration of Abstract Syntax Tree (AST) nodes representing various query operations.
@@ -25,12 +26,24 @@ pub enum AST<P> {
         value: Value,
     },
 
+    /// Represents a negated containment operation: field not in value.
+    NotIn {
+        field: P,
+        value: Value,
+    },
+
     /// Represents a containment operation: field contains value.
     Contains {
         field: P,
         value: Value,
     },
 
+    /// Represents a negated containment operation: field not contains value.
+    NotContains {
+        field: P,
+        value: Value,
+    },
+
     /// Represents a greater-than operation: field > value.
     GreaterThan {
         field: P,
@@ -61,18 +74,71 @@ pub enum AST<P> {
         value: Value,
     },
 
+    /// Represents a negated starts-with operation: field does not start with value.
+    NotStartsWith {
+        field: P,
+        value: Value,
+    },
+
     /// Represents an ends-with operation: field ends with value.
     EndsWith {
         field: P,
         value: Value,
     },
 
+    /// Represents a negated ends-with operation: field does not end with value.
+    NotEndsWith {
+        field: P,
+        value: Value,
+    },
+
     /// Represents a regex-match operation: field matches regex pattern.
     RegexMatch {
         field: P,
         value: Value,
     },
 
+    /// Represents a negated regex-match operation: field does not match regex pattern.
+    NotRegexMatch {
+        field: P,
+        value: Value,
+    },
+
+    /// Represents a wildcard match: field like "Al%" or field glob "*.rs".
+    Like {
+        field: P,
+        pattern: Pattern,
+    },
+
+    /// Represents a negated wildcard match: field not like "Al%".
+    NotLike {
+        field: P,
+        pattern: Pattern,
+    },
+
+    /// Represents a range check: field between lower and upper, or field in lower..upper.
+    Between {
+        field: P,
+        lower: Bound<Value>,
+        upper: Bound<Value>,
+    },
+
+    /// Represents a comparison between the result of an expression and a value:
+    /// lower(field) == value.
+    Compare {
+        expr: Expr<P>,
+        operator: Operator,
+        value: Value,
+    },
+
+    /// Represents a comparison against an operand that only has a value once it is bound or
+    /// matched: age > $min_age or created_at > now() - 7d.
+    Deferred {
+        expr: Expr<P>,
+        operator: Operator,
+        operand: Operand,
+    },
+
     InvalidField {
         field_name: String,
     },
@@ -84,22 +150,327 @@ pub enum AST<P> {
     Or(Box<AST<P>>, Box<AST<P>>),
 
     Not(Box<AST<P>>),
+
+    /// Matches every item: true.
+    True,
+
+    /// Matches no item: false.
+    False,
 }
 
 impl<P> AST<P> {
+    /// Builds the comparison node for `operator` between a field and a value.
+    pub fn comparison(field: P, operator: Operator, value: Value) -> Self {
+        match operator {
+            Operator::Equals => AST::Equals { field, value },
+            Operator::NotEquals => AST::NotEquals { field, value },
+            Operator::In => AST::In { field, value },
+            Operator::Contains => AST::Contains { field, value },
+            Operator::GreaterThan => AST::GreaterThan { field, value },
+            Operator::LessThan => AST::LessThan { field, value },
+            Operator::GreaterThanOrEqual => AST::GreaterThanOrEqual { field, value },
+            Operator::LessThanOrEqual => AST::LessThanOrEqual { field, value },
+            Operator::StartsWith => AST::StartsWith { field, value },
+            Operator::EndsWith => AST::EndsWith { field, value },
+            Operator::RegexMatch => AST::RegexMatch { field, value },
+            Operator::NotIn => AST::NotIn { field, value },
+            Operator::NotContains => AST::NotContains { field, value },
+            Operator::NotStartsWith => AST::NotStartsWith { field, value },
+            Operator::NotEndsWith => AST::NotEndsWith { field, value },
+            Operator::NotRegexMatch => AST::NotRegexMatch { field, value },
+            Operator::Custom(_) => AST::Compare {
+                expr: Expr::Field(field),
+                operator,
+                value,
+            },
+        }
+    }
+
+    /// Builds the comparison between an expression and a value: the node for `operator` if the
+    /// expression is a field, or `Compare` otherwise.
+    pub(crate) fn compare(expr: Expr<P>, operator: Operator, value: Value) -> Self {
+        match expr {
+            Expr::Field(field) => AST::comparison(field, operator, value),
+            expr => AST::Compare {
+                expr,
+                operator,
+                value,
+            },
+        }
+    }
+
+    /// Returns the operator of a comparison node, or `None` for logical nodes.
+    pub fn operator(&self) -> Option<Operator> {
+        match self {
+            AST::Equals { .. } => Some(Operator::Equals),
+            AST::NotEquals { .. } => Some(Operator::NotEquals),
+            AST::In { .. } => Some(Operator::In),
+            AST::Contains { .. } => Some(Operator::Contains),
+            AST::GreaterThan { .. } => Some(Operator::GreaterThan),
+            AST::LessThan { .. } => Some(Operator::LessThan),
+            AST::GreaterThanOrEqual { .. } => Some(Operator::GreaterThanOrEqual),
+            AST::LessThanOrEqual { .. } => Some(Operator::LessThanOrEqual),
+            AST::StartsWith { .. } => Some(Operator::StartsWith),
+            AST::EndsWith { .. } => Some(Operator::EndsWith),
+            AST::RegexMatch { .. } => Some(Operator::RegexMatch),
+            AST::NotIn { .. } => Some(Operator::NotIn),
+            AST::NotContains { .. } => Some(Operator::NotContains),
+            AST::NotStartsWith { .. } => Some(Operator::NotStartsWith),
+            AST::NotEndsWith { .. } => Some(Operator::NotEndsWith),
+            AST::NotRegexMatch { .. } => Some(Operator::NotRegexMatch),
+            AST::Compare { operator, .. } | AST::Deferred { operator, .. } => {
+                Some(operator.clone())
+            }
+            AST::Between { .. }
+            | AST::Like { .. }
+            | AST::NotLike { .. }
+            | AST::InvalidField { .. }
+            | AST::And(..)
+            | AST::Or(..)
+            | AST::Not(..)
+            | AST::True
+            | AST::False => None,
+        }
+    }
+
+    /// Returns the value a comparison node compares against, or `None` for other nodes.
+    pub(crate) fn value(&self) -> Option<&Value> {
+        match self {
+            AST::Equals { value, .. }
+            | AST::NotEquals { value, .. }
+            | AST::In { value, .. }
+            | AST::NotIn { value, .. }
+            | AST::Contains { value, .. }
+            | AST::NotContains { value, .. }
+            | AST::GreaterThan { value, .. }
+            | AST::LessThan { value, .. }
+            | AST::GreaterThanOrEqual { value, .. }
+            | AST::LessThanOrEqual { value, .. }
+            | AST::StartsWith { value, .. }
+            | AST::NotStartsWith { value, .. }
+            | AST::EndsWith { value, .. }
+            | AST::NotEndsWith { value, .. }
+            | AST::RegexMatch { value, .. }
+            | AST::NotRegexMatch { value, .. }
+            | AST::Compare { value, .. } => Some(value),
+            AST::Between { .. }
+            | AST::Deferred { .. }
+            | AST::Like { .. }
+            | AST::NotLike { .. }
+            | AST::InvalidField { .. }
+            | AST::And(..)
+            | AST::Or(..)
+            | AST::Not(..)
+            | AST::True
+            | AST::False => None,
+        }
+    }
+}
+
+impl<P: Clone> AST<P> {
+    /// Returns the items that match the query. `now()` and `today()` are read once, so every item
+    /// is matched against the same time.
     pub fn apply<F: StructMatcher<P> + Clone>(&self, items: &[F]) -> Vec<F> {
+        #[cfg(feature = "chrono")]
+        let read = crate::temporal::read_clocks(self);
+        #[cfg(feature = "chrono")]
+        let ast = read.as_ref().unwrap_or(self);
+        #[cfg(not(feature = "chrono"))]
+        let ast = self;
         items
             .iter()
-            .filter(|item| item.matches_ast(self))
+            .filter(|item| item.matches_ast(ast))
             .cloned()
             .collect()
     }
 }
 
+/// The spelling used when an `AST` is written back to query text. Both styles are accepted by the
+/// parser.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, Default)]
+pub enum QueryStyle {
+    /// `&&`, `||`, `!(...)`, `==` and `!=`. This is the style used by `Display`.
+    #[default]
+    Symbols,
+
+    /// `and`, `or`, `not (...)`, `=` and `<>`.
+    Keywords,
+}
+
+impl QueryStyle {
+    fn operator<'a>(&self, operator: &'a Operator) -> &'a str {
+        match (self, operator) {
+            (QueryStyle::Keywords, Operator::Equals) => "=",
+            (QueryStyle::Keywords, Operator::NotEquals) => "<>",
+            _ => operator.as_str(),
+        }
+    }
+
+    fn and(&self) -> &'static str {
+        match self {
+            QueryStyle::Symbols => "&&",
+            QueryStyle::Keywords => "and",
+        }
+    }
+
+    fn or(&self) -> &'static str {
+        match self {
+            QueryStyle::Symbols => "||",
+            QueryStyle::Keywords => "or",
+        }
+    }
+
+    fn not(&self) -> &'static str {
+        match self {
+            QueryStyle::Symbols => "!",
+            QueryStyle::Keywords => "not ",
+        }
+    }
+}
+
+impl<P: fmt::Display> AST<P> {
+    /// Writes the AST as query text in the given style, e.g.
+    /// `ast.to_query(QueryStyle::Keywords)` gives `(age = 30) and (name <> "Bob")`.
+    pub fn to_query(&self, style: QueryStyle) -> String {
+        Styled { ast: self, style }.to_string()
+    }
+}
+
+/// Writes the AST back out in the query syntax accepted by `parse_query`, using
+/// `QueryStyle::Symbols`.
+impl<P: fmt::Display> fmt::Display for AST<P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Styled {
+            ast: self,
+            style: QueryStyle::Symbols,
+        }
+        .fmt(f)
+    }
+}
+
+struct Styled<'a, P> {
+    ast: &'a AST<P>,
+    style: QueryStyle,
+}
+
+impl<P: fmt::Display> fmt::Display for Styled<'_, P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let style = self.style;
+        let styled = |ast| Styled { ast, style };
+
+        // Operands of && and || are bracketed, except for the left-hand side of a chain, which
+        // the parser folds to the left.
+        let write_operand = |f: &mut fmt::Formatter, ast| write!(f, "({})", styled(ast));
+
+        match self.ast {
+            AST::Equals { field, value }
+            | AST::NotEquals { field, value }
+            | AST::In { field, value }
+            | AST::NotIn { field, value }
+            | AST::Contains { field, value }
+            | AST::NotContains { field, value }
+            | AST::GreaterThan { field, value }
+            | AST::LessThan { field, value }
+            | AST::GreaterThanOrEqual { field, value }
+            | AST::LessThanOrEqual { field, value }
+            | AST::StartsWith { field, value }
+            | AST::NotStartsWith { field, value }
+            | AST::EndsWith { field, value }
+            | AST::NotEndsWith { field, value }
+            | AST::RegexMatch { field, value }
+            | AST::NotRegexMatch { field, value } => {
+                let operator = self
+                    .ast
+                    .operator()
+                    .expect("comparison nodes always have an operator");
+                write!(f, "{} {} {}", field, style.operator(&operator), value)
+            }
+            AST::Between {
+                field,
+                lower: Bound::Included(lower),
+                upper: Bound::Included(upper),
+            } => write!(f, "{} between {} and {}", field, lower, upper),
+            AST::Between {
+                field,
+                lower: Bound::Included(lower),
+                upper: Bound::Excluded(upper),
+            } => write!(f, "{} in {}..{}", field, lower, upper),
+            AST::Between {
+                field,
+                lower,
+                upper,
+            } => {
+                let lower = match lower {
+                    Bound::Included(value) => Some((">=", value)),
+                    Bound::Excluded(value) => Some((">", value)),
+                    Bound::Unbounded => None,
+                };
+                let upper = match upper {
+                    Bound::Included(value) => Some(("<=", value)),
+                    Bound::Excluded(value) => Some(("<", value)),
+                    Bound::Unbounded => None,
+                };
+                match (lower, upper) {
+                    (Some((l, lower)), Some((u, upper))) => write!(
+                        f,
+                        "({} {} {}) {} ({} {} {})",
+                        field,
+                        l,
+                        lower,
+                        style.and(),
+                        field,
+                        u,
+                        upper
+                    ),
+                    (Some((op, value)), None) | (None, Some((op, value))) => {
+                        write!(f, "{} {} {}", field, op, value)
+                    }
+                    (None, None) => write!(f, "true"),
+                }
+            }
+            AST::Like { field, pattern } => write!(f, "{} {}", field, pattern),
+            AST::NotLike { field, pattern } => write!(f, "{} not {}", field, pattern),
+            AST::Compare {
+                expr,
+                operator,
+                value,
+            } => write!(f, "{} {} {}", expr, style.operator(operator), value),
+            AST::Deferred {
+                expr,
+                operator,
+                operand,
+            } => write!(f, "{} {} {}", expr, style.operator(operator), operand),
+            AST::InvalidField { field_name } => write!(f, "{}", field_name),
+            AST::And(left, right) | AST::Or(left, right) => {
+                let connector = match self.ast {
+                    AST::And(..) => style.and(),
+                    _ => style.or(),
+                };
+                match left.as_ref() {
+                    AST::And(..) | AST::Or(..) => write!(f, "{}", styled(left))?,
+                    _ => write_operand(f, left)?,
+                }
+                write!(f, " {} ", connector)?;
+                write_operand(f, right)
+            }
+            AST::Not(expr) => write!(f, "{}({})", style.not(), styled(expr)),
+            AST::True => write!(f, "true"),
+            AST::False => write!(f, "false"),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, PartialOrd, Clone)]
 pub enum Value {
     String(String),
     Int(i32),
+    Float(f64),
+    #[cfg(feature = "chrono")]
+    DateTime(chrono::DateTime<chrono::Utc>),
+    #[cfg(feature = "chrono")]
+    Date(chrono::NaiveDate),
+    #[cfg(feature = "chrono")]
+    Duration(chrono::Duration),
     VecString(Vec<String>),
     VecInt(Vec<i32>),
 }
@@ -113,6 +484,57 @@ impl Value {
     }
 }
 
+/// Writes the value in the query syntax accepted by `parse_query`.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::String(s) => write!(f, "{}", quote(s)),
+            Value::Int(n) => write!(f, "{}", n),
+            // `Debug` always writes a decimal point or an exponent, so the value reads back as a float.
+            Value::Float(n) => write!(f, "{:?}", n),
+            #[cfg(feature = "chrono")]
+            Value::DateTime(d) => write!(
+                f,
+                "{}",
+                d.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true)
+            ),
+            #[cfg(feature = "chrono")]
+            Value::Date(d) => write!(f, "{}", d.format("%Y-%m-%d")),
+            #[cfg(feature = "chrono")]
+            Value::Duration(d) => crate::temporal::format_duration(d, f),
+            Value::VecString(v) => {
+                let values: Vec<_> = v.iter().map(|s| quote(s)).collect();
+                write!(f, "[{}]", values.join(","))
+            }
+            Value::VecInt(v) => {
+                let values: Vec<_> = v.iter().map(|n| n.to_string()).collect();
+                write!(f, "[{}]", values.join(","))
+            }
+        }
+    }
+}
+
+/// Writes a string as a double-quoted literal, escaping the characters the parser would not read
+/// back verbatim.
+pub(crate) fn quote(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            '\0' => quoted.push_str("\\0"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{{{:x}}}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
 impl AsRef<Value> for Value {
     fn as_ref(&self) -> &Value {
         self
@@ -137,6 +559,39 @@ impl From<i32> for Value {
     }
 }
 
+impl From<f64> for Value {
+    fn from(val: f64) -> Self {
+        Value::Float(val)
+    }
+}
+
+impl From<f32> for Value {
+    fn from(val: f32) -> Self {
+        Value::Float(val.into())
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<chrono::DateTime<chrono::Utc>> for Value {
+    fn from(val: chrono::DateTime<chrono::Utc>) -> Self {
+        Value::DateTime(val)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<chrono::NaiveDate> for Value {
+    fn from(val: chrono::NaiveDate) -> Self {
+        Value::Date(val)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<chrono::Duration> for Value {
+    fn from(val: chrono::Duration) -> Self {
+        Value::Duration(val)
+    }
+}
+
 impl From<Vec<String>> for Value {
     fn from(val: Vec<String>) -> Self {
         Value::VecString(val)
@@ -149,97 +604,295 @@ impl From<Vec<i32>> for Value {
     }
 }
 
-fn valid_comparison_values<P>(ast: &AST<P>, value: &Value) -> Vec<Value> {
-    match ast {
-        AST::Equals { field: _, value: _ } => {
-            vec![value.clone()]
-        }
-        AST::NotEquals { field: _, value: _ } => {
-            vec![value.clone()]
+impl Value {
+    /// Returns the type of this value.
+    pub fn field_type(&self) -> FieldType {
+        match self {
+            Value::String(_) => FieldType::String,
+            Value::Int(_) => FieldType::Int,
+            Value::Float(_) => FieldType::Float,
+            #[cfg(feature = "chrono")]
+            Value::DateTime(_) => FieldType::DateTime,
+            #[cfg(feature = "chrono")]
+            Value::Date(_) => FieldType::Date,
+            #[cfg(feature = "chrono")]
+            Value::Duration(_) => FieldType::Duration,
+            Value::VecString(_) => FieldType::VecString,
+            Value::VecInt(_) => FieldType::VecInt,
         }
-        AST::GreaterThan { field: _, value: _ } => {
-            if let Value::Int(ref n) = value {
-                vec![Value::Int(*n - 1)]
-            } else {
-                vec![]
-            }
+    }
+}
+
+/// The type of a field or of the result of an expression.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum FieldType {
+    String,
+    Int,
+    Float,
+    #[cfg(feature = "chrono")]
+    DateTime,
+    #[cfg(feature = "chrono")]
+    Date,
+    #[cfg(feature = "chrono")]
+    Duration,
+    VecString,
+    VecInt,
+}
+
+impl FieldType {
+    /// Returns the default value of this type, matching `StructProperties::get_value_type`.
+    pub fn default_value(&self) -> Value {
+        match self {
+            FieldType::String => Value::String(String::default()),
+            FieldType::Int => Value::Int(i32::default()),
+            FieldType::Float => Value::Float(f64::default()),
+            #[cfg(feature = "chrono")]
+            FieldType::DateTime => Value::DateTime(Default::default()),
+            #[cfg(feature = "chrono")]
+            FieldType::Date => Value::Date(Default::default()),
+            #[cfg(feature = "chrono")]
+            FieldType::Duration => Value::Duration(Default::default()),
+            FieldType::VecString => Value::VecString(vec![]),
+            FieldType::VecInt => Value::VecInt(vec![]),
         }
-        AST::LessThan { field: _, value: _ } => {
-            if let Value::Int(ref n) = value {
-                vec![Value::Int(*n + 1)]
-            } else {
-                vec![]
-            }
+    }
+}
+
+impl fmt::Display for FieldType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// The right-hand side of a `Deferred` comparison, which does not have a value when the query is
+/// parsed.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Operand {
+    /// A `$name` placeholder, replaced with a value by `AST::bind`.
+    Param(String),
+
+    /// A time relative to `now()` or `today()`, resolved each time the query is applied.
+    #[cfg(feature = "chrono")]
+    Relative(crate::temporal::RelativeTime),
+}
+
+/// Writes the operand in the query syntax accepted by `parse_query`.
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Operand::Param(name) => write!(f, "${}", name),
+            #[cfg(feature = "chrono")]
+            Operand::Relative(time) => write!(f, "{}", time),
         }
-        AST::GreaterThanOrEqual { field: _, value: _ } => {
-            if let Value::Int(ref n) = value {
-                vec![value.clone(), Value::Int(*n - 1)]
-            } else {
-                vec![]
-            }
+    }
+}
+
+/// An enumeration of the comparison operators supported between an expression and a value.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub enum Operator {
+    Equals,
+    NotEquals,
+    In,
+    Contains,
+    GreaterThan,
+    LessThan,
+    GreaterThanOrEqual,
+    LessThanOrEqual,
+    StartsWith,
+    EndsWith,
+    RegexMatch,
+    NotIn,
+    NotContains,
+    NotStartsWith,
+    NotEndsWith,
+    NotRegexMatch,
+
+    /// An operator registered through a `Registry`.
+    Custom(CustomOperator),
+}
+
+impl Operator {
+    /// Returns the query syntax for this operator.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Operator::Equals => "==",
+            Operator::NotEquals => "!=",
+            Operator::In => "in",
+            Operator::Contains => "contains",
+            Operator::GreaterThan => ">",
+            Operator::LessThan => "<",
+            Operator::GreaterThanOrEqual => ">=",
+            Operator::LessThanOrEqual => "<=",
+            Operator::StartsWith => "startswith",
+            Operator::EndsWith => "endswith",
+            Operator::RegexMatch => "regexmatch",
+            Operator::NotIn => "not in",
+            Operator::NotContains => "not contains",
+            Operator::NotStartsWith => "not startswith",
+            Operator::NotEndsWith => "not endswith",
+            Operator::NotRegexMatch => "!~",
+            Operator::Custom(operator) => operator.name(),
         }
-        AST::LessThanOrEqual { field: _, value: _ } => {
-            if let Value::Int(ref n) = value {
-                vec![value.clone(), Value::Int(*n + 1)]
-            } else {
-                vec![]
-            }
+    }
+
+    /// Returns the operator that matches exactly the items this operator does not, if there is one.
+    pub fn negate(&self) -> Option<Operator> {
+        match self {
+            Operator::Equals => Some(Operator::NotEquals),
+            Operator::NotEquals => Some(Operator::Equals),
+            Operator::In => Some(Operator::NotIn),
+            Operator::NotIn => Some(Operator::In),
+            Operator::Contains => Some(Operator::NotContains),
+            Operator::NotContains => Some(Operator::Contains),
+            Operator::StartsWith => Some(Operator::NotStartsWith),
+            Operator::NotStartsWith => Some(Operator::StartsWith),
+            Operator::EndsWith => Some(Operator::NotEndsWith),
+            Operator::NotEndsWith => Some(Operator::EndsWith),
+            Operator::RegexMatch => Some(Operator::NotRegexMatch),
+            Operator::NotRegexMatch => Some(Operator::RegexMatch),
+            Operator::GreaterThan
+            | Operator::LessThan
+            | Operator::GreaterThanOrEqual
+            | Operator::LessThanOrEqual
+            | Operator::Custom(_) => None,
         }
-        AST::Contains { field: _, value: _ } => {
-            if let Value::String(ref s) = value {
-                vec![Value::String(s.clone())]
-            } else {
-                vec![]
+    }
+
+    /// Returns true for the negated forms of operators, e.g. `not in`.
+    pub fn is_negated(&self) -> bool {
+        matches!(
+            self,
+            Operator::NotIn
+                | Operator::NotContains
+                | Operator::NotStartsWith
+                | Operator::NotEndsWith
+                | Operator::NotRegexMatch
+        )
+    }
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// An expression that produces a value when evaluated against an item.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Expr<P> {
+    /// The value of a field.
+    Field(P),
+
+    /// A literal value.
+    Literal(Value),
+
+    /// The result of calling a function with the given arguments.
+    Call {
+        function: Function,
+        args: Vec<Expr<P>>,
+    },
+}
+
+impl<P: StructProperties> Expr<P> {
+    /// Returns the type this expression evaluates to, or `None` if a function is called with
+    /// arguments it does not accept.
+    pub fn field_type(&self) -> Option<FieldType> {
+        match self {
+            Expr::Field(field) => Some(field.get_value_type().field_type()),
+            Expr::Literal(value) => Some(value.field_type()),
+            Expr::Call { function, args } => {
+                let arg_types = args
+                    .iter()
+                    .map(|arg| arg.field_type())
+                    .collect::<Option<Vec<_>>>()?;
+                function.return_type(&arg_types)
             }
         }
-        AST::StartsWith { field: _, value: _ } => {
-            if let Value::String(ref s) = value {
-                vec![Value::String(s.clone())]
-            } else {
-                vec![]
+    }
+}
+
+impl<P: fmt::Display> fmt::Display for Expr<P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expr::Field(field) => write!(f, "{}", field),
+            Expr::Literal(value) => write!(f, "{}", value),
+            Expr::Call { function, args } => {
+                write!(f, "{}(", function)?;
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", arg)?;
+                }
+                write!(f, ")")
             }
         }
-        AST::EndsWith { field: _, value: _ } => {
-            if let Value::String(ref s) = value {
-                vec![Value::String(s.clone())]
-            } else {
-                vec![]
-            }
+    }
+}
+
+pub(crate) fn valid_comparison_values(operator: &Operator, value: &Value) -> Vec<Value> {
+    if operator.is_negated() {
+        return valid_comparison_values(&operator.negate().unwrap(), value);
+    }
+
+    match operator {
+        Operator::Equals | Operator::NotEquals => {
+            vec![value.clone()]
         }
-        AST::RegexMatch { field: _, value: _ } => {
+        Operator::GreaterThan => match value {
+            Value::Int(ref n) => vec![Value::Int(*n - 1)],
+            Value::String(_) | Value::VecString(_) | Value::VecInt(_) => vec![],
+            _ => vec![value.clone()],
+        },
+        Operator::LessThan => match value {
+            Value::Int(ref n) => vec![Value::Int(*n + 1)],
+            Value::String(_) | Value::VecString(_) | Value::VecInt(_) => vec![],
+            _ => vec![value.clone()],
+        },
+        Operator::GreaterThanOrEqual => match value {
+            Value::Int(ref n) => vec![value.clone(), Value::Int(*n - 1)],
+            Value::String(_) | Value::VecString(_) | Value::VecInt(_) => vec![],
+            _ => vec![value.clone()],
+        },
+        Operator::LessThanOrEqual => match value {
+            Value::Int(ref n) => vec![value.clone(), Value::Int(*n + 1)],
+            Value::String(_) | Value::VecString(_) | Value::VecInt(_) => vec![],
+            _ => vec![value.clone()],
+        },
+        Operator::Contains | Operator::StartsWith | Operator::EndsWith | Operator::RegexMatch => {
             if let Value::String(ref s) = value {
                 vec![Value::String(s.clone())]
             } else {
                 vec![]
             }
         }
-        AST::In { field: _, value: _ } => match value {
+        Operator::In => match value {
             Value::String(_) | Value::VecString(_) => {
                 vec![Value::String(String::default()), Value::VecString(vec![])]
             }
             Value::Int(_) | Value::VecInt(_) => {
                 vec![Value::Int(i32::default()), Value::VecInt(vec![])]
             }
+            _ => vec![],
         },
-        AST::And(left, right) => {
-            let mut values = vec![];
-            values.append(&mut valid_comparison_values(left, value));
-            values.append(&mut valid_comparison_values(right, value));
-            values
-        }
-        AST::Or(left, right) => {
-            let mut values = vec![];
-            values.append(&mut valid_comparison_values(left, value));
-            values.append(&mut valid_comparison_values(right, value));
-            values
+        Operator::NotIn
+        | Operator::NotContains
+        | Operator::NotStartsWith
+        | Operator::NotEndsWith
+        | Operator::NotRegexMatch => unreachable!(),
+        Operator::Custom(operator) => {
+            if operator.field_types().contains(&value.field_type()) {
+                vec![operator.value_type().default_value()]
+            } else {
+                vec![]
+            }
         }
-        AST::InvalidField { field_name: _ } | AST::Not { .. } => unimplemented!("This should never be called"),
     }
 }
 
 #[derive(Debug, PartialEq)]
 pub struct CompatibilityError<P: StructProperties> {
-    pub ast: AST<P>,
+    pub ast: Box<AST<P>>,
     pub field: P,
     pub provided_value: Value,
     pub valid_values: Vec<Value>,
@@ -257,6 +910,38 @@ impl<P: StructProperties> std::fmt::Display for CompatibilityError<P> {
 
 impl<P: StructProperties> std::error::Error for CompatibilityError<P> {}
 
+/// Checks that `parsed_value` can be compared using `operator` against a value of the same type
+/// as `value_type`, returning the valid value options if it cannot.
+pub(crate) fn check_comparison(
+    operator: &Operator,
+    value_type: &Value,
+    parsed_value: &Value,
+) -> Result<(), Vec<Value>> {
+    fn variants_match(a: &Value, b: &Value) -> bool {
+        std::mem::discriminant(a) == std::mem::discriminant(b)
+    }
+
+    let valid_values = valid_comparison_values(operator, value_type);
+
+    if valid_values
+        .iter()
+        .any(|value| variants_match(value, parsed_value))
+    {
+        Ok(())
+    } else {
+        Err(valid_values)
+    }
+}
+
+/// Checks that `parsed_value` can be used as a bound of a range over values of `value_type`.
+fn check_range(value_type: &Value, parsed_value: &Value) -> Result<(), Vec<Value>> {
+    match value_type {
+        Value::VecString(_) | Value::VecInt(_) => Err(vec![]),
+        _ if value_type.field_type() == parsed_value.field_type() => Ok(()),
+        _ => Err(vec![value_type.clone()]),
+    }
+}
+
 pub fn is_compatible<P: StructProperties>(
     ast: &AST<P>,
     parsed_value: &Value,
@@ -264,10 +949,6 @@ pub fn is_compatible<P: StructProperties>(
 where
     <P as FromStr>::Err: Debug,
 {
-    fn variants_match(a: &Value, b: &Value) -> bool {
-        std::mem::discriminant(a) == std::mem::discriminant(b)
-    }
-
     let (field, field_value_type) = match ast {
         AST::Equals { field, .. }
         | AST::NotEquals { field, .. }
@@ -279,28 +960,38 @@ where
         | AST::StartsWith { field, .. }
         | AST::EndsWith { field, .. }
         | AST::RegexMatch { field, .. }
-        | AST::Contains { field, .. } 
-            => (field, field.get_value_type()),
+        | AST::NotIn { field, .. }
+        | AST::NotContains { field, .. }
+        | AST::NotStartsWith { field, .. }
+        | AST::NotEndsWith { field, .. }
+        | AST::NotRegexMatch { field, .. }
+        | AST::Contains { field, .. }
+        | AST::Between { field, .. }
+        | AST::Like { field, .. }
+        | AST::NotLike { field, .. } => (field, field.get_value_type()),
         AST::And { .. }
         | AST::Or { .. }
-        | AST::InvalidField { .. } 
+        | AST::InvalidField { .. }
         | AST::Not { .. }
-            => unreachable!("This variant should not be handled"),
+        | AST::True
+        | AST::False
+        | AST::Compare { .. }
+        | AST::Deferred { .. } => unreachable!("This variant should not be handled"),
     };
 
-    let valid_values = valid_comparison_values(ast, &field_value_type);
+    let result = match (ast, ast.operator()) {
+        (_, Some(operator)) => check_comparison(&operator, &field_value_type, parsed_value),
+        (AST::Like { .. }, None) | (AST::NotLike { .. }, None) => match field_value_type {
+            Value::String(_) | Value::VecString(_) => Ok(()),
+            _ => Err(vec![]),
+        },
+        (_, None) => check_range(&field_value_type, parsed_value),
+    };
 
-    if valid_values
-        .iter()
-        .any(|value| variants_match(value, parsed_value))
-    {
-        Ok(())
-    } else {
-        Err(CompatibilityError {
-            ast: ast.clone(),
-            field: field.clone(),
-            provided_value: parsed_value.clone(),
-            valid_values,
-        })
-    }
+    result.map_err(|valid_values| CompatibilityError {
+        ast: Box::new(ast.clone()),
+        field: field.clone(),
+        provided_value: parsed_value.clone(),
+        valid_values,
+    })
 }
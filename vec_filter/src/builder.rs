@@ -0,0 +1,438 @@
+use crate::{Pattern, Value, AST};
+use std::marker::PhantomData;
+use std::ops::{BitAnd, BitOr, Bound, Not, RangeBounds};
+
+/// Returns the typed builder for queries on the fields of a struct, which
+/// `#[derive(Filterable)]` generates with `#[filterable(builder)]`.
+pub trait QueryBuilder {
+    type Builder;
+
+    fn filter() -> Self::Builder;
+}
+
+/// Builds the predicates on a field of type `T`. The builder returned by `QueryBuilder::filter`
+/// has a method returning one for each field, e.g. `Person::filter().age()`, and only the operators that are
+/// valid for the field's type are available, so a query that `parse_query` would reject does not
+/// compile:
+///
+/// ```ignore
+/// let ast = Person::filter().name().eq("Alice").and(Person::filter().age().gt(30));
+/// ```
+pub struct Field<P, T> {
+    field: P,
+    value_type: PhantomData<fn() -> T>,
+}
+
+/// A value that a field can be compared with: its `Comparable::Literal`, or a `&str` for a
+/// `String` field.
+pub trait Argument<T> {
+    fn into_argument(self) -> T;
+}
+
+impl<T> Argument<T> for T {
+    fn into_argument(self) -> T {
+        self
+    }
+}
+
+impl Argument<String> for &str {
+    fn into_argument(self) -> String {
+        self.to_string()
+    }
+}
+
+/// The types of fields that can be compared with `==` and `!=`. `Literal` is the type of the
+/// values they are compared with, which is the field's own type except for `u32` fields: query
+/// values are `i32`s, so these are compared with `i32`s, and a `u32` above `i32::MAX` cannot be
+/// passed to the builder.
+pub trait Comparable {
+    type Literal: Into<Value>;
+}
+
+impl Comparable for String {
+    type Literal = String;
+}
+impl Comparable for u32 {
+    type Literal = i32;
+}
+impl Comparable for i32 {
+    type Literal = i32;
+}
+impl Comparable for f32 {
+    type Literal = f32;
+}
+impl Comparable for f64 {
+    type Literal = f64;
+}
+#[cfg(feature = "chrono")]
+impl Comparable for chrono::DateTime<chrono::Utc> {
+    type Literal = chrono::DateTime<chrono::Utc>;
+}
+#[cfg(feature = "chrono")]
+impl Comparable for chrono::NaiveDate {
+    type Literal = chrono::NaiveDate;
+}
+#[cfg(feature = "chrono")]
+impl Comparable for chrono::Duration {
+    type Literal = chrono::Duration;
+}
+
+/// The types of fields that can be compared with `<`, `>` and `between`.
+pub trait Ordered: Comparable {}
+
+impl Ordered for u32 {}
+impl Ordered for i32 {}
+impl Ordered for f32 {}
+impl Ordered for f64 {}
+#[cfg(feature = "chrono")]
+impl Ordered for chrono::DateTime<chrono::Utc> {}
+#[cfg(feature = "chrono")]
+impl Ordered for chrono::NaiveDate {}
+#[cfg(feature = "chrono")]
+impl Ordered for chrono::Duration {}
+
+/// The types of integer fields, which can be checked against a list of integers with `is_in`.
+pub trait Integer: Ordered + Comparable<Literal = i32> {}
+
+impl Integer for u32 {}
+impl Integer for i32 {}
+
+impl<P, T> Field<P, T> {
+    pub fn new(field: P) -> Self {
+        Field {
+            field,
+            value_type: PhantomData,
+        }
+    }
+}
+
+impl<P, T: Comparable> Field<P, T> {
+    /// `field == value`
+    pub fn eq(self, value: impl Argument<T::Literal>) -> AST<P> {
+        AST::Equals {
+            field: self.field,
+            value: value.into_argument().into(),
+        }
+    }
+
+    /// `field != value`
+    pub fn ne(self, value: impl Argument<T::Literal>) -> AST<P> {
+        AST::NotEquals {
+            field: self.field,
+            value: value.into_argument().into(),
+        }
+    }
+}
+
+impl<P, T: Ordered> Field<P, T> {
+    /// `field > value`
+    pub fn gt(self, value: impl Argument<T::Literal>) -> AST<P> {
+        AST::GreaterThan {
+            field: self.field,
+            value: value.into_argument().into(),
+        }
+    }
+
+    /// `field >= value`
+    pub fn ge(self, value: impl Argument<T::Literal>) -> AST<P> {
+        AST::GreaterThanOrEqual {
+            field: self.field,
+            value: value.into_argument().into(),
+        }
+    }
+
+    /// `field < value`
+    pub fn lt(self, value: impl Argument<T::Literal>) -> AST<P> {
+        AST::LessThan {
+            field: self.field,
+            value: value.into_argument().into(),
+        }
+    }
+
+    /// `field <= value`
+    pub fn le(self, value: impl Argument<T::Literal>) -> AST<P> {
+        AST::LessThanOrEqual {
+            field: self.field,
+            value: value.into_argument().into(),
+        }
+    }
+
+    /// `field between lower and upper`, including both ends.
+    pub fn between(
+        self,
+        lower: impl Argument<T::Literal>,
+        upper: impl Argument<T::Literal>,
+    ) -> AST<P> {
+        AST::Between {
+            field: self.field,
+            lower: Bound::Included(lower.into_argument().into()),
+            upper: Bound::Included(upper.into_argument().into()),
+        }
+    }
+
+    /// `field in lower..upper` for any kind of range, e.g. `age().in_range(18..)`. The full range
+    /// `..` matches everything and gives `true`.
+    pub fn in_range(self, range: impl RangeBounds<T::Literal>) -> AST<P>
+    where
+        T::Literal: Clone,
+    {
+        if let (Bound::Unbounded, Bound::Unbounded) = (range.start_bound(), range.end_bound()) {
+            return AST::True;
+        }
+        let bound = |bound: Bound<&T::Literal>| match bound {
+            Bound::Included(value) => Bound::Included(value.clone().into()),
+            Bound::Excluded(value) => Bound::Excluded(value.clone().into()),
+            Bound::Unbounded => Bound::Unbounded,
+        };
+        AST::Between {
+            field: self.field,
+            lower: bound(range.start_bound()),
+            upper: bound(range.end_bound()),
+        }
+    }
+}
+
+impl<P, T: Integer> Field<P, T> {
+    /// `field in [values]`
+    pub fn is_in(self, values: impl IntoIterator<Item = i32>) -> AST<P> {
+        AST::In {
+            field: self.field,
+            value: Value::VecInt(values.into_iter().collect()),
+        }
+    }
+
+    /// `field not in [values]`
+    pub fn not_in(self, values: impl IntoIterator<Item = i32>) -> AST<P> {
+        AST::NotIn {
+            field: self.field,
+            value: Value::VecInt(values.into_iter().collect()),
+        }
+    }
+}
+
+impl<P> Field<P, String> {
+    /// `field contains value`
+    pub fn contains(self, value: impl Into<String>) -> AST<P> {
+        AST::Contains {
+            field: self.field,
+            value: Value::String(value.into()),
+        }
+    }
+
+    /// `field not contains value`
+    pub fn not_contains(self, value: impl Into<String>) -> AST<P> {
+        AST::NotContains {
+            field: self.field,
+            value: Value::String(value.into()),
+        }
+    }
+
+    /// `field startswith value`
+    pub fn starts_with(self, value: impl Into<String>) -> AST<P> {
+        AST::StartsWith {
+            field: self.field,
+            value: Value::String(value.into()),
+        }
+    }
+
+    /// `field not startswith value`
+    pub fn not_starts_with(self, value: impl Into<String>) -> AST<P> {
+        AST::NotStartsWith {
+            field: self.field,
+            value: Value::String(value.into()),
+        }
+    }
+
+    /// `field endswith value`
+    pub fn ends_with(self, value: impl Into<String>) -> AST<P> {
+        AST::EndsWith {
+            field: self.field,
+            value: Value::String(value.into()),
+        }
+    }
+
+    /// `field not endswith value`
+    pub fn not_ends_with(self, value: impl Into<String>) -> AST<P> {
+        AST::NotEndsWith {
+            field: self.field,
+            value: Value::String(value.into()),
+        }
+    }
+
+    /// `field regexmatch pattern`. Unlike `parse_query`, this does not check that the pattern is
+    /// a valid regular expression; an invalid one matches nothing.
+    pub fn regex_match(self, pattern: impl Into<String>) -> AST<P> {
+        AST::RegexMatch {
+            field: self.field,
+            value: Value::String(pattern.into()),
+        }
+    }
+
+    /// `field not regexmatch pattern`. See `regex_match`.
+    pub fn not_regex_match(self, pattern: impl Into<String>) -> AST<P> {
+        AST::NotRegexMatch {
+            field: self.field,
+            value: Value::String(pattern.into()),
+        }
+    }
+
+    /// `field in [values]`, which matches if the field contains any of the values.
+    pub fn is_in<S: Into<String>>(self, values: impl IntoIterator<Item = S>) -> AST<P> {
+        AST::In {
+            field: self.field,
+            value: strings(values),
+        }
+    }
+
+    /// `field not in [values]`
+    pub fn not_in<S: Into<String>>(self, values: impl IntoIterator<Item = S>) -> AST<P> {
+        AST::NotIn {
+            field: self.field,
+            value: strings(values),
+        }
+    }
+
+    /// `field like pattern`, or `glob` or `ilike`, depending on the pattern.
+    pub fn like(self, pattern: Pattern) -> AST<P> {
+        AST::Like {
+            field: self.field,
+            pattern,
+        }
+    }
+
+    /// `field not like pattern`
+    pub fn not_like(self, pattern: Pattern) -> AST<P> {
+        AST::NotLike {
+            field: self.field,
+            pattern,
+        }
+    }
+}
+
+impl<P> Field<P, Vec<String>> {
+    /// `field in [values]`, which matches if any item of the field contains any of the values.
+    pub fn is_in<S: Into<String>>(self, values: impl IntoIterator<Item = S>) -> AST<P> {
+        AST::In {
+            field: self.field,
+            value: strings(values),
+        }
+    }
+
+    /// `field not in [values]`
+    pub fn not_in<S: Into<String>>(self, values: impl IntoIterator<Item = S>) -> AST<P> {
+        AST::NotIn {
+            field: self.field,
+            value: strings(values),
+        }
+    }
+
+    /// `field like pattern`, which matches if any item of the field matches the pattern.
+    pub fn like(self, pattern: Pattern) -> AST<P> {
+        AST::Like {
+            field: self.field,
+            pattern,
+        }
+    }
+
+    /// `field not like pattern`
+    pub fn not_like(self, pattern: Pattern) -> AST<P> {
+        AST::NotLike {
+            field: self.field,
+            pattern,
+        }
+    }
+}
+
+impl<P> Field<P, Vec<i32>> {
+    /// `field in [values]`, which matches if the field has any of the values.
+    pub fn is_in(self, values: impl IntoIterator<Item = i32>) -> AST<P> {
+        AST::In {
+            field: self.field,
+            value: Value::VecInt(values.into_iter().collect()),
+        }
+    }
+
+    /// `field not in [values]`
+    pub fn not_in(self, values: impl IntoIterator<Item = i32>) -> AST<P> {
+        AST::NotIn {
+            field: self.field,
+            value: Value::VecInt(values.into_iter().collect()),
+        }
+    }
+}
+
+fn strings<S: Into<String>>(values: impl IntoIterator<Item = S>) -> Value {
+    Value::VecString(values.into_iter().map(Into::into).collect())
+}
+
+impl<P> AST<P> {
+    /// `(self) && (other)`
+    pub fn and(self, other: AST<P>) -> AST<P> {
+        AST::And(Box::new(self), Box::new(other))
+    }
+
+    /// `(self) || (other)`
+    pub fn or(self, other: AST<P>) -> AST<P> {
+        AST::Or(Box::new(self), Box::new(other))
+    }
+
+    /// `!(self)`
+    pub fn negate(self) -> AST<P> {
+        AST::Not(Box::new(self))
+    }
+
+    /// `true`, which matches every item.
+    pub fn always() -> AST<P> {
+        AST::True
+    }
+
+    /// `false`, which matches no item.
+    pub fn never() -> AST<P> {
+        AST::False
+    }
+
+    /// `(a) && (b) && ...`, or `true` if there are no operands.
+    pub fn all_of(operands: impl IntoIterator<Item = AST<P>>) -> AST<P> {
+        let mut operands = operands.into_iter();
+        match operands.next() {
+            Some(first) => operands.fold(first, AST::and),
+            None => AST::True,
+        }
+    }
+
+    /// `(a) || (b) || ...`, or `false` if there are no operands.
+    pub fn any_of(operands: impl IntoIterator<Item = AST<P>>) -> AST<P> {
+        let mut operands = operands.into_iter();
+        match operands.next() {
+            Some(first) => operands.fold(first, AST::or),
+            None => AST::False,
+        }
+    }
+}
+
+/// `a & b` is `(a) && (b)`.
+impl<P> BitAnd for AST<P> {
+    type Output = AST<P>;
+
+    fn bitand(self, other: AST<P>) -> AST<P> {
+        self.and(other)
+    }
+}
+
+/// `a | b` is `(a) || (b)`.
+impl<P> BitOr for AST<P> {
+    type Output = AST<P>;
+
+    fn bitor(self, other: AST<P>) -> AST<P> {
+        self.or(other)
+    }
+}
+
+/// `!a` is `!(a)`.
+impl<P> Not for AST<P> {
+    type Output = AST<P>;
+
+    fn not(self) -> AST<P> {
+        self.negate()
+    }
+}
@@ -0,0 +1,316 @@
+use crate::ast::valid_comparison_values;
+use crate::parsers::{ws0, Keyword, OPERATOR_KEYWORDS};
+use crate::recovery::{chunks, Chunk, Kind};
+use crate::{Expr, FieldType, Function, Operator, Parser, StructProperties};
+use nom::sequence::delimited;
+use std::fmt::Debug;
+use std::ops::Range;
+use std::str::FromStr;
+
+/// What a `Completion` inserts into the query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionKind {
+    Field,
+    /// A function name followed by an opening parenthesis, e.g. `lower(`.
+    Function,
+    Operator,
+    Value,
+    /// A logical connector between groups, e.g. `&&` or `or`.
+    Connector,
+    /// An opening parenthesis, which starts each group joined by a connector.
+    Group,
+}
+
+/// A suggestion for what can be typed at a position in a query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+    pub kind: CompletionKind,
+    pub text: String,
+    /// The byte range of the query that `text` replaces: the partially typed word at the cursor,
+    /// or an empty range at the cursor if nothing has been typed yet.
+    pub span: Range<usize>,
+}
+
+/// The built-in operators written as symbols, which are suggested first. The operators written
+/// as words and their negations follow them, in the order of `OPERATOR_KEYWORDS`.
+const SYMBOL_OPERATORS: [Operator; 6] = [
+    Operator::Equals,
+    Operator::NotEquals,
+    Operator::GreaterThan,
+    Operator::GreaterThanOrEqual,
+    Operator::LessThan,
+    Operator::LessThanOrEqual,
+];
+
+const FUNCTIONS: [Function; 6] = [
+    Function::Lower,
+    Function::Upper,
+    Function::Trim,
+    Function::Substring,
+    Function::Abs,
+    Function::Coalesce,
+];
+
+const CONNECTORS: [&str; 4] = ["&&", "||", "and", "or"];
+
+impl Parser {
+    /// Suggests what can be typed at byte offset `cursor` of a partially typed query: field and
+    /// function names where a comparison starts, the operators that apply to the type of the field
+    /// before the cursor, and connectors after a group in parentheses. Suggestions are filtered by
+    /// the word being typed at the cursor, which they replace. Input that breaks the parser's
+    /// length or nesting limits gets no suggestions.
+    pub fn complete<P: StructProperties>(&self, input: &str, cursor: usize) -> Vec<Completion>
+    where
+        <P as FromStr>::Err: Debug,
+    {
+        if self.limits.check_input::<P>(input).is_err() {
+            return vec![];
+        }
+        let prefix = match input.get(..cursor) {
+            Some(prefix) => prefix,
+            None => return vec![],
+        };
+        let chunks = chunks(prefix);
+
+        // The word or operator symbol being typed, which is completed along with any part of it
+        // after the cursor.
+        let is_symbol = |c: char| "=!<>~".contains(c);
+        let typed = match chunks.last() {
+            Some(chunk) if chunk.kind == Kind::Word => chunks.len() - 1,
+            Some(_) => chunks
+                .iter()
+                .rposition(|chunk| {
+                    !(chunk.kind == Kind::Punct
+                        && prefix[chunk.span.clone()].chars().all(is_symbol))
+                })
+                .map_or(0, |i| i + 1),
+            None => 0,
+        };
+        let start = chunks.get(typed).map_or(cursor, |chunk| chunk.span.start);
+        let rest = &input[cursor..];
+        let end = cursor
+            + match chunks.get(typed) {
+                Some(chunk) if chunk.kind == Kind::Word => {
+                    rest.len()
+                        - rest
+                            .trim_start_matches(|c: char| c.is_alphanumeric() || c == '_')
+                            .len()
+                }
+                Some(_) => rest.len() - rest.trim_start_matches(is_symbol).len(),
+                None => 0,
+            };
+        let partial = prefix[start..].to_lowercase();
+
+        let candidates = self.candidates::<P>(&prefix[..start], &chunks[..typed]);
+        candidates
+            .into_iter()
+            .filter(|(_, text)| text.to_lowercase().starts_with(&partial))
+            .map(|(kind, text)| Completion {
+                kind,
+                text,
+                span: start..end,
+            })
+            .collect()
+    }
+
+    /// Lists everything that could follow `chunks`, the tokens before the one being typed.
+    fn candidates<P: StructProperties>(
+        &self,
+        input: &str,
+        chunks: &[Chunk],
+    ) -> Vec<(CompletionKind, String)>
+    where
+        <P as FromStr>::Err: Debug,
+    {
+        let start = clause_start(input, chunks);
+        let significant: Vec<&Chunk> = chunks[start..]
+            .iter()
+            .filter(|chunk| chunk.kind != Kind::Trivia)
+            .collect();
+        let text = |chunk: &Chunk| &input[chunk.span.clone()];
+
+        if significant.is_empty() {
+            let after_connector = chunks[..start]
+                .iter()
+                .rev()
+                .find(|chunk| chunk.kind != Kind::Trivia)
+                .is_some_and(|chunk| text(chunk) != "(");
+            return match after_connector {
+                true => vec![(CompletionKind::Group, "(".to_string())],
+                false => self.targets::<P>(),
+            };
+        }
+        let group = match significant.as_slice() {
+            [not, rest @ ..] if text(not) == "!" || text(not).eq_ignore_ascii_case("not") => rest,
+            all => all,
+        };
+        match group {
+            // A group must have been closed, since the clause would otherwise be inside it.
+            [open, .., close] if text(open) == "(" && text(close) == ")" => return connectors(),
+            // Only a group can follow `not`.
+            _ if group.len() < significant.len() => return vec![],
+            _ => {}
+        }
+
+        let clause_text = &input[significant[0].span.start..];
+        let (rest, target) = match self.parse_target::<P>(clause_text) {
+            Ok((rest, Some(target))) => (rest, target),
+            _ => return vec![],
+        };
+        let value_type = match target.field_type() {
+            Some(value_type) => value_type,
+            None => return vec![],
+        };
+        // Ranges and patterns can only be applied to fields, not to function calls.
+        let is_field = matches!(target, Expr::Field(_));
+        if rest.is_empty() {
+            return self.operators(&value_type, is_field, false);
+        }
+        if rest.trim_end().eq_ignore_ascii_case("not") || rest.trim_end() == "!" {
+            return self.operators(&value_type, is_field, true);
+        }
+        if let Ok(("", _)) = delimited(ws0, |i| self.parse_operator(i), ws0)(rest) {
+            return values(&value_type);
+        }
+        vec![]
+    }
+
+    /// The fields of `P` and the functions that can start a comparison.
+    fn targets<P: StructProperties>(&self) -> Vec<(CompletionKind, String)> {
+        let fields = P::valid_fields()
+            .into_iter()
+            .map(|field| (CompletionKind::Field, field.to_string()));
+        let mut custom: Vec<String> = self
+            .registry
+            .functions()
+            .map(|function| function.name().to_string())
+            .collect();
+        custom.sort();
+        let functions = FUNCTIONS
+            .iter()
+            .map(|function| function.name().to_string())
+            .chain(custom)
+            .map(|name| (CompletionKind::Function, format!("{}(", name)));
+        fields.chain(functions).collect()
+    }
+
+    /// The operators that can compare a value of `value_type`, including ranges and patterns if it
+    /// is a field. With `negated`, only the operators that can follow `not` are listed, without the
+    /// `not`.
+    fn operators(
+        &self,
+        value_type: &FieldType,
+        is_field: bool,
+        negated: bool,
+    ) -> Vec<(CompletionKind, String)> {
+        let value = value_type.default_value();
+        let mut custom: Vec<Operator> = self
+            .registry
+            .operator_names()
+            .filter_map(|name| self.registry.operator(name).cloned().map(Operator::Custom))
+            .collect();
+        custom.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+
+        let words: Vec<Operator> = OPERATOR_KEYWORDS
+            .iter()
+            .filter_map(|(_, keyword)| match keyword {
+                Keyword::Comparison(operator) => Some(operator.clone()),
+                _ => None,
+            })
+            .collect();
+        // Only the operators written as words can be negated with `not`.
+        let comparisons: Vec<Operator> = match negated {
+            true => words,
+            false => {
+                let negations: Vec<Operator> = words.iter().filter_map(Operator::negate).collect();
+                SYMBOL_OPERATORS
+                    .iter()
+                    .cloned()
+                    .chain(words)
+                    .chain(negations)
+                    .chain(custom)
+                    .collect()
+            }
+        };
+        let comparisons = comparisons
+            .into_iter()
+            .filter(|operator| !valid_comparison_values(operator, &value).is_empty())
+            .map(|operator| operator.as_str().to_string());
+        let keywords = OPERATOR_KEYWORDS
+            .iter()
+            .filter(|(_, keyword)| match keyword {
+                Keyword::Comparison(_) => false,
+                _ if !is_field => false,
+                Keyword::Range => {
+                    !negated && !matches!(value_type, FieldType::VecString | FieldType::VecInt)
+                }
+                Keyword::Pattern(..) => {
+                    matches!(value_type, FieldType::String | FieldType::VecString)
+                }
+            })
+            .map(|(word, _)| word.to_string());
+        comparisons
+            .chain(keywords)
+            .map(|operator| (CompletionKind::Operator, operator))
+            .collect()
+    }
+}
+
+/// Returns the index of the first chunk of the clause that `chunks` ends in, which follows the last
+/// connector or unclosed parenthesis. The `and` of `between ... and ...` is not a connector.
+fn clause_start(input: &str, chunks: &[Chunk]) -> usize {
+    // The start of the clause and whether it has an unfinished `between`, for each open group.
+    let mut groups = vec![(0, false)];
+    for (i, chunk) in chunks.iter().enumerate() {
+        let text = &input[chunk.span.clone()];
+        if chunk.kind == Kind::Punct && text == "(" {
+            groups.push((i + 1, false));
+            continue;
+        } else if chunk.kind == Kind::Punct && text == ")" && groups.len() > 1 {
+            groups.pop();
+            continue;
+        }
+        let (start, between) = groups.last_mut().unwrap();
+        match (chunk.kind, text) {
+            (Kind::Word, word) if word.eq_ignore_ascii_case("between") => *between = true,
+            (Kind::Word, word) if *between && word.eq_ignore_ascii_case("and") => *between = false,
+            (Kind::Punct, "&&") | (Kind::Punct, "||") => *start = i + 1,
+            (Kind::Word, word)
+                if word.eq_ignore_ascii_case("and") || word.eq_ignore_ascii_case("or") =>
+            {
+                *start = i + 1
+            }
+            _ => {}
+        }
+    }
+    groups.last().unwrap().0
+}
+
+fn connectors() -> Vec<(CompletionKind, String)> {
+    CONNECTORS
+        .iter()
+        .map(|connector| (CompletionKind::Connector, connector.to_string()))
+        .collect()
+}
+
+/// The values that can be suggested for a field of `value_type`. Only the current date and time
+/// are known ahead of time.
+fn values(value_type: &FieldType) -> Vec<(CompletionKind, String)> {
+    match value_type {
+        #[cfg(feature = "chrono")]
+        FieldType::DateTime | FieldType::Date => vec![
+            (CompletionKind::Value, "now()".to_string()),
+            (CompletionKind::Value, "today()".to_string()),
+        ],
+        _ => vec![],
+    }
+}
+
+/// Suggests what can be typed at byte offset `cursor` of a partially typed query, using the
+/// default parser configuration. See `Parser::complete`.
+pub fn complete<P: StructProperties>(input: &str, cursor: usize) -> Vec<Completion>
+where
+    <P as FromStr>::Err: Debug,
+{
+    Parser::new().complete::<P>(input, cursor)
+}
@@ -0,0 +1,166 @@
+use crate::{CustomFunction, FieldType, Value};
+use std::fmt;
+use std::str::FromStr;
+
+/// An enumeration of the scalar functions that can be called inside a query,
+/// e.g. `lower(email) endswith "@example.com"`.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub enum Function {
+    /// Converts a string (or every string in a list) to lowercase: lower(field).
+    Lower,
+
+    /// Converts a string (or every string in a list) to uppercase: upper(field).
+    Upper,
+
+    /// Removes leading and trailing whitespace from a string (or every string in a list): trim(field).
+    Trim,
+
+    /// Takes the characters of a string starting at `start`, optionally limited to `length`
+    /// characters: substring(field, start, length).
+    Substring,
+
+    /// Returns the absolute value of a number: abs(field).
+    Abs,
+
+    /// Returns the first argument that is present and not empty: coalesce(field, field, ...).
+    Coalesce,
+
+    /// A function registered through a `Registry`.
+    Custom(CustomFunction),
+}
+
+impl Function {
+    pub fn name(&self) -> &str {
+        match self {
+            Function::Lower => "lower",
+            Function::Upper => "upper",
+            Function::Trim => "trim",
+            Function::Substring => "substring",
+            Function::Abs => "abs",
+            Function::Coalesce => "coalesce",
+            Function::Custom(function) => function.name(),
+        }
+    }
+
+    /// A human readable description of the accepted arguments, used in error messages.
+    pub fn signature(&self) -> String {
+        match self {
+            Function::Lower => "lower(String | VecString)".to_string(),
+            Function::Upper => "upper(String | VecString)".to_string(),
+            Function::Trim => "trim(String | VecString)".to_string(),
+            Function::Substring => "substring(String, Int[, Int])".to_string(),
+            Function::Abs => "abs(Int | Float)".to_string(),
+            Function::Coalesce => "coalesce(T, T, ...)".to_string(),
+            Function::Custom(function) => format!(
+                "{}({}) -> {}",
+                function.name(),
+                function
+                    .arguments()
+                    .iter()
+                    .map(|t| t.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                function.return_type()
+            ),
+        }
+    }
+
+    /// Type checks the argument types and returns the type of the value the function produces.
+    pub fn return_type(&self, arguments: &[FieldType]) -> Option<FieldType> {
+        match (self, arguments) {
+            (Function::Lower, [t]) | (Function::Upper, [t]) | (Function::Trim, [t])
+                if *t == FieldType::String || *t == FieldType::VecString =>
+            {
+                Some(*t)
+            }
+            (Function::Substring, [FieldType::String, FieldType::Int])
+            | (Function::Substring, [FieldType::String, FieldType::Int, FieldType::Int]) => {
+                Some(FieldType::String)
+            }
+            (Function::Abs, [t]) if *t == FieldType::Int || *t == FieldType::Float => Some(*t),
+            (Function::Coalesce, [first, rest @ ..]) if rest.iter().all(|t| t == first) => {
+                Some(*first)
+            }
+            (Function::Custom(function), arguments) if function.arguments() == arguments => {
+                Some(function.return_type())
+            }
+            _ => None,
+        }
+    }
+
+    /// Evaluates the function. Arguments that could not be resolved are passed as `None`.
+    pub fn call(&self, arguments: Vec<Option<Value>>) -> Option<Value> {
+        fn map_strings(value: Option<Value>, f: impl Fn(&str) -> String) -> Option<Value> {
+            match value? {
+                Value::String(s) => Some(Value::String(f(&s))),
+                Value::VecString(v) => Some(Value::VecString(v.iter().map(|s| f(s)).collect())),
+                _ => None,
+            }
+        }
+
+        if let Function::Custom(function) = self {
+            return function.call(arguments);
+        }
+
+        let mut arguments = arguments.into_iter();
+        match self {
+            Function::Lower => map_strings(arguments.next()?, str::to_lowercase),
+            Function::Upper => map_strings(arguments.next()?, str::to_uppercase),
+            Function::Trim => map_strings(arguments.next()?, |s| s.trim().to_string()),
+            Function::Substring => {
+                let (s, start, length) = match (
+                    arguments.next().flatten(),
+                    arguments.next().flatten(),
+                    arguments.next(),
+                ) {
+                    (Some(Value::String(s)), Some(Value::Int(start)), None) => (s, start, None),
+                    (
+                        Some(Value::String(s)),
+                        Some(Value::Int(start)),
+                        Some(Some(Value::Int(length))),
+                    ) => (s, start, Some(length)),
+                    _ => return None,
+                };
+                let chars = s.chars().skip(start.max(0) as usize);
+                Some(Value::String(match length {
+                    Some(length) => chars.take(length.max(0) as usize).collect(),
+                    None => chars.collect(),
+                }))
+            }
+            Function::Abs => match arguments.next()? {
+                Some(Value::Int(n)) => n.checked_abs().map(Value::Int),
+                Some(Value::Float(n)) => Some(Value::Float(n.abs())),
+                _ => None,
+            },
+            Function::Coalesce => arguments.flatten().find(|value| match value {
+                Value::String(s) => !s.is_empty(),
+                Value::VecString(v) => !v.is_empty(),
+                Value::VecInt(v) => !v.is_empty(),
+                _ => true,
+            }),
+            Function::Custom(_) => unreachable!(),
+        }
+    }
+}
+
+impl FromStr for Function {
+    type Err = ();
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_lowercase().as_str() {
+            "lower" => Ok(Function::Lower),
+            "upper" => Ok(Function::Upper),
+            "trim" => Ok(Function::Trim),
+            "substring" => Ok(Function::Substring),
+            "abs" => Ok(Function::Abs),
+            "coalesce" => Ok(Function::Coalesce),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for Function {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
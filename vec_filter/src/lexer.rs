@@ -0,0 +1,280 @@
+use crate::parsers::{operator_keyword, parse_string, parse_word, separator, ws0};
+use crate::{Parser, Registry, StructProperties};
+use std::ops::Range;
+
+/// What a `Token` is, for syntax highlighting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    Whitespace,
+    Comment,
+    Field,
+    /// A word where a field is expected that is not a field of the struct. Only
+    /// `Parser::tokenize` resolves fields; `tokenize` reports every such word as a `Field`.
+    UnknownField,
+    /// The name of a function being called, e.g. `lower` in `lower(name)`.
+    Function,
+    /// A comparison operator, including the `not` or `!` that negates it, the `and` of
+    /// `between ... and ...` and the `escape` of a pattern.
+    Operator,
+    /// `&&`, `||`, `and`, `or`, the `not` or `!` before a group, and `true` and `false`.
+    Logical,
+    String,
+    /// A number, or a date, time or duration literal.
+    Number,
+    /// A `$name` placeholder.
+    Parameter,
+    /// Parentheses, brackets, commas, the `..` of a range and the `+` or `-` of date arithmetic.
+    Punctuation,
+    /// Text that cannot be part of a query, such as an unterminated string or a stray symbol.
+    Error,
+}
+
+/// A token of a query and the byte range of the query it covers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub span: Range<usize>,
+}
+
+const SYMBOLS: [(&str, TokenKind); 16] = [
+    ("&&", TokenKind::Logical),
+    ("||", TokenKind::Logical),
+    ("==", TokenKind::Operator),
+    ("!=", TokenKind::Operator),
+    ("<>", TokenKind::Operator),
+    (">=", TokenKind::Operator),
+    ("<=", TokenKind::Operator),
+    ("!~", TokenKind::Operator),
+    ("..=", TokenKind::Punctuation),
+    ("..", TokenKind::Punctuation),
+    ("=", TokenKind::Operator),
+    (">", TokenKind::Operator),
+    ("<", TokenKind::Operator),
+    ("(", TokenKind::Punctuation),
+    (")", TokenKind::Punctuation),
+    (",", TokenKind::Punctuation),
+];
+
+impl Parser {
+    /// Splits a query into tokens like `tokenize`, and also resolves words against `P` and the
+    /// registry: words where a field is expected are marked as `UnknownField` unless they are a
+    /// field of `P`, and custom operators are recognized. Words where an operator is expected that
+    /// are not operators are marked as `Error`.
+    pub fn tokenize<P: StructProperties>(&self, input: &str) -> Vec<Token> {
+        let is_field = |word: &str| P::from_str(word).is_ok();
+        let resolve = Resolve {
+            is_field: &is_field,
+            registry: &self.registry,
+        };
+        Lexer::new(input, Some(resolve)).tokenize()
+    }
+}
+
+/// Splits a query into tokens for syntax highlighting. This never fails: text that cannot be part
+/// of a query is returned as `Error` tokens, and the tokens cover the whole input, including
+/// whitespace and comments. Words are classified by where they appear, so a word where an
+/// operator is expected is an `Operator` and any other word that is not a keyword is a `Field`.
+/// Use `Parser::tokenize` to check fields and operators.
+pub fn tokenize(input: &str) -> Vec<Token> {
+    Lexer::new(input, None).tokenize()
+}
+
+/// What words are resolved against by `Parser::tokenize`.
+struct Resolve<'a> {
+    is_field: &'a dyn Fn(&str) -> bool,
+    registry: &'a Registry,
+}
+
+struct Lexer<'a> {
+    input: &'a str,
+    resolve: Option<Resolve<'a>>,
+    /// Whether an operator is expected next, after a field, a call or the `not` before an
+    /// operator.
+    after_target: bool,
+    /// Whether the last operator was a `between` that has not been followed by its `and` yet.
+    between: bool,
+    /// For each open parenthesis, whether it starts the arguments of a call.
+    parens: Vec<bool>,
+    previous: Option<TokenKind>,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(input: &'a str, resolve: Option<Resolve<'a>>) -> Self {
+        Lexer {
+            input,
+            resolve,
+            after_target: false,
+            between: false,
+            parens: Vec::new(),
+            previous: None,
+        }
+    }
+
+    fn tokenize(mut self) -> Vec<Token> {
+        let mut tokens: Vec<Token> = Vec::new();
+        let mut rest = self.input;
+        while !rest.is_empty() {
+            let (kind, len) = self.token(rest);
+            let start = self.input.len() - rest.len();
+            match tokens.last_mut() {
+                // Runs of stray characters are reported as a single error.
+                Some(last) if kind == TokenKind::Error && last.kind == TokenKind::Error => {
+                    last.span.end = start + len
+                }
+                _ => tokens.push(Token {
+                    kind,
+                    span: start..start + len,
+                }),
+            }
+            if !matches!(kind, TokenKind::Whitespace | TokenKind::Comment) {
+                self.previous = Some(kind);
+            }
+            rest = &rest[len..];
+        }
+        tokens
+    }
+
+    /// Returns the kind and length of the token at the start of `input`, which is not empty.
+    fn token(&mut self, input: &str) -> (TokenKind, usize) {
+        let c = input.chars().next().unwrap();
+        if let Ok((after, _)) = separator(input) {
+            let kind = match c.is_whitespace() {
+                true => TokenKind::Whitespace,
+                false => TokenKind::Comment,
+            };
+            return (kind, input.len() - after.len());
+        }
+        if input.starts_with("/*") {
+            // An unterminated comment.
+            return (TokenKind::Error, input.len());
+        }
+
+        let after_target = std::mem::replace(&mut self.after_target, false);
+        if c == '"' || c == '\'' || input.starts_with("r\"") || input.starts_with("r#") {
+            if let Ok((after, _)) = parse_string(input) {
+                return (TokenKind::String, input.len() - after.len());
+            }
+            if c == '"' || c == '\'' {
+                return (TokenKind::Error, invalid_string(input, c));
+            }
+            if input.trim_start_matches(['r', '#']).starts_with('"') {
+                return (TokenKind::Error, input.len());
+            }
+        }
+        if let Some(name) = input.strip_prefix('$') {
+            return match parse_word(name) {
+                Ok((after, _)) => (TokenKind::Parameter, input.len() - after.len()),
+                Err(_) => (TokenKind::Error, 1),
+            };
+        }
+        let signed = input.strip_prefix(['-', '+']);
+        if c.is_ascii_digit()
+            || signed.is_some_and(|rest| rest.starts_with(|c: char| c.is_ascii_digit()))
+        {
+            return (TokenKind::Number, number(input));
+        }
+        if let Ok((after, word)) = parse_word(input) {
+            return (self.word(word, after, after_target), word.len());
+        }
+        if let Some((symbol, kind)) = SYMBOLS.iter().find(|(symbol, _)| input.starts_with(symbol)) {
+            match *symbol {
+                "(" => self.parens.push(self.previous == Some(TokenKind::Function)),
+                ")" => {
+                    let closes_call = self.parens.pop() == Some(true);
+                    self.after_target = closes_call && !self.parens.contains(&true);
+                }
+                _ => {}
+            }
+            return (*kind, symbol.len());
+        }
+        if c == '!' {
+            self.after_target = after_target;
+            let kind = match after_target {
+                true => TokenKind::Operator,
+                false => TokenKind::Logical,
+            };
+            return (kind, 1);
+        }
+        match c {
+            '[' | ']' | '+' | '-' => (TokenKind::Punctuation, 1),
+            _ => (TokenKind::Error, c.len_utf8()),
+        }
+    }
+
+    /// Classifies a word by what it is and where it appears. `after` is the input following it.
+    fn word(&mut self, word: &str, after: &str, after_target: bool) -> TokenKind {
+        let lower = word.to_lowercase();
+        match lower.as_str() {
+            "and" if self.between => {
+                self.between = false;
+                return TokenKind::Operator;
+            }
+            "and" | "or" => return TokenKind::Logical,
+            "not" if after_target => {
+                self.after_target = true;
+                return TokenKind::Operator;
+            }
+            "not" => return TokenKind::Logical,
+            "true" | "false" if !after_target => return TokenKind::Logical,
+            "between" => {
+                self.between = true;
+                return TokenKind::Operator;
+            }
+            "escape" if self.previous == Some(TokenKind::String) => return TokenKind::Operator,
+            _ if operator_keyword(&lower).is_some() => return TokenKind::Operator,
+            _ => {}
+        }
+        if let Ok((after, _)) = ws0(after) {
+            if after.starts_with('(') {
+                return TokenKind::Function;
+            }
+        }
+        if after_target {
+            return match self.resolve {
+                Some(Resolve { registry, .. }) if registry.operator(word).is_none() => {
+                    TokenKind::Error
+                }
+                _ => TokenKind::Operator,
+            };
+        }
+
+        // A field is the target of a comparison unless it is an argument of a call.
+        self.after_target = !self.parens.contains(&true);
+        match self.resolve {
+            Some(Resolve { is_field, .. }) if !is_field(word) => TokenKind::UnknownField,
+            _ => TokenKind::Field,
+        }
+    }
+}
+
+/// Returns the length of a string literal that `parse_string` rejected, up to its closing quote or
+/// the end of the input.
+fn invalid_string(input: &str, quote: char) -> usize {
+    let mut chars = input.char_indices().skip(1);
+    while let Some((i, c)) = chars.next() {
+        if c == '\\' {
+            chars.next();
+        } else if c == quote {
+            return i + 1;
+        }
+    }
+    input.len()
+}
+
+/// Returns the length of the numeric literal at the start of `input`, which may be signed and may
+/// be a float, a date, a time or a duration, but does not include the `..` of a range.
+fn number(input: &str) -> usize {
+    let mut end = 1;
+    for (i, c) in input.char_indices().skip(1) {
+        let continues = c.is_alphanumeric()
+            || c == '_'
+            || c == ':'
+            || (c == '.' && !input[i..].starts_with(".."))
+            || ((c == '-' || c == '+') && !input[..i].ends_with(['-', '+']));
+        if !continues {
+            break;
+        }
+        end = i + c.len_utf8();
+    }
+    end
+}
@@ -1,12 +1,44 @@
 #[allow(unused_imports)]
 #[macro_use]
 extern crate vec_filter_derive;
-pub use vec_filter_derive::Filterable;
+pub use vec_filter_derive::{filter, Filterable};
 
+mod analysis;
 mod ast;
+mod builder;
+mod complete;
+mod functions;
+mod lexer;
+mod limits;
+mod optimize;
+mod params;
 mod parsers;
+mod pattern;
+mod policy;
+mod recovery;
+mod registry;
+mod simplify;
 mod struct_matcher;
+mod suggest;
+#[cfg(feature = "chrono")]
+mod temporal;
+mod visit;
 
 pub use crate::struct_matcher::{StructMatcher, StructProperties};
-pub use ast::{CompatibilityError, Value, AST};
-pub use parsers::{parse_query, ASTValidationError, FieldNotFound};
+pub use analysis::Warning;
+pub use ast::{CompatibilityError, Expr, FieldType, Operand, Operator, QueryStyle, Value, AST};
+pub use builder::{Argument, Comparable, Field, Integer, Ordered, QueryBuilder};
+pub use complete::{complete, Completion, CompletionKind};
+pub use functions::Function;
+pub use lexer::{tokenize, Token, TokenKind};
+pub use parsers::{parse_query, ASTValidationError, FieldNotFound, Parser};
+pub use pattern::{Pattern, PatternSyntax};
+pub use policy::{AuthorizationError, QueryPolicy};
+pub use recovery::{Diagnostic, ParseOutput};
+pub use registry::{CustomFunction, CustomOperator, Registry};
+#[cfg(feature = "chrono")]
+pub use temporal::{Anchor, RelativeTime};
+pub use visit::{
+    fold_ast, fold_expr, walk_ast, walk_ast_mut, walk_expr, walk_expr_mut, walk_predicate,
+    walk_predicate_mut, Fold, Visitor, VisitorMut,
+};
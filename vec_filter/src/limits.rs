@@ -0,0 +1,240 @@
+use crate::{
+    ASTValidationError, Expr, Function, Operator, Parser, PatternSyntax, StructProperties, Value,
+    AST,
+};
+use regex::RegexBuilder;
+use std::ops::Bound;
+
+/// Limits on the size and content of queries, set through the `Parser` builder methods. `None`
+/// means unlimited.
+#[derive(Debug, Clone)]
+pub(crate) struct Limits {
+    pub(crate) max_input_length: Option<usize>,
+    pub(crate) max_depth: Option<usize>,
+    pub(crate) max_list_length: Option<usize>,
+    pub(crate) max_regex_size: Option<usize>,
+    pub(crate) allowed_operators: Option<Vec<Operator>>,
+    pub(crate) allowed_patterns: Option<Vec<PatternSyntax>>,
+    pub(crate) allowed_functions: Option<Vec<Function>>,
+    pub(crate) allow_ranges: bool,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_input_length: None,
+            max_depth: Some(Parser::DEFAULT_MAX_DEPTH),
+            max_list_length: None,
+            max_regex_size: None,
+            allowed_operators: None,
+            allowed_patterns: None,
+            allowed_functions: None,
+            allow_ranges: true,
+        }
+    }
+}
+
+impl Limits {
+    /// Checks the limits that can be enforced on the raw input. These run before parsing, so that
+    /// deeply nested input is rejected before it can exhaust the stack.
+    pub(crate) fn check_input<P: StructProperties>(
+        &self,
+        input: &str,
+    ) -> Result<(), ASTValidationError<P>> {
+        if let Some(max) = self.max_input_length {
+            if input.len() > max {
+                return Err(ASTValidationError::InputTooLong {
+                    length: input.len(),
+                    max,
+                });
+            }
+        }
+        if let Some(max) = self.max_depth {
+            let depth = nesting_depth(input);
+            if depth > max {
+                return Err(ASTValidationError::NestingTooDeep { depth, max });
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the limits on the operators, patterns, ranges, functions, lists and regular
+    /// expressions in a parsed AST.
+    pub(crate) fn check_ast<P: StructProperties>(
+        &self,
+        ast: &AST<P>,
+        errors: &mut Vec<ASTValidationError<P>>,
+    ) {
+        if let Some(operator) = ast.operator() {
+            if let Some(allowed) = &self.allowed_operators {
+                if !allowed.contains(&operator) {
+                    errors.push(ASTValidationError::OperatorNotAllowed { operator });
+                }
+            }
+        }
+
+        match ast {
+            AST::Equals { value, .. }
+            | AST::NotEquals { value, .. }
+            | AST::In { value, .. }
+            | AST::NotIn { value, .. }
+            | AST::Contains { value, .. }
+            | AST::NotContains { value, .. }
+            | AST::GreaterThan { value, .. }
+            | AST::LessThan { value, .. }
+            | AST::GreaterThanOrEqual { value, .. }
+            | AST::LessThanOrEqual { value, .. }
+            | AST::StartsWith { value, .. }
+            | AST::NotStartsWith { value, .. }
+            | AST::EndsWith { value, .. }
+            | AST::NotEndsWith { value, .. } => self.check_value(value, errors),
+            AST::RegexMatch { value, .. } | AST::NotRegexMatch { value, .. } => {
+                self.check_value(value, errors);
+                self.check_regex(value, errors);
+            }
+            AST::Compare { expr, value, .. } => {
+                self.check_expr(expr, errors);
+                self.check_value(value, errors);
+            }
+            AST::Deferred { expr, .. } => self.check_expr(expr, errors),
+            AST::Between { lower, upper, .. } => {
+                if !self.allow_ranges {
+                    errors.push(ASTValidationError::RangeNotAllowed);
+                }
+                for bound in [lower, upper] {
+                    if let Bound::Included(value) | Bound::Excluded(value) = bound {
+                        self.check_value(value, errors);
+                    }
+                }
+            }
+            AST::And(left, right) | AST::Or(left, right) => {
+                self.check_ast(left, errors);
+                self.check_ast(right, errors);
+            }
+            AST::Not(ast) => self.check_ast(ast, errors),
+            AST::Like { pattern, .. } | AST::NotLike { pattern, .. } => {
+                let syntax = pattern.syntax();
+                if let Some(allowed) = &self.allowed_patterns {
+                    if !allowed.contains(&syntax) {
+                        errors.push(ASTValidationError::PatternNotAllowed { syntax });
+                    }
+                }
+            }
+            AST::InvalidField { .. } | AST::True | AST::False => {}
+        }
+    }
+
+    fn check_expr<P: StructProperties>(
+        &self,
+        expr: &Expr<P>,
+        errors: &mut Vec<ASTValidationError<P>>,
+    ) {
+        match expr {
+            Expr::Field(_) => {}
+            Expr::Literal(value) => self.check_value(value, errors),
+            Expr::Call { function, args } => {
+                if let Some(allowed) = &self.allowed_functions {
+                    if !allowed.contains(function) {
+                        errors.push(ASTValidationError::FunctionNotAllowed {
+                            function: function.clone(),
+                        });
+                    }
+                }
+                for arg in args {
+                    self.check_expr(arg, errors);
+                }
+            }
+        }
+    }
+
+    fn check_value<P: StructProperties>(
+        &self,
+        value: &Value,
+        errors: &mut Vec<ASTValidationError<P>>,
+    ) {
+        let length = match value {
+            Value::VecString(values) => values.len(),
+            Value::VecInt(values) => values.len(),
+            _ => return,
+        };
+        if let Some(max) = self.max_list_length {
+            if length > max {
+                errors.push(ASTValidationError::ListTooLong { length, max });
+            }
+        }
+    }
+
+    fn check_regex<P: StructProperties>(
+        &self,
+        value: &Value,
+        errors: &mut Vec<ASTValidationError<P>>,
+    ) {
+        if let (Some(max), Value::String(pattern)) = (self.max_regex_size, value) {
+            // Invalid expressions are left alone, since they already never match.
+            if let Err(regex::Error::CompiledTooBig(_)) =
+                RegexBuilder::new(pattern).size_limit(max).build()
+            {
+                errors.push(ASTValidationError::RegexTooLarge {
+                    pattern: pattern.clone(),
+                    max,
+                });
+            }
+        }
+    }
+}
+
+/// Returns the deepest nesting of parentheses in the input, ignoring any inside string literals
+/// and comments. This does not need to parse the query, so it is safe to run on any input.
+fn nesting_depth(input: &str) -> usize {
+    let bytes = input.as_bytes();
+    let (mut depth, mut deepest) = (0usize, 0);
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'(' => {
+                depth += 1;
+                deepest = deepest.max(depth);
+            }
+            b')' => depth = depth.saturating_sub(1),
+            quote @ b'"' | quote @ b'\'' => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != quote {
+                    if bytes[i] == b'\\' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+            }
+            b'r' if i == 0 || !is_word_byte(bytes[i - 1]) => {
+                let hashes = bytes[i + 1..].iter().take_while(|b| **b == b'#').count();
+                if bytes.get(i + 1 + hashes) == Some(&b'"') {
+                    let body = &input[i + 2 + hashes..];
+                    let terminator = format!("\"{}", "#".repeat(hashes));
+                    i = match body.find(&terminator) {
+                        Some(end) => input.len() - body.len() + end + terminator.len() - 1,
+                        None => input.len(),
+                    };
+                }
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                i = match input[i..].find('\n') {
+                    Some(end) => i + end,
+                    None => input.len(),
+                };
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i = match input[i + 2..].find("*/") {
+                    Some(end) => i + 2 + end + 1,
+                    None => input.len(),
+                };
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    deepest
+}
+
+fn is_word_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_'
+}
@@ -0,0 +1,241 @@
+use crate::simplify::Connective;
+use crate::{Expr, Function, Operator, StructMatcher, Value, AST};
+use std::cmp::Ordering;
+
+/// The cost of comparing two plain values, the unit of the other costs.
+const COMPARISON_COST: f64 = 1.0;
+/// Scanning a string for a substring, prefix or suffix.
+const SCAN_COST: f64 = 2.0;
+/// Calling a built-in function.
+const CALL_COST: f64 = 5.0;
+/// Matching a `like` or `glob` pattern, which is compiled when the query is parsed.
+const PATTERN_COST: f64 = 10.0;
+/// Calling a custom operator or function, whose cost is unknown.
+const CUSTOM_COST: f64 = 10.0;
+/// Matching a regular expression, which is compiled for every item.
+const REGEX_COST: f64 = 100.0;
+
+/// Estimates the fraction of items a predicate matches.
+trait Selectivity<P> {
+    fn predicate(&self, ast: &AST<P>) -> f64;
+
+    /// The selectivity of `ast`, a chain of `connective` whose operands have the given
+    /// selectivities.
+    fn chain(&self, ast: &AST<P>, connective: Connective, operands: &[f64]) -> f64;
+}
+
+/// Fixed estimates for each operator, assuming predicates are independent.
+struct Estimates;
+
+impl<P> Selectivity<P> for Estimates {
+    fn predicate(&self, ast: &AST<P>) -> f64 {
+        let size = ast.value().map_or(1, list_size).max(1) as f64;
+        match ast {
+            AST::Equals { .. } => 0.1,
+            AST::NotEquals { .. } => 0.9,
+            AST::In { .. } => (0.1 * size).min(0.9),
+            AST::NotIn { .. } => 1.0 - (0.1 * size).min(0.9),
+            AST::GreaterThan { .. }
+            | AST::LessThan { .. }
+            | AST::GreaterThanOrEqual { .. }
+            | AST::LessThanOrEqual { .. } => 1.0 / 3.0,
+            AST::Between { .. } => 0.25,
+            AST::Contains { .. }
+            | AST::StartsWith { .. }
+            | AST::EndsWith { .. }
+            | AST::RegexMatch { .. }
+            | AST::Like { .. } => 0.25,
+            AST::NotContains { .. }
+            | AST::NotStartsWith { .. }
+            | AST::NotEndsWith { .. }
+            | AST::NotRegexMatch { .. }
+            | AST::NotLike { .. } => 0.75,
+            AST::True => 1.0,
+            AST::False => 0.0,
+            _ => 0.5,
+        }
+    }
+
+    fn chain(&self, _: &AST<P>, connective: Connective, operands: &[f64]) -> f64 {
+        match connective {
+            Connective::And => operands.iter().product(),
+            Connective::Or => 1.0 - operands.iter().map(|s| 1.0 - s).product::<f64>(),
+        }
+    }
+}
+
+/// The fraction of a sample of items that each predicate matches.
+struct Sample<'a, T>(&'a [T]);
+
+impl<T> Sample<'_, T> {
+    fn measure<P>(&self, ast: &AST<P>) -> f64
+    where
+        T: StructMatcher<P>,
+    {
+        // Smoothed, so that a predicate no item in the sample matches still has some chance.
+        let matches = self.0.iter().filter(|item| item.matches_ast(ast)).count();
+        (matches as f64 + 1.0) / (self.0.len() as f64 + 2.0)
+    }
+}
+
+impl<P, T: StructMatcher<P>> Selectivity<P> for Sample<'_, T> {
+    fn predicate(&self, ast: &AST<P>) -> f64 {
+        self.measure(ast)
+    }
+
+    fn chain(&self, ast: &AST<P>, _: Connective, _: &[f64]) -> f64 {
+        self.measure(ast)
+    }
+}
+
+impl<P> AST<P> {
+    /// Returns the expected cost of checking an item against the query, in units of a comparison
+    /// between two plain values. Operands of `&&` and `||` are checked in order and only as long
+    /// as the result is undecided, so the cost depends on their order.
+    pub fn cost(&self) -> f64 {
+        estimate(self, &Estimates).0
+    }
+}
+
+impl<P: Clone> AST<P> {
+    /// Returns an equivalent AST with the operands of each chain of `&&` and `||` reordered so that
+    /// predicates that are cheap to check and likely to decide the result are checked first, e.g.
+    /// `id == 5` before `description regexmatch "..."`. Each operator has a fixed cost, with
+    /// regular expressions the most expensive and lists costing more the longer they are, and a
+    /// fixed estimate of how many items it matches.
+    ///
+    /// Chains are rebuilt as balanced trees, however they were nested.
+    pub fn optimize(&self) -> AST<P> {
+        reorder(self.clone(), &Estimates)
+    }
+
+    /// Like `optimize`, but estimates how many items each part of the query matches by checking
+    /// it against a sample of the items it will be applied to.
+    pub fn optimize_with_sample<T: StructMatcher<P>>(&self, sample: &[T]) -> AST<P> {
+        reorder(self.clone(), &Sample(sample))
+    }
+}
+
+/// Returns the expected cost of checking an item against `ast`, and the fraction of items it
+/// matches.
+fn estimate<P, S: Selectivity<P>>(ast: &AST<P>, selectivity: &S) -> (f64, f64) {
+    if let AST::Not(inner) = ast {
+        let (cost, inner) = estimate(inner, selectivity);
+        return (cost, 1.0 - inner);
+    }
+    let connective = match Connective::of(ast) {
+        Some(connective) => connective,
+        None => return (predicate_cost(ast), selectivity.predicate(ast)),
+    };
+    let operands = match connective {
+        Connective::And => ast.conjuncts(),
+        Connective::Or => ast.disjuncts(),
+    };
+
+    let (mut cost, mut reached) = (0.0, 1.0);
+    let mut selectivities = Vec::with_capacity(operands.len());
+    for operand in operands {
+        let (operand_cost, operand_selectivity) = estimate(operand, selectivity);
+        cost += reached * operand_cost;
+        reached *= undecided(connective, operand_selectivity);
+        selectivities.push(operand_selectivity);
+    }
+    (cost, selectivity.chain(ast, connective, &selectivities))
+}
+
+/// The chance that an operand of a chain of `connective` that matches `selectivity` of the items
+/// leaves the result undecided, so the next operand has to be checked.
+fn undecided(connective: Connective, selectivity: f64) -> f64 {
+    match connective {
+        Connective::And => selectivity,
+        Connective::Or => 1.0 - selectivity,
+    }
+}
+
+fn reorder<P, S: Selectivity<P>>(ast: AST<P>, selectivity: &S) -> AST<P> {
+    if let AST::Not(inner) = ast {
+        return AST::Not(Box::new(reorder(*inner, selectivity)));
+    }
+    let connective = match Connective::of(&ast) {
+        Some(connective) => connective,
+        None => return ast,
+    };
+    let mut chain = Vec::new();
+    connective.flatten(ast, &mut chain);
+
+    // Checking operands in increasing order of cost per chance of deciding the result minimizes
+    // the expected cost when they are independent.
+    let mut operands: Vec<(f64, AST<P>)> = chain
+        .into_iter()
+        .map(|operand| {
+            let operand = reorder(operand, selectivity);
+            let (cost, operand_selectivity) = estimate(&operand, selectivity);
+            let decided = 1.0 - undecided(connective, operand_selectivity);
+            (cost / decided.max(f64::EPSILON), operand)
+        })
+        .collect();
+    operands.sort_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    connective.join(operands.into_iter().map(|(_, operand)| operand).collect())
+}
+
+fn predicate_cost<P>(ast: &AST<P>) -> f64 {
+    match ast {
+        AST::Compare {
+            expr,
+            operator,
+            value,
+        } => expr_cost(expr) + operator_cost(operator, value),
+        AST::Deferred { expr, .. } => expr_cost(expr) + COMPARISON_COST,
+        AST::Between { .. } => 2.0 * COMPARISON_COST,
+        AST::Like { .. } | AST::NotLike { .. } => PATTERN_COST,
+        AST::InvalidField { .. } | AST::True | AST::False => 0.0,
+        ast => match (ast.operator(), ast.value()) {
+            (Some(operator), Some(value)) => operator_cost(&operator, value),
+            _ => COMPARISON_COST,
+        },
+    }
+}
+
+fn operator_cost(operator: &Operator, value: &Value) -> f64 {
+    let cost = match operator {
+        Operator::Equals
+        | Operator::NotEquals
+        | Operator::In
+        | Operator::NotIn
+        | Operator::GreaterThan
+        | Operator::LessThan
+        | Operator::GreaterThanOrEqual
+        | Operator::LessThanOrEqual => COMPARISON_COST,
+        Operator::Contains
+        | Operator::NotContains
+        | Operator::StartsWith
+        | Operator::NotStartsWith
+        | Operator::EndsWith
+        | Operator::NotEndsWith => SCAN_COST,
+        Operator::RegexMatch | Operator::NotRegexMatch => REGEX_COST,
+        Operator::Custom(_) => CUSTOM_COST,
+    };
+    // Each item of a list is compared in turn.
+    cost * list_size(value).max(1) as f64
+}
+
+fn expr_cost<P>(expr: &Expr<P>) -> f64 {
+    match expr {
+        Expr::Field(_) | Expr::Literal(_) => 0.0,
+        Expr::Call { function, args } => {
+            let cost = match function {
+                Function::Custom(_) => CUSTOM_COST,
+                _ => CALL_COST,
+            };
+            cost + args.iter().map(expr_cost).sum::<f64>()
+        }
+    }
+}
+
+fn list_size(value: &Value) -> usize {
+    match value {
+        Value::VecString(values) => values.len(),
+        Value::VecInt(values) => values.len(),
+        _ => 1,
+    }
+}
@@ -0,0 +1,112 @@
+use crate::parsers::{coerce, validate_ast};
+use crate::{
+    ASTValidationError, Operand, Parser, StructProperties, Value, Visitor, VisitorMut, AST,
+};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Debug;
+use std::str::FromStr;
+
+impl<P: StructProperties> AST<P> {
+    /// Returns the names of the `$name` parameters used in the query, sorted and without
+    /// duplicates.
+    pub fn params(&self) -> Vec<String> {
+        struct Params<'ast>(BTreeSet<&'ast str>);
+
+        impl<'ast, P: 'ast> Visitor<'ast, P> for Params<'ast> {
+            fn visit_operand(&mut self, operand: &'ast Operand) {
+                match operand {
+                    Operand::Param(name) => {
+                        self.0.insert(name);
+                    }
+                    #[cfg(feature = "chrono")]
+                    Operand::Relative(_) => {}
+                }
+            }
+        }
+
+        let mut params = Params(BTreeSet::new());
+        params.visit_ast(self);
+        params.0.into_iter().map(str::to_string).collect()
+    }
+
+    /// Replaces every `$name` parameter with the value bound to `name`, e.g. binding
+    /// `"min_age" => Value::Int(30)` to `age > $min_age` gives `age > 30`. Bound values are type
+    /// checked against the fields they are compared with in the same way as literals in the query.
+    /// Every parameter must be bound and every bound value must be used, otherwise a
+    /// `ParameterMismatch` error lists the differences. Bound values are not checked against a
+    /// parser's limits, such as `max_list_length`; use `Parser::bind` for that.
+    pub fn bind(&self, params: &HashMap<&str, Value>) -> Result<AST<P>, Vec<ASTValidationError<P>>>
+    where
+        <P as FromStr>::Err: Debug,
+    {
+        let used = self.params();
+        let unbound: Vec<String> = used
+            .iter()
+            .filter(|name| !params.contains_key(name.as_str()))
+            .cloned()
+            .collect();
+        let mut extra: Vec<String> = params
+            .keys()
+            .filter(|name| !used.iter().any(|used| used == *name))
+            .map(|name| name.to_string())
+            .collect();
+        extra.sort();
+        if !unbound.is_empty() || !extra.is_empty() {
+            return Err(vec![ASTValidationError::ParameterMismatch {
+                unbound,
+                extra,
+            }]);
+        }
+
+        let mut ast = self.clone();
+        Bind(params).visit_ast_mut(&mut ast);
+
+        let mut errors = Vec::new();
+        validate_ast(&ast, &mut errors);
+        if errors.is_empty() {
+            Ok(ast)
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl Parser {
+    /// Binds values to the `$name` parameters of `ast` like `AST::bind`, then checks the bound
+    /// query against the parser's limits, so that a parameter cannot pass a list or regular
+    /// expression that the parser would reject in the query text.
+    pub fn bind<P: StructProperties>(
+        &self,
+        ast: &AST<P>,
+        params: &HashMap<&str, Value>,
+    ) -> Result<AST<P>, Vec<ASTValidationError<P>>>
+    where
+        <P as FromStr>::Err: Debug,
+    {
+        let ast = ast.bind(params)?;
+        let mut errors = Vec::new();
+        self.limits.check_ast(&ast, &mut errors);
+        if errors.is_empty() {
+            Ok(ast)
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// Replaces each comparison against a parameter with a comparison against its bound value.
+struct Bind<'a>(&'a HashMap<&'a str, Value>);
+
+impl<P: StructProperties> VisitorMut<P> for Bind<'_> {
+    fn visit_predicate_mut(&mut self, ast: &mut AST<P>) {
+        if let AST::Deferred {
+            expr,
+            operator,
+            operand: Operand::Param(name),
+        } = ast
+        {
+            let value = coerce(self.0[name.as_str()].clone(), expr.field_type());
+            *ast = AST::compare(expr.clone(), operator.clone(), value);
+        }
+    }
+}
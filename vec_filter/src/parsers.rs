@@ -1,17 +1,27 @@
-use crate::ast::{is_compatible, CompatibilityError};
-use crate::{StructProperties, Value, AST};
+use crate::ast::{check_comparison, is_compatible, CompatibilityError};
+use crate::limits::Limits;
+use crate::suggest::suggest;
+#[cfg(feature = "chrono")]
+use crate::temporal;
+use crate::{
+    Expr, FieldType, Function, Operand, Operator, Pattern, PatternSyntax, Registry,
+    StructProperties, Value, AST,
+};
 use core::fmt::Debug;
 use nom::{
     branch::alt,
-    bytes::complete::{tag, take_while1},
-    character::complete::{alphanumeric1, digit1, space0},
-    combinator::{map, map_res, recognize},
-    multi::{many0, separated_list1},
-    sequence::{delimited, tuple},
+    bytes::complete::{tag, tag_no_case, take_until, take_while, take_while1},
+    character::complete::{char, one_of, satisfy},
+    combinator::{consumed, map, map_opt, not, opt, peek, recognize, value, verify},
+    error::ErrorKind,
+    multi::{many0, many0_count, many1_count, separated_list0, separated_list1},
+    sequence::{delimited, pair, preceded, terminated, tuple},
     Err, IResult,
 };
+use std::convert::TryFrom;
 use std::error::Error;
 use std::fmt;
+use std::ops::{Bound, Range};
 use std::str::FromStr;
 
 #[derive(Debug)]
@@ -35,238 +45,1266 @@ impl fmt::Display for FieldNotFound {
 
 impl Error for FieldNotFound {}
 
+/// Errors raised by the parsers, in addition to nom's own syntax errors.
+#[derive(Debug, PartialEq)]
+pub(crate) enum ParseError<I> {
+    Nom(I, ErrorKind),
+    UnknownFunction {
+        name: String,
+    },
+    UnknownOperator {
+        operator: String,
+        suggestions: Vec<String>,
+    },
+    InvalidField {
+        field_name: String,
+    },
+    InvalidPattern {
+        pattern: String,
+        message: String,
+    },
+    NumberOutOfRange {
+        literal: I,
+    },
+}
+
+impl<I> nom::error::ParseError<I> for ParseError<I> {
+    fn from_error_kind(input: I, kind: ErrorKind) -> Self {
+        ParseError::Nom(input, kind)
+    }
+
+    fn append(_: I, _: ErrorKind, other: Self) -> Self {
+        other
+    }
+}
+
+pub(crate) type ParseResult<'a, T> = IResult<&'a str, T, ParseError<&'a str>>;
+
+/// Skips a run of whitespace (including newlines) or a `// line` or `/* block */` comment.
+pub(crate) fn separator(input: &str) -> ParseResult<'_, &str> {
+    alt((
+        take_while1(char::is_whitespace),
+        recognize(pair(tag("//"), take_while(|c| c != '\n'))),
+        recognize(tuple((tag("/*"), take_until("*/"), tag("*/")))),
+    ))(input)
+}
+
+/// Skips any whitespace and comments between tokens.
+pub(crate) fn ws0(input: &str) -> ParseResult<'_, &str> {
+    recognize(many0_count(separator))(input)
+}
+
+/// Skips whitespace and comments between tokens, requiring at least one separator.
+pub(crate) fn ws1(input: &str) -> ParseResult<'_, &str> {
+    recognize(many1_count(separator))(input)
+}
+
+/// A value on the right-hand side of an operator, or an operand that has no value yet.
+pub(crate) enum Rhs {
+    Value(Value),
+    Deferred(Operand),
+}
+
+/// The right-hand side of a comparison.
+enum Predicate {
+    Comparison {
+        operator: Operator,
+        value: Rhs,
+    },
+    Range {
+        lower: Bound<Rhs>,
+        upper: Bound<Rhs>,
+        negated: bool,
+    },
+    Pattern {
+        pattern: Pattern,
+        negated: bool,
+    },
+}
+
 enum ParseFieldResult<P> {
     FoundField { field: P },
     InvalidField { field_name: String },
+    Expression { expr: Expr<P> },
 }
 
-fn parse_field_ast<P: StructProperties>(input: &str) -> IResult<&str, ParseFieldResult<P>> {
-    map_res(
-        recognize(tuple((alphanumeric1, space0))),
-        |s: &str| match P::from_str(s.trim()) {
-            Ok(v) => Ok(ParseFieldResult::FoundField { field: v }),
-            Err(_) => Ok(ParseFieldResult::InvalidField {
-                field_name: s.trim().to_string(),
-            }),
-            #[allow(unreachable_patterns)]
-            _ => Err(Err::Error((s, nom::error::ErrorKind::Alpha))),
-        },
-    )(input)
+fn parse_field_ast<P: StructProperties>(input: &str) -> ParseResult<'_, ParseFieldResult<P>> {
+    map(terminated(parse_word, ws0), |s: &str| {
+        match P::from_str(s) {
+            Ok(v) => ParseFieldResult::FoundField { field: v },
+            Err(_) => ParseFieldResult::InvalidField {
+                field_name: s.to_string(),
+            },
+        }
+    })(input)
 }
 
-fn parse_value(input: &str) -> IResult<&str, Value> {
-    let (input, value) = alt((
-        map(digit1, |s: &str| Value::Int(s.parse::<i32>().unwrap())),
-        map(
-            delimited(tag("\""), take_while1(|c: char| c != '"'), tag("\"")),
-            |s: &str| Value::String(s.to_string()),
-        ),
+pub(crate) fn parse_value(input: &str) -> ParseResult<'_, Value> {
+    #[cfg(feature = "chrono")]
+    match temporal::parse_literal(input) {
+        Err(Err::Error(_)) => {}
+        result => return result,
+    }
+
+    let (input, value) = alt((parse_number, map(parse_string, Value::String)))(input)?;
+    Ok((input, value))
+}
+
+/// Parses a signed number: `42`, `-7`, `1_000_000`, `0x1F`, `2.5` or `1e-3`. Numbers with a
+/// fraction or an exponent are floats. Literals that do not fit are a hard error rather than a
+/// syntax error, so the caller can report where they are.
+fn parse_number(input: &str) -> ParseResult<'_, Value> {
+    fn digits<'a>(
+        digit: impl Fn(char) -> bool + Copy,
+    ) -> impl FnMut(&'a str) -> ParseResult<'a, &'a str> {
+        recognize(pair(
+            take_while1(digit),
+            take_while(move |c: char| digit(c) || c == '_'),
+        ))
+    }
+
+    let (input, (literal, hex)) = consumed(preceded(
+        opt(one_of("+-")),
+        alt((
+            map(
+                preceded(tag_no_case("0x"), digits(|c| c.is_ascii_hexdigit())),
+                Some,
+            ),
+            map(
+                tuple((
+                    digits(|c| c.is_ascii_digit()),
+                    opt(preceded(char('.'), digits(|c| c.is_ascii_digit()))),
+                    opt(tuple((
+                        one_of("eE"),
+                        opt(one_of("+-")),
+                        digits(|c| c.is_ascii_digit()),
+                    ))),
+                )),
+                |_| None,
+            ),
+        )),
     ))(input)?;
+
+    let out_of_range = || Err::Failure(ParseError::NumberOutOfRange { literal });
+    let cleaned = literal.replace('_', "");
+    let value = match hex {
+        Some(hex) => {
+            let n = i64::from_str_radix(&hex.replace('_', ""), 16).map_err(|_| out_of_range())?;
+            let n = if literal.starts_with('-') { -n } else { n };
+            Value::Int(i32::try_from(n).map_err(|_| out_of_range())?)
+        }
+        None if cleaned.contains(['.', 'e', 'E']) => match cleaned.parse::<f64>() {
+            Ok(n) if n.is_finite() => Value::Float(n),
+            _ => return Err(out_of_range()),
+        },
+        None => Value::Int(cleaned.parse::<i32>().map_err(|_| out_of_range())?),
+    };
     Ok((input, value))
 }
 
-fn parse_vec_string(input: &str) -> IResult<&str, Vec<String>> {
-    let (input, values) =
-        delimited(tag("["), separated_list1(tag(","), parse_value), tag("]"))(input)?;
-    let values = values
-        .into_iter()
-        .filter_map(|v| match v {
-            Value::String(s) => Some(s),
-            _ => None,
-        })
-        .collect();
-    Ok((input, values))
+/// Converts literals to the type of the field they are compared against: integers compared against
+/// floats become floats, so `price > 10` matches in the same way as `price > 10.0`, and dates
+/// compared against date-times become midnight UTC on that date.
+pub(crate) fn coerce(value: Value, value_type: Option<FieldType>) -> Value {
+    match (value, value_type) {
+        (Value::Int(n), Some(FieldType::Float)) => Value::Float(n.into()),
+        #[cfg(feature = "chrono")]
+        (value, Some(FieldType::DateTime)) => temporal::coerce(value),
+        (value, _) => value,
+    }
 }
 
-fn parse_vec_int(input: &str) -> IResult<&str, Vec<i32>> {
-    let (input, values) =
-        delimited(tag("["), separated_list1(tag(","), parse_value), tag("]"))(input)?;
-    let values = values
-        .into_iter()
-        .filter_map(|v| match v {
-            Value::Int(i) => Some(i),
+/// Parses a string literal. Strings are delimited by double or single quotes and support the
+/// escapes `\"`, `\'`, `\\`, `\n`, `\r`, `\t`, `\0` and `\u{...}`. Raw strings such as
+/// `r"C:\temp"` or `r#"say "hi""#` are taken verbatim.
+pub(crate) fn parse_string(input: &str) -> ParseResult<'_, String> {
+    let error = |i| Err(Err::Error(ParseError::Nom(i, ErrorKind::Escaped)));
+
+    if let Some(raw) = input.strip_prefix('r') {
+        let hashes = raw.len() - raw.trim_start_matches('#').len();
+        let body = match raw[hashes..].strip_prefix('"') {
+            Some(body) => body,
+            None => return error(input),
+        };
+        let terminator = format!("\"{}", "#".repeat(hashes));
+        return match body.find(&terminator) {
+            Some(end) => Ok((&body[end + terminator.len()..], body[..end].to_string())),
+            None => error(input),
+        };
+    }
+
+    let quote = match input.chars().next() {
+        Some(quote @ '"') | Some(quote @ '\'') => quote,
+        _ => return error(input),
+    };
+    let mut value = String::new();
+    let mut chars = input.char_indices().skip(1);
+    while let Some((i, c)) = chars.next() {
+        match c {
+            c if c == quote => return Ok((&input[i + c.len_utf8()..], value)),
+            '\\' => match chars.next() {
+                Some((_, '"')) => value.push('"'),
+                Some((_, '\'')) => value.push('\''),
+                Some((_, '\\')) => value.push('\\'),
+                Some((_, 'n')) => value.push('\n'),
+                Some((_, 'r')) => value.push('\r'),
+                Some((_, 't')) => value.push('\t'),
+                Some((_, '0')) => value.push('\0'),
+                Some((j, 'u')) => {
+                    let rest = &input[j + 1..];
+                    let end = match rest.strip_prefix('{').and_then(|r| r.find('}')) {
+                        Some(end) => end + 1,
+                        None => return error(&input[i..]),
+                    };
+                    match u32::from_str_radix(&rest[1..end], 16)
+                        .ok()
+                        .and_then(char::from_u32)
+                    {
+                        Some(c) => value.push(c),
+                        None => return error(&input[i..]),
+                    }
+                    // Skip over the braces and hex digits that were just decoded.
+                    for _ in 0..=end {
+                        chars.next();
+                    }
+                }
+                _ => return error(&input[i..]),
+            },
+            c => value.push(c),
+        }
+    }
+    error(input)
+}
+
+fn parse_list(input: &str) -> ParseResult<'_, Value> {
+    map_opt(
+        delimited(
+            pair(tag("["), ws0),
+            separated_list1(tuple((ws0, tag(","), ws0)), parse_value),
+            pair(ws0, tag("]")),
+        ),
+        |values: Vec<Value>| match values[0] {
+            Value::String(_) => values
+                .into_iter()
+                .map(|v| match v {
+                    Value::String(s) => Some(s),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>()
+                .map(Value::VecString),
+            Value::Int(_) => values
+                .into_iter()
+                .map(|v| match v {
+                    Value::Int(i) => Some(i),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>()
+                .map(Value::VecInt),
             _ => None,
+        },
+    )(input)
+}
+
+pub(crate) fn parse_word(input: &str) -> ParseResult<'_, &str> {
+    take_while1(|c: char| c.is_alphanumeric() || c == '_')(input)
+}
+
+/// Parses a keyword case-insensitively, making sure it is not the start of a longer word.
+fn keyword<'a>(word: &'static str) -> impl FnMut(&'a str) -> ParseResult<'a, &'a str> {
+    terminated(
+        tag_no_case(word),
+        not(peek(satisfy(|c: char| c.is_alphanumeric() || c == '_'))),
+    )
+}
+
+/// What an operator written as a word stands for.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Keyword {
+    Comparison(Operator),
+    Range,
+    /// A pattern syntax and whether it ignores case.
+    Pattern(PatternSyntax, bool),
+}
+
+/// The operators written as words, in the order they are suggested. The parser, the lexer and
+/// the completer all read them from here. `filter!` in `vec_filter_derive` keeps its own list of
+/// the ones that can be negated, which `tests/derive_copies.rs` checks against this one.
+pub(crate) const OPERATOR_KEYWORDS: [(&str, Keyword); 10] = [
+    ("contains", Keyword::Comparison(Operator::Contains)),
+    ("startswith", Keyword::Comparison(Operator::StartsWith)),
+    ("endswith", Keyword::Comparison(Operator::EndsWith)),
+    ("regexmatch", Keyword::Comparison(Operator::RegexMatch)),
+    ("in", Keyword::Comparison(Operator::In)),
+    ("between", Keyword::Range),
+    ("like", Keyword::Pattern(PatternSyntax::Like, false)),
+    ("ilike", Keyword::Pattern(PatternSyntax::Like, true)),
+    ("glob", Keyword::Pattern(PatternSyntax::Glob, false)),
+    ("iglob", Keyword::Pattern(PatternSyntax::Glob, true)),
+];
+
+/// Returns what an operator written as a word stands for, ignoring case.
+pub(crate) fn operator_keyword(word: &str) -> Option<&'static Keyword> {
+    OPERATOR_KEYWORDS
+        .iter()
+        .find(|(keyword, _)| keyword.eq_ignore_ascii_case(word))
+        .map(|(_, keyword)| keyword)
+}
+
+fn parse_builtin_operator(word: &str) -> Option<Operator> {
+    match operator_keyword(word)? {
+        Keyword::Comparison(operator) => Some(operator.clone()),
+        _ => None,
+    }
+}
+
+/// Parses a wildcard pattern: `like "Al%"`, `not ilike "al!%%" escape "!"`, `glob "*.rs"`.
+fn parse_pattern(input: &str) -> ParseResult<'_, Predicate> {
+    let (input, (negated, operator, _, source)) = tuple((
+        opt(alt((tag("!"), terminated(tag_no_case("not"), ws1)))),
+        verify(parse_word, |word: &str| {
+            matches!(operator_keyword(word), Some(Keyword::Pattern(..)))
+        }),
+        ws0,
+        parse_value,
+    ))(input)?;
+    let (input, escape) = opt(preceded(tuple((ws0, keyword("escape"), ws0)), parse_value))(input)?;
+
+    let source = match source {
+        Value::String(source) => source,
+        _ => return Err(Err::Error(ParseError::Nom(input, ErrorKind::Verify))),
+    };
+    let escape = match escape {
+        Some(Value::String(escape)) if escape.chars().count() == 1 => {
+            escape.chars().next().unwrap()
+        }
+        Some(_) => return Err(Err::Error(ParseError::Nom(input, ErrorKind::Verify))),
+        None => Pattern::DEFAULT_ESCAPE,
+    };
+    let (syntax, case_insensitive) = match operator_keyword(operator) {
+        Some(Keyword::Pattern(syntax, case_insensitive)) => (*syntax, *case_insensitive),
+        _ => unreachable!("verified to be a pattern keyword"),
+    };
+
+    let pattern = Pattern::new(syntax, &source, escape, case_insensitive).map_err(|message| {
+        Err::Failure(ParseError::InvalidPattern {
+            pattern: source.clone(),
+            message,
         })
-        .collect();
-    Ok((input, values))
+    })?;
+    Ok((
+        input,
+        Predicate::Pattern {
+            pattern,
+            negated: negated.is_some(),
+        },
+    ))
 }
 
 // Add a new AST validation error type
 #[derive(Debug, PartialEq)]
 pub enum ASTValidationError<P: StructProperties> {
     InvalidSyntax,
-    InvalidField { field_name: String },
+    /// A field that is not one of `valid_fields`. `suggestions` lists the valid fields closest to
+    /// `field_name`, which was likely misspelled.
+    InvalidField {
+        field_name: String,
+        suggestions: Vec<String>,
+        valid_fields: Vec<String>,
+    },
     CompatibilityError(CompatibilityError<P>),
+    UnknownFunction {
+        name: String,
+    },
+    /// A word in the position of an operator that is not a built-in or registered operator, with
+    /// the closest operators as `suggestions`.
+    UnknownOperator {
+        operator: String,
+        suggestions: Vec<String>,
+    },
+    InvalidPattern {
+        pattern: String,
+        message: String,
+    },
+    /// Parameters used in the query without a bound value (`unbound`), and bound values for
+    /// parameters the query does not use (`extra`).
+    ParameterMismatch {
+        unbound: Vec<String>,
+        extra: Vec<String>,
+    },
+    /// A numeric literal that does not fit in the type it would be parsed as. `span` is the byte
+    /// range of the literal in the query.
+    NumberOutOfRange {
+        literal: String,
+        span: Range<usize>,
+    },
+    InvalidArguments {
+        function: Function,
+        arguments: Vec<FieldType>,
+    },
+    IncompatibleExpression {
+        ast: Box<AST<P>>,
+        value_type: FieldType,
+        valid_values: Vec<Value>,
+    },
+    /// The query is longer than `Parser::max_input_length` bytes.
+    InputTooLong {
+        length: usize,
+        max: usize,
+    },
+    /// Parentheses in the query are nested deeper than `Parser::max_depth`.
+    NestingTooDeep {
+        depth: usize,
+        max: usize,
+    },
+    /// A list has more items than `Parser::max_list_length`.
+    ListTooLong {
+        length: usize,
+        max: usize,
+    },
+    /// A regular expression compiles to more than `Parser::max_regex_size` bytes.
+    RegexTooLarge {
+        pattern: String,
+        max: usize,
+    },
+    /// An operator that is not in `Parser::allowed_operators`.
+    OperatorNotAllowed {
+        operator: Operator,
+    },
+    /// A `like` or `glob` pattern, or its case-insensitive form, whose syntax is not in
+    /// `Parser::allowed_patterns`.
+    PatternNotAllowed {
+        syntax: PatternSyntax,
+    },
+    /// A `between` or `in` range, when `Parser::allow_ranges` is off.
+    RangeNotAllowed,
+    /// A function that is not in `Parser::allowed_functions`.
+    FunctionNotAllowed {
+        function: Function,
+    },
 }
 
 impl<P: StructProperties> std::fmt::Display for ASTValidationError<P> {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
             ASTValidationError::InvalidSyntax => write!(f, "Invalid syntax"),
-            ASTValidationError::InvalidField { field_name } => {
-                write!(f, "Invalid field {}", field_name)
-            }
+            ASTValidationError::InvalidField {
+                field_name,
+                suggestions,
+                ..
+            } => write!(
+                f,
+                "Invalid field {}{}",
+                field_name,
+                did_you_mean(suggestions)
+            ),
             ASTValidationError::CompatibilityError(e) => write!(f, "{}", e),
+            ASTValidationError::UnknownFunction { name } => write!(f, "Unknown function {}", name),
+            ASTValidationError::UnknownOperator {
+                operator,
+                suggestions,
+            } => write!(
+                f,
+                "Unknown operator {}{}",
+                operator,
+                did_you_mean(suggestions)
+            ),
+            ASTValidationError::InvalidPattern { pattern, message } => {
+                write!(f, "Invalid pattern {}: {}", pattern, message)
+            }
+            ASTValidationError::ParameterMismatch { unbound, extra } => write!(
+                f,
+                "Unbound parameters: {:?}, unexpected parameters: {:?}",
+                unbound, extra
+            ),
+            ASTValidationError::NumberOutOfRange { literal, span } => write!(
+                f,
+                "Number {} at {}..{} is out of range",
+                literal, span.start, span.end
+            ),
+            ASTValidationError::InvalidArguments {
+                function,
+                arguments,
+            } => write!(
+                f,
+                "Invalid arguments for function {}. Provided argument types: {:?}, expected: {}",
+                function,
+                arguments,
+                function.signature()
+            ),
+            ASTValidationError::IncompatibleExpression {
+                ast,
+                value_type,
+                valid_values,
+            } => write!(
+                f,
+                "Incompatible value for expression of type {}. Operation: {:?}, valid value options: {:?}",
+                value_type, ast, valid_values
+            ),
+            ASTValidationError::InputTooLong { length, max } => write!(
+                f,
+                "Query is {} bytes long, the maximum is {}",
+                length, max
+            ),
+            ASTValidationError::NestingTooDeep { depth, max } => write!(
+                f,
+                "Query is nested {} levels deep, the maximum is {}",
+                depth, max
+            ),
+            ASTValidationError::ListTooLong { length, max } => {
+                write!(f, "List has {} items, the maximum is {}", length, max)
+            }
+            ASTValidationError::RegexTooLarge { pattern, max } => write!(
+                f,
+                "Regular expression {} is larger than the maximum of {} bytes",
+                pattern, max
+            ),
+            ASTValidationError::OperatorNotAllowed { operator } => {
+                write!(f, "Operator {} is not allowed", operator)
+            }
+            ASTValidationError::PatternNotAllowed { syntax } => {
+                let operator = match syntax {
+                    PatternSyntax::Like => "like",
+                    PatternSyntax::Glob => "glob",
+                };
+                write!(f, "Operator {} is not allowed", operator)
+            }
+            ASTValidationError::RangeNotAllowed => write!(f, "Ranges are not allowed"),
+            ASTValidationError::FunctionNotAllowed { function } => {
+                write!(f, "Function {} is not allowed", function)
+            }
         }
     }
 }
 
 impl<P: StructProperties> std::error::Error for ASTValidationError<P> {}
 
+impl<P: StructProperties> ASTValidationError<P> {
+    /// Builds an `InvalidField` error, suggesting the fields of `P` closest to `field_name`.
+    pub(crate) fn invalid_field(field_name: String) -> Self {
+        let valid_fields = P::valid_fields();
+        ASTValidationError::InvalidField {
+            suggestions: suggest(&field_name, valid_fields.iter().copied()),
+            valid_fields: valid_fields.iter().map(|field| field.to_string()).collect(),
+            field_name,
+        }
+    }
+}
+
+fn did_you_mean(suggestions: &[String]) -> String {
+    match suggestions {
+        [] => String::new(),
+        [suggestion] => format!(", did you mean {}?", suggestion),
+        [rest @ .., last] => format!(", did you mean {} or {}?", rest.join(", "), last),
+    }
+}
+
 impl<P: StructProperties> From<CompatibilityError<P>> for ASTValidationError<P> {
     fn from(error: CompatibilityError<P>) -> Self {
         ASTValidationError::CompatibilityError(error)
     }
 }
 
-pub fn parse_query<P: StructProperties>(input: &str) -> Result<AST<P>, Vec<ASTValidationError<P>>>
-where
-    <P as FromStr>::Err: Debug,
-{
-    // Parse input into a raw AST
-    let raw_ast = delimited(space0, parse_raw_ast, space0)(input)
-        .map_err(|_| vec![ASTValidationError::InvalidSyntax])?;
+/// A configurable query parser, built with `Parser::new()` and its builder methods.
+/// `parse_query` uses the default configuration, which only limits the nesting depth to
+/// `Parser::DEFAULT_MAX_DEPTH`.
+#[derive(Debug, Clone, Default)]
+pub struct Parser {
+    pub(crate) registry: Registry,
+    pub(crate) limits: Limits,
+    #[cfg(feature = "chrono")]
+    clock: temporal::Clock,
+}
+
+impl Parser {
+    /// The deepest nesting of parentheses accepted unless `max_depth` is set, which keeps hostile
+    /// input from overflowing the stack.
+    pub const DEFAULT_MAX_DEPTH: usize = 64;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the custom operators and functions accepted by the parser.
+    pub fn registry(mut self, registry: Registry) -> Self {
+        self.registry = registry;
+        self
+    }
+
+    /// Rejects queries longer than `max` bytes with `InputTooLong`.
+    pub fn max_input_length(mut self, max: usize) -> Self {
+        self.limits.max_input_length = Some(max);
+        self
+    }
+
+    /// Rejects queries with parentheses nested more than `max` levels deep with `NestingTooDeep`.
+    /// This is checked before parsing, so it protects against stack overflows from hostile input.
+    /// Defaults to `Parser::DEFAULT_MAX_DEPTH`.
+    pub fn max_depth(mut self, max: usize) -> Self {
+        self.limits.max_depth = Some(max);
+        self
+    }
+
+    /// Rejects lists with more than `max` items, e.g. in `name in [...]`, with `ListTooLong`.
+    pub fn max_list_length(mut self, max: usize) -> Self {
+        self.limits.max_list_length = Some(max);
+        self
+    }
+
+    /// Rejects regular expressions that compile to more than `max` bytes with `RegexTooLarge`.
+    /// Large repetitions such as `a{1000}{1000}` are rejected even though the pattern is short.
+    pub fn max_regex_size(mut self, max: usize) -> Self {
+        self.limits.max_regex_size = Some(max);
+        self
+    }
+
+    /// Restricts the comparison operators a query may use, rejecting others with
+    /// `OperatorNotAllowed`. Patterns, ranges and functions are restricted separately, with
+    /// `allowed_patterns`, `allow_ranges` and `allowed_functions`.
+    pub fn allowed_operators<I>(mut self, operators: I) -> Self
+    where
+        I: IntoIterator<Item = Operator>,
+    {
+        self.limits.allowed_operators = Some(operators.into_iter().collect());
+        self
+    }
+
+    /// Restricts the pattern syntaxes a query may use, rejecting others with `PatternNotAllowed`.
+    /// Allowing `PatternSyntax::Like` allows `like` and `ilike`, and `PatternSyntax::Glob` allows
+    /// `glob` and `iglob`.
+    pub fn allowed_patterns<I>(mut self, syntaxes: I) -> Self
+    where
+        I: IntoIterator<Item = PatternSyntax>,
+    {
+        self.limits.allowed_patterns = Some(syntaxes.into_iter().collect());
+        self
+    }
+
+    /// Sets whether a query may use ranges, `between` or `in lower..upper`, rejecting them with
+    /// `RangeNotAllowed` if not. Ranges are allowed by default.
+    pub fn allow_ranges(mut self, allow: bool) -> Self {
+        self.limits.allow_ranges = allow;
+        self
+    }
+
+    /// Restricts the functions a query may call, rejecting others with `FunctionNotAllowed`.
+    pub fn allowed_functions<I>(mut self, functions: I) -> Self
+    where
+        I: IntoIterator<Item = Function>,
+    {
+        self.limits.allowed_functions = Some(functions.into_iter().collect());
+        self
+    }
+
+    /// Sets the clock used to resolve `now()` and `today()`, which defaults to the system clock.
+    /// A fixed clock makes relative queries such as `created_at > now() - 7d` deterministic.
+    #[cfg(feature = "chrono")]
+    pub fn clock<F>(mut self, clock: F) -> Self
+    where
+        F: Fn() -> chrono::DateTime<chrono::Utc> + Send + Sync + 'static,
+    {
+        self.clock = temporal::Clock::new(clock);
+        self
+    }
 
-    // Validate AST recursively
-    fn validate_ast<P: StructProperties>(ast: &AST<P>, errors: &mut Vec<ASTValidationError<P>>)
+    pub fn parse<P: StructProperties>(
+        &self,
+        input: &str,
+    ) -> Result<AST<P>, Vec<ASTValidationError<P>>>
     where
         <P as FromStr>::Err: Debug,
     {
-        match ast {
-            AST::And(left, right) | AST::Or(left, right) => {
-                validate_ast(left, errors);
-                validate_ast(right, errors);
+        self.limits.check_input(input).map_err(|e| vec![e])?;
+
+        // Parse input into a raw AST
+        let raw_ast = delimited(ws0, |i| self.parse_raw_ast(i), ws0)(input)
+            .map_err(|e| vec![convert_error(input, input, e).0])?;
+
+        // Perform validation checks
+        let mut errors = Vec::new();
+        validate_ast(&raw_ast.1, &mut errors);
+        self.limits.check_ast(&raw_ast.1, &mut errors);
+
+        if !raw_ast.0.is_empty() {
+            errors.push(ASTValidationError::InvalidSyntax);
+        }
+
+        if errors.is_empty() {
+            // Return the parsed and validated AST
+            Ok(raw_ast.1)
+        } else {
+            // Return all the errors
+            Err(errors)
+        }
+    }
+
+    fn parse_call<'a, P: StructProperties>(&self, input: &'a str) -> ParseResult<'a, Expr<P>> {
+        let (input, (name, _, _, _)) = tuple((parse_word, ws0, tag("("), ws0))(input)?;
+        let function = match (Function::from_str(name), self.registry.function(name)) {
+            (Ok(function), _) => function,
+            (Err(_), Some(function)) => Function::Custom(function.clone()),
+            (Err(_), None) => {
+                return Err(Err::Failure(ParseError::UnknownFunction {
+                    name: name.to_string(),
+                }))
+            }
+        };
+        let (input, args) =
+            separated_list0(tuple((ws0, tag(","), ws0)), |i| self.parse_expr(i))(input)?;
+        let (input, _) = tuple((ws0, tag(")")))(input)?;
+        Ok((input, Expr::Call { function, args }))
+    }
+
+    fn parse_expr<'a, P: StructProperties>(&self, input: &'a str) -> ParseResult<'a, Expr<P>> {
+        alt((
+            |i| self.parse_call(i),
+            map(parse_value, Expr::Literal),
+            |input| match parse_field_ast(input)? {
+                (input, ParseFieldResult::FoundField { field }) => Ok((input, Expr::Field(field))),
+                (_, ParseFieldResult::InvalidField { field_name }) => {
+                    Err(Err::Failure(ParseError::InvalidField { field_name }))
+                }
+                (_, ParseFieldResult::Expression { .. }) => unreachable!(),
             },
-            AST::Not(expr) => validate_ast(expr, errors),
-            AST::Equals { field: _, value }
-            | AST::NotEquals { field: _, value }
-            | AST::In { field: _, value }
-            | AST::GreaterThan { field: _, value }
-            | AST::LessThan { field: _, value }
-            | AST::GreaterThanOrEqual { field: _, value }
-            | AST::LessThanOrEqual { field: _, value }
-            | AST::Contains { field: _, value }
-            | AST::StartsWith { field: _, value }
-            | AST::EndsWith { field: _, value }
-            | AST::RegexMatch { field: _, value } => {
-                if let Err(e) = is_compatible(ast, &Value::wrap(value.clone())) {
-                    errors.push(e.into());
+        ))(input)
+    }
+
+    /// Parses the left-hand side of a comparison, returning `None` for an unknown field.
+    pub(crate) fn parse_target<'a, P: StructProperties>(
+        &self,
+        input: &'a str,
+    ) -> ParseResult<'a, Option<Expr<P>>> {
+        alt((
+            map(terminated(|i| self.parse_call::<P>(i), ws0), Some),
+            map(parse_field_ast::<P>, |target| match target {
+                ParseFieldResult::FoundField { field } => Some(Expr::Field(field)),
+                _ => None,
+            }),
+        ))(input)
+    }
+
+    pub(crate) fn parse_operator<'a>(&self, input: &'a str) -> ParseResult<'a, Operator> {
+        alt((
+            map(tag("=="), |_| Operator::Equals),
+            map(tag("="), |_| Operator::Equals),
+            map(tag("!="), |_| Operator::NotEquals),
+            map(tag("<>"), |_| Operator::NotEquals),
+            map(tag(">="), |_| Operator::GreaterThanOrEqual),
+            map(tag(">"), |_| Operator::GreaterThan),
+            map(tag("<="), |_| Operator::LessThanOrEqual),
+            map(tag("<"), |_| Operator::LessThan),
+            map(tag("!~"), |_| Operator::NotRegexMatch),
+            map_opt(
+                preceded(
+                    alt((tag("!"), terminated(tag_no_case("not"), ws1))),
+                    parse_word,
+                ),
+                |word: &str| parse_builtin_operator(word)?.negate(),
+            ),
+            map_opt(parse_word, |word: &str| {
+                parse_builtin_operator(word)
+                    .or_else(|| self.registry.operator(word).cloned().map(Operator::Custom))
+            }),
+        ))(input)
+    }
+
+    pub(crate) fn parse_comparison<'a, P: StructProperties>(
+        &self,
+        input: &'a str,
+    ) -> ParseResult<'a, AST<P>> {
+        let (input, target) = alt((
+            map(
+                |i| self.parse_call::<P>(i),
+                |expr| ParseFieldResult::Expression { expr },
+            ),
+            parse_field_ast,
+        ))(input)?;
+        let (input, _) = ws0(input)?;
+        let (input, predicate) = alt((
+            map(
+                |i| self.parse_between(i),
+                |(lower, upper)| Predicate::Range {
+                    lower,
+                    upper,
+                    negated: false,
+                },
+            ),
+            parse_pattern,
+            |i| self.parse_predicate(i),
+        ))(input)
+        .map_err(|e| self.unknown_operator(input, e))?;
+        let ast = match (target, predicate) {
+            (ParseFieldResult::InvalidField { field_name }, _) => AST::InvalidField { field_name },
+            (ParseFieldResult::FoundField { field }, Predicate::Comparison { operator, value }) => {
+                deferred_comparison(Expr::Field(field), operator, value)
+            }
+            (
+                ParseFieldResult::FoundField { field },
+                Predicate::Range {
+                    lower,
+                    upper,
+                    negated,
+                },
+            ) => {
+                let range = range(field, lower, upper);
+                match negated {
+                    true => AST::Not(Box::new(range)),
+                    false => range,
                 }
             }
-            AST::InvalidField { field_name } => {
-                errors.push(ASTValidationError::InvalidField {
-                    field_name: field_name.to_string(),
-                });
+            (ParseFieldResult::Expression { expr }, Predicate::Comparison { operator, value }) => {
+                deferred_comparison(expr, operator, value)
+            }
+            (ParseFieldResult::FoundField { field }, Predicate::Pattern { pattern, negated }) => {
+                match negated {
+                    true => AST::NotLike { field, pattern },
+                    false => AST::Like { field, pattern },
+                }
+            }
+            (ParseFieldResult::Expression { .. }, Predicate::Range { .. })
+            | (ParseFieldResult::Expression { .. }, Predicate::Pattern { .. }) => {
+                return Err(Err::Error(ParseError::Nom(input, ErrorKind::Verify)))
             }
+        };
+        Ok((input, ast))
+    }
+
+    /// Turns the syntax error for a comparison into an `UnknownOperator` error if the operator is a
+    /// word that is not a known operator, e.g. `name contians "a"`.
+    fn unknown_operator<'a>(
+        &self,
+        input: &'a str,
+        error: Err<ParseError<&'a str>>,
+    ) -> Err<ParseError<&'a str>> {
+        let negated_word = preceded(
+            opt(alt((tag("!"), terminated(tag_no_case("not"), ws1)))),
+            parse_word,
+        )(input);
+        let word = match (&error, negated_word) {
+            (Err::Error(_), Ok((_, word))) if word.starts_with(char::is_alphabetic) => word,
+            _ => return error,
+        };
+        let known = operator_keyword(word).is_some()
+            || word.eq_ignore_ascii_case("not")
+            || self.registry.operator(word).is_some();
+        if known {
+            return error;
         }
+        let candidates = OPERATOR_KEYWORDS
+            .iter()
+            .map(|(keyword, _)| *keyword)
+            .chain(self.registry.operator_names());
+        Err::Failure(ParseError::UnknownOperator {
+            operator: word.to_string(),
+            suggestions: suggest(word, candidates),
+        })
     }
 
-    // Perform validation checks
-    let mut errors = Vec::new();
-    validate_ast(&raw_ast.1, &mut errors);
+    /// Parses the value on the right-hand side of an operator, which may be a `$name` parameter.
+    /// With the `chrono` feature this also accepts `now()` and `today()` and adding or subtracting
+    /// durations, e.g. `now() - 7d`.
+    fn parse_operand<'a>(&self, input: &'a str) -> ParseResult<'a, Rhs> {
+        if let Ok((input, name)) = preceded(char('$'), parse_word)(input) {
+            return Ok((input, Rhs::Deferred(Operand::Param(name.to_string()))));
+        }
 
-    let remaining_input = raw_ast.0.trim();
-    if !remaining_input.is_empty() && errors.is_empty() {
-        errors.push(ASTValidationError::InvalidSyntax);
+        #[cfg(feature = "chrono")]
+        return temporal::parse_relative(input, &self.clock);
+        #[cfg(not(feature = "chrono"))]
+        map(parse_value, Rhs::Value)(input)
     }
 
-    if errors.is_empty() {
-        // Return the parsed and validated AST
-        Ok(raw_ast.1)
-    } else {
-        // Return all the errors
-        Err(errors)
+    /// Parses a list, or a single operand: the right-hand side of `in` and custom operators.
+    fn parse_list_or_operand<'a>(&self, input: &'a str) -> ParseResult<'a, Rhs> {
+        alt((map(parse_list, Rhs::Value), |i| self.parse_operand(i)))(input)
     }
-}
 
-fn parse_raw_ast<P: StructProperties>(input: &str) -> IResult<&str, AST<P>> {
-    let (input, ast) = alt((
+    /// Parses an inclusive range: `between 20 and 30`.
+    fn parse_between<'a>(&self, input: &'a str) -> ParseResult<'a, (Bound<Rhs>, Bound<Rhs>)> {
         map(
             tuple((
-                parse_field_ast,
-                space0,
-                alt((
-                    tag("=="),
-                    tag("!="),
-                    tag(">="),
-                    tag(">"),
-                    tag("<="),
-                    tag("<"),
-                    tag("contains"),
-                    tag("startswith"),
-                    tag("endswith"),
-                    tag("regexmatch"),
-                    tag("contains"),
-                )),
-                space0,
-                parse_value,
+                keyword("between"),
+                ws0,
+                |i| self.parse_operand(i),
+                ws0,
+                keyword("and"),
+                ws0,
+                |i| self.parse_operand(i),
             )),
-            |(field_ast, _, op, _, value)| match (op, field_ast) {
-                (operator, ParseFieldResult::FoundField { field }) => match operator {
-                    "==" => AST::Equals { field, value },
-                    "!=" => AST::NotEquals { field, value },
-                    ">" => AST::GreaterThan { field, value },
-                    ">=" => AST::GreaterThanOrEqual { field, value },
-                    "<" => AST::LessThan { field, value },
-                    "<=" => AST::LessThanOrEqual { field, value },
-                    "contains" => AST::Contains { field, value },
-                    "startswith" => AST::StartsWith { field, value },
-                    "endswith" => AST::EndsWith { field, value },
-                    "regexmatch" => AST::RegexMatch { field, value },
-                    _ => unreachable!(),
-                },
-                (_, ParseFieldResult::InvalidField { field_name }) => AST::InvalidField { field_name },
-            },
-        ),
+            |(_, _, lower, _, _, _, upper)| (Bound::Included(lower), Bound::Included(upper)),
+        )(input)
+    }
+
+    /// Parses a range literal, excluding the upper bound unless written with `..=`: `20..30`, `20..=30`.
+    fn parse_range<'a>(&self, input: &'a str) -> ParseResult<'a, (Bound<Rhs>, Bound<Rhs>)> {
         map(
             tuple((
-                parse_field_ast,
-                space0,
-                tag("in"),
-                space0,
-                alt((
-                    map(parse_vec_string, Value::VecString),
-                    map(parse_vec_int, Value::VecInt),
-                    map(parse_value, |x| x),
-                )),
+                |i| self.parse_operand(i),
+                ws0,
+                tag(".."),
+                opt(tag("=")),
+                ws0,
+                |i| self.parse_operand(i),
             )),
-            |(field_ast, _, _, _, value)| match field_ast {
-                ParseFieldResult::FoundField { field } => AST::In { field, value },
-                ParseFieldResult::InvalidField { field_name } => AST::InvalidField { field_name },
+            |(lower, _, _, inclusive, _, upper)| {
+                let upper = match inclusive {
+                    Some(_) => Bound::Included(upper),
+                    None => Bound::Excluded(upper),
+                };
+                (Bound::Included(lower), upper)
             },
-        ),
+        )(input)
+    }
+
+    fn parse_predicate<'a>(&self, input: &'a str) -> ParseResult<'a, Predicate> {
+        let (input, (operator, _)) = tuple((|i| self.parse_operator(i), ws0))(input)?;
+        match operator {
+            Operator::In | Operator::NotIn => alt((
+                map(
+                    |i| self.parse_range(i),
+                    |(lower, upper)| Predicate::Range {
+                        lower,
+                        upper,
+                        negated: operator == Operator::NotIn,
+                    },
+                ),
+                map(
+                    |i| self.parse_list_or_operand(i),
+                    |value| Predicate::Comparison {
+                        operator: operator.clone(),
+                        value,
+                    },
+                ),
+            ))(input),
+            Operator::Custom(_) => map(
+                |i| self.parse_list_or_operand(i),
+                |value| Predicate::Comparison {
+                    operator: operator.clone(),
+                    value,
+                },
+            )(input),
+            _ => map(
+                |i| self.parse_operand(i),
+                |value| Predicate::Comparison {
+                    operator: operator.clone(),
+                    value,
+                },
+            )(input),
+        }
+    }
+
+    fn parse_raw_ast<'a, P: StructProperties>(&self, input: &'a str) -> ParseResult<'a, AST<P>> {
+        // Groups are tried first so that `not (...)` is not mistaken for a call to `not`.
+        let (input, ast) = alt((
+            map(
+                tuple((
+                    |i| self.parse_brackets(i),
+                    many0(tuple((
+                        ws0,
+                        alt((tag("&&"), tag("||"), keyword("and"), keyword("or"))),
+                        ws0,
+                        |i| self.parse_brackets(i),
+                    ))),
+                )),
+                |(first, rest)| {
+                    rest.into_iter()
+                        .fold(first, |acc, (_, op, _, expr)| match op {
+                            "&&" => AST::And(Box::new(acc), Box::new(expr)),
+                            "||" => AST::Or(Box::new(acc), Box::new(expr)),
+                            op if op.eq_ignore_ascii_case("and") => {
+                                AST::And(Box::new(acc), Box::new(expr))
+                            }
+                            _ => AST::Or(Box::new(acc), Box::new(expr)),
+                        })
+                },
+            ),
+            |i| self.parse_term(i),
+        ))(input)?;
+        Ok((input, ast))
+    }
+
+    /// Parses a comparison, or `true` or `false`, which match every item or none.
+    pub(crate) fn parse_term<'a, P: StructProperties>(
+        &self,
+        input: &'a str,
+    ) -> ParseResult<'a, AST<P>> {
+        alt((
+            value(AST::True, keyword("true")),
+            value(AST::False, keyword("false")),
+            |i| self.parse_comparison(i),
+        ))(input)
+    }
+
+    fn parse_brackets<'a, P: StructProperties>(&self, input: &'a str) -> ParseResult<'a, AST<P>> {
         map(
             tuple((
-                parse_brackets,
-                many0(tuple((
-                    space0,
-                    alt((tag("&&"), tag("||"))),
-                    space0,
-                    parse_brackets,
-                ))),
+                opt(alt((tag("!"), terminated(keyword("not"), ws0)))),
+                tag("("),
+                ws0,
+                |i| self.parse_raw_ast(i),
+                ws0,
+                tag(")"),
             )),
-            |(first, rest)| {
-                rest.into_iter()
-                    .fold(first, |acc, (_, op, _, expr)| match op {
-                        "&&" => AST::And(Box::new(acc), Box::new(expr)),
-                        "||" => AST::Or(Box::new(acc), Box::new(expr)),
-                        _ => unreachable!(),
-                    })
+            |(not, _, _, ast, _, _)| match not {
+                Some(_) => AST::Not(Box::new(ast)),
+                None => ast,
             },
+        )(input)
+    }
+}
+
+/// Builds the comparison of `expr` with a parsed operand, deferring it if the operand has no value
+/// yet.
+fn deferred_comparison<P: StructProperties>(expr: Expr<P>, operator: Operator, rhs: Rhs) -> AST<P> {
+    match rhs {
+        Rhs::Value(value) => {
+            let value = coerce(value, expr.field_type());
+            AST::compare(expr, operator, value)
+        }
+        Rhs::Deferred(operand) => AST::Deferred {
+            expr,
+            operator,
+            operand,
+        },
+    }
+}
+
+/// Builds a range check on `field`. A range with a bound that has no value yet, e.g.
+/// `created_at in 2024-01-01..now()`, is written as comparisons against each bound instead:
+/// `(created_at >= 2024-01-01) && (created_at < now())`.
+fn range<P: StructProperties>(field: P, lower: Bound<Rhs>, upper: Bound<Rhs>) -> AST<P> {
+    let value_type = Some(field.get_value_type().field_type());
+    match (bound_value(lower), bound_value(upper)) {
+        (Ok(lower), Ok(upper)) => AST::Between {
+            lower: lower.map(|value| coerce(value, value_type)),
+            upper: upper.map(|value| coerce(value, value_type)),
+            field,
+        },
+        (lower, upper) => {
+            let bound = |bound: Result<Bound<Value>, Bound<Rhs>>| match bound {
+                Ok(bound) => bound.map(Rhs::Value),
+                Err(bound) => bound,
+            };
+            let bounds = vec![
+                (
+                    bound(lower),
+                    Operator::GreaterThanOrEqual,
+                    Operator::GreaterThan,
+                ),
+                (bound(upper), Operator::LessThanOrEqual, Operator::LessThan),
+            ];
+            AST::all_of(
+                bounds
+                    .into_iter()
+                    .filter_map(|(bound, included, excluded)| {
+                        let (operator, rhs) = match bound {
+                            Bound::Included(rhs) => (included, rhs),
+                            Bound::Excluded(rhs) => (excluded, rhs),
+                            Bound::Unbounded => return None,
+                        };
+                        Some(deferred_comparison(
+                            Expr::Field(field.clone()),
+                            operator,
+                            rhs,
+                        ))
+                    }),
+            )
+        }
+    }
+}
+
+/// Returns the value of a bound, or the bound itself if it has no value yet.
+fn bound_value(bound: Bound<Rhs>) -> Result<Bound<Value>, Bound<Rhs>> {
+    match bound {
+        Bound::Included(Rhs::Value(value)) => Ok(Bound::Included(value)),
+        Bound::Excluded(Rhs::Value(value)) => Ok(Bound::Excluded(value)),
+        Bound::Unbounded => Ok(Bound::Unbounded),
+        bound => Err(bound),
+    }
+}
+
+/// Converts a parser failure into a validation error and the byte range of `input` it applies to.
+/// `clause` is the part of `input` that was being parsed, and is the range given to errors that
+/// cannot be narrowed down further.
+pub(crate) fn convert_error<P: StructProperties>(
+    input: &str,
+    clause: &str,
+    error: Err<ParseError<&str>>,
+) -> (ASTValidationError<P>, Range<usize>) {
+    let span_of = |s: &str| {
+        let start = s.as_ptr() as usize - input.as_ptr() as usize;
+        start..start + s.len()
+    };
+    let clause_span = span_of(clause.trim());
+    match error {
+        Err::Failure(ParseError::UnknownFunction { name }) => {
+            let span = find_word(clause, &name).map_or(clause_span, span_of);
+            (ASTValidationError::UnknownFunction { name }, span)
+        }
+        Err::Failure(ParseError::UnknownOperator {
+            operator,
+            suggestions,
+        }) => {
+            let span = find_word(clause, &operator).map_or(clause_span, span_of);
+            let error = ASTValidationError::UnknownOperator {
+                operator,
+                suggestions,
+            };
+            (error, span)
+        }
+        Err::Failure(ParseError::InvalidField { field_name }) => {
+            let span = find_word(clause, &field_name).map_or(clause_span, span_of);
+            (ASTValidationError::invalid_field(field_name), span)
+        }
+        Err::Failure(ParseError::InvalidPattern { pattern, message }) => (
+            ASTValidationError::InvalidPattern { pattern, message },
+            clause_span,
         ),
-    ))(input)?;
-    Ok((input, ast))
+        Err::Failure(ParseError::NumberOutOfRange { literal }) => {
+            let span = span_of(literal);
+            (
+                ASTValidationError::NumberOutOfRange {
+                    literal: literal.to_string(),
+                    span: span.clone(),
+                },
+                span,
+            )
+        }
+        _ => (ASTValidationError::InvalidSyntax, clause_span),
+    }
+}
+
+/// Returns the first occurrence of `word` in `input` that is not part of a longer word.
+pub(crate) fn find_word<'a>(input: &'a str, word: &str) -> Option<&'a str> {
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+    input.match_indices(word).find_map(|(start, _)| {
+        let end = start + word.len();
+        let before = input[..start].chars().next_back();
+        let after = input[end..].chars().next();
+        match (before, after) {
+            (Some(c), _) | (_, Some(c)) if is_word_char(c) => None,
+            _ => Some(&input[start..end]),
+        }
+    })
+}
+
+pub fn parse_query<P: StructProperties>(input: &str) -> Result<AST<P>, Vec<ASTValidationError<P>>>
+where
+    <P as FromStr>::Err: Debug,
+{
+    Parser::new().parse(input)
 }
 
-fn parse_brackets<P: StructProperties>(input: &str) -> IResult<&str, AST<P>> {
-    map_res(
-        tuple((alt((tag("!("), tag("("))), parse_raw_ast, tag(")"))),
-        |(op, ast, _)| {
-            match op {
-                "!(" => Ok(AST::Not(Box::new(ast))),
-                "(" => Ok(ast),
-                _ => Err(Err::Error(("", nom::error::ErrorKind::Alpha))), // TODO: Find better errors
+// Type check function calls, returning the type of the expression if it is valid
+fn validate_expr<P: StructProperties>(
+    expr: &Expr<P>,
+    errors: &mut Vec<ASTValidationError<P>>,
+) -> Option<FieldType> {
+    match expr {
+        Expr::Field(_) | Expr::Literal(_) => expr.field_type(),
+        Expr::Call { function, args } => {
+            let arguments = args
+                .iter()
+                .map(|arg| validate_expr(arg, errors))
+                .collect::<Option<Vec<_>>>()?;
+            let return_type = function.return_type(&arguments);
+            if return_type.is_none() {
+                errors.push(ASTValidationError::InvalidArguments {
+                    function: function.clone(),
+                    arguments,
+                });
             }
-        },
-    )(input)
+            return_type
+        }
+    }
+}
+
+// Validate AST recursively
+pub(crate) fn validate_ast<P: StructProperties>(
+    ast: &AST<P>,
+    errors: &mut Vec<ASTValidationError<P>>,
+) where
+    <P as FromStr>::Err: Debug,
+{
+    match ast {
+        AST::And(left, right) | AST::Or(left, right) => {
+            validate_ast(left, errors);
+            validate_ast(right, errors);
+        }
+        AST::Not(expr) => validate_ast(expr, errors),
+        AST::Equals { field: _, value }
+        | AST::NotEquals { field: _, value }
+        | AST::In { field: _, value }
+        | AST::GreaterThan { field: _, value }
+        | AST::LessThan { field: _, value }
+        | AST::GreaterThanOrEqual { field: _, value }
+        | AST::LessThanOrEqual { field: _, value }
+        | AST::Contains { field: _, value }
+        | AST::StartsWith { field: _, value }
+        | AST::EndsWith { field: _, value }
+        | AST::RegexMatch { field: _, value }
+        | AST::NotIn { field: _, value }
+        | AST::NotContains { field: _, value }
+        | AST::NotStartsWith { field: _, value }
+        | AST::NotEndsWith { field: _, value }
+        | AST::NotRegexMatch { field: _, value } => {
+            if let Err(e) = is_compatible(ast, &Value::wrap(value.clone())) {
+                errors.push(e.into());
+            }
+        }
+        AST::Like { pattern, .. } | AST::NotLike { pattern, .. } => {
+            if let Err(e) = is_compatible(ast, &Value::String(pattern.source().to_string())) {
+                errors.push(e.into());
+            }
+        }
+        AST::Between { lower, upper, .. } => {
+            for bound in [lower, upper] {
+                if let Bound::Included(value) | Bound::Excluded(value) = bound {
+                    if let Err(e) = is_compatible(ast, value) {
+                        errors.push(e.into());
+                    }
+                }
+            }
+        }
+        AST::Compare {
+            expr,
+            operator,
+            value,
+        } => {
+            if let Some(value_type) = validate_expr(expr, errors) {
+                if let Err(valid_values) =
+                    check_comparison(operator, &value_type.default_value(), value)
+                {
+                    errors.push(ASTValidationError::IncompatibleExpression {
+                        ast: Box::new(ast.clone()),
+                        value_type,
+                        valid_values,
+                    });
+                }
+            }
+        }
+        // Parameters are checked when a value is bound to them.
+        AST::Deferred {
+            expr,
+            operand: Operand::Param(_),
+            ..
+        } => {
+            validate_expr(expr, errors);
+        }
+        // The value of a relative time changes, but its type does not.
+        #[cfg(feature = "chrono")]
+        AST::Deferred {
+            expr,
+            operator,
+            operand: Operand::Relative(time),
+        } => {
+            if let Some(value) = time.resolve() {
+                let value = coerce(value, expr.field_type());
+                validate_ast(&AST::compare(expr.clone(), operator.clone(), value), errors);
+            }
+        }
+        AST::InvalidField { field_name } => {
+            errors.push(ASTValidationError::invalid_field(field_name.to_string()));
+        }
+        AST::True | AST::False => {}
+    }
 }
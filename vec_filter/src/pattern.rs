@@ -0,0 +1,179 @@
+use crate::ast::quote;
+use regex::{Regex, RegexBuilder};
+use std::fmt;
+
+/// The wildcard syntax of a `Pattern`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum PatternSyntax {
+    /// SQL LIKE: `%` matches any run of characters and `_` matches a single character.
+    Like,
+
+    /// Shell glob: `*` matches any run of characters, `?` a single character and `[...]` a set.
+    Glob,
+}
+
+/// A wildcard pattern used by the `like` and `glob` operators. The pattern is compiled once when
+/// it is created, so matching does not recompile it for every item.
+#[derive(Clone)]
+pub struct Pattern {
+    syntax: PatternSyntax,
+    source: String,
+    escape: char,
+    case_insensitive: bool,
+    regex: Regex,
+}
+
+impl Pattern {
+    /// The escape character used when none is given.
+    pub const DEFAULT_ESCAPE: char = '\\';
+
+    pub fn new(
+        syntax: PatternSyntax,
+        source: &str,
+        escape: char,
+        case_insensitive: bool,
+    ) -> Result<Self, String> {
+        let translated = match syntax {
+            PatternSyntax::Like => translate_like(source, escape)?,
+            PatternSyntax::Glob => translate_glob(source, escape)?,
+        };
+        let regex = RegexBuilder::new(&format!("^{}$", translated))
+            .case_insensitive(case_insensitive)
+            .dot_matches_new_line(true)
+            .build()
+            .map_err(|e| e.to_string())?;
+
+        Ok(Pattern {
+            syntax,
+            source: source.to_string(),
+            escape,
+            case_insensitive,
+            regex,
+        })
+    }
+
+    /// Compiles a case-sensitive SQL LIKE pattern, e.g. `Al%`.
+    pub fn like(source: &str) -> Result<Self, String> {
+        Pattern::new(PatternSyntax::Like, source, Pattern::DEFAULT_ESCAPE, false)
+    }
+
+    /// Compiles a case-sensitive glob pattern, e.g. `*.rs`.
+    pub fn glob(source: &str) -> Result<Self, String> {
+        Pattern::new(PatternSyntax::Glob, source, Pattern::DEFAULT_ESCAPE, false)
+    }
+
+    pub fn syntax(&self) -> PatternSyntax {
+        self.syntax
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn escape(&self) -> char {
+        self.escape
+    }
+
+    pub fn is_case_insensitive(&self) -> bool {
+        self.case_insensitive
+    }
+
+    pub fn is_match(&self, s: &str) -> bool {
+        self.regex.is_match(s)
+    }
+
+    /// Returns the operator keyword for this pattern: `like`, `ilike`, `glob` or `iglob`.
+    pub fn operator(&self) -> &'static str {
+        match (self.syntax, self.case_insensitive) {
+            (PatternSyntax::Like, false) => "like",
+            (PatternSyntax::Like, true) => "ilike",
+            (PatternSyntax::Glob, false) => "glob",
+            (PatternSyntax::Glob, true) => "iglob",
+        }
+    }
+}
+
+// `vec_filter_derive` copies `translate_like` and `translate_glob` to check the patterns in
+// `filter!`, and `tests/derive_copies.rs` checks that the copies agree with these.
+fn translate_like(source: &str, escape: char) -> Result<String, String> {
+    let mut regex = String::new();
+    let mut chars = source.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c == escape => match chars.next() {
+                Some(escaped) => regex.push_str(&regex::escape(&escaped.to_string())),
+                None => return Err("pattern ends with the escape character".to_string()),
+            },
+            '%' => regex.push_str(".*"),
+            '_' => regex.push('.'),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    Ok(regex)
+}
+
+fn translate_glob(source: &str, escape: char) -> Result<String, String> {
+    let mut regex = String::new();
+    let mut chars = source.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c == escape => match chars.next() {
+                Some(escaped) => regex.push_str(&regex::escape(&escaped.to_string())),
+                None => return Err("pattern ends with the escape character".to_string()),
+            },
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            '[' => {
+                regex.push('[');
+                if let Some('!') | Some('^') = chars.peek() {
+                    chars.next();
+                    regex.push('^');
+                }
+                let mut first = true;
+                loop {
+                    match chars.next() {
+                        Some(']') if !first => break,
+                        Some('-') if !first && chars.peek() != Some(&']') => regex.push('-'),
+                        Some(c) => regex.push_str(&regex::escape(&c.to_string())),
+                        None => return Err("unclosed character class".to_string()),
+                    }
+                    first = false;
+                }
+                regex.push(']');
+            }
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    Ok(regex)
+}
+
+impl PartialEq for Pattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.syntax == other.syntax
+            && self.source == other.source
+            && self.escape == other.escape
+            && self.case_insensitive == other.case_insensitive
+    }
+}
+
+impl fmt::Debug for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pattern")
+            .field("syntax", &self.syntax)
+            .field("source", &self.source)
+            .field("escape", &self.escape)
+            .field("case_insensitive", &self.case_insensitive)
+            .finish()
+    }
+}
+
+/// Writes the pattern in query syntax, e.g. `ilike "al%"`.
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.operator(), quote(&self.source))?;
+        if self.escape != Pattern::DEFAULT_ESCAPE {
+            write!(f, " escape {}", quote(&self.escape.to_string()))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,206 @@
+use crate::{
+    walk_expr, Expr, Function, Operator, Pattern, PatternSyntax, StructProperties, Visitor, AST,
+};
+use std::fmt;
+use std::iter;
+
+/// Restrictions on the queries a caller may run, for exposing `parse_query` to untrusted users.
+/// `authorize` rejects a query that references a forbidden field or uses a forbidden operator,
+/// pattern syntax, range or function, and otherwise joins the required scope to it with `&&`, so that whatever the query is, it only
+/// matches items within the scope:
+///
+/// ```ignore
+/// let policy = QueryPolicy::new()
+///     .forbid_field(EmployeeProperties::salary)
+///     .forbid_operator(Operator::RegexMatch)
+///     .require(Employee::filter().tenant().eq(tenant));
+/// let ast = policy.authorize(parse_query::<EmployeeProperties>(input)?)?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct QueryPolicy<P> {
+    forbidden_fields: Vec<P>,
+    forbidden_operators: Vec<Operator>,
+    forbidden_patterns: Vec<PatternSyntax>,
+    forbid_ranges: bool,
+    forbidden_functions: Vec<Function>,
+    scope: Vec<AST<P>>,
+}
+
+impl<P> Default for QueryPolicy<P> {
+    fn default() -> Self {
+        QueryPolicy {
+            forbidden_fields: Vec::new(),
+            forbidden_operators: Vec::new(),
+            forbidden_patterns: Vec::new(),
+            forbid_ranges: false,
+            forbidden_functions: Vec::new(),
+            scope: Vec::new(),
+        }
+    }
+}
+
+impl<P> QueryPolicy<P> {
+    /// A policy that allows every query and adds no scope.
+    pub fn new() -> Self {
+        QueryPolicy::default()
+    }
+
+    /// Rejects queries that reference `field`, whether it is compared directly or passed to a
+    /// function, with `ForbiddenField`. The required scope may still use it.
+    pub fn forbid_field(mut self, field: P) -> Self {
+        self.forbidden_fields.push(field);
+        self
+    }
+
+    /// Rejects queries that use the comparison `operator` or its negation with `ForbiddenOperator`,
+    /// since `!(a regexmatch b)` can be written as `a !~ b` or `a not regexmatch b`. Patterns,
+    /// ranges and functions are forbidden with `forbid_pattern`, `forbid_ranges` and
+    /// `forbid_function`.
+    pub fn forbid_operator(mut self, operator: Operator) -> Self {
+        self.forbidden_operators.extend(operator.negate());
+        self.forbidden_operators.push(operator);
+        self
+    }
+
+    /// Rejects queries that use a pattern of `syntax` with `ForbiddenPattern`. Forbidding
+    /// `PatternSyntax::Like` forbids `like` and `ilike`, and `PatternSyntax::Glob` forbids `glob`
+    /// and `iglob`, each also with `not`.
+    pub fn forbid_pattern(mut self, syntax: PatternSyntax) -> Self {
+        self.forbidden_patterns.push(syntax);
+        self
+    }
+
+    /// Rejects queries that use `between` or an `in` range with `ForbiddenRange`.
+    pub fn forbid_ranges(mut self) -> Self {
+        self.forbid_ranges = true;
+        self
+    }
+
+    /// Rejects queries that call `function` with `ForbiddenFunction`.
+    pub fn forbid_function(mut self, function: Function) -> Self {
+        self.forbidden_functions.push(function);
+        self
+    }
+
+    /// Adds a condition every authorized query is restricted to, e.g. `tenant == 42`.
+    pub fn require(mut self, scope: AST<P>) -> Self {
+        self.scope.push(scope);
+        self
+    }
+}
+
+impl<P: PartialEq + Clone> QueryPolicy<P> {
+    /// Checks `ast` against the policy, returning every forbidden field, operator, pattern syntax,
+    /// range and function it uses, or the query restricted to the required scope:
+    /// `(scope) && (ast)`.
+    pub fn authorize(&self, ast: AST<P>) -> Result<AST<P>, Vec<AuthorizationError<P>>> {
+        let mut errors = Vec::new();
+        for field in ast.fields() {
+            let error = AuthorizationError::ForbiddenField {
+                field: field.clone(),
+            };
+            if self.forbidden_fields.contains(field) && !errors.contains(&error) {
+                errors.push(error);
+            }
+        }
+        for operator in ast.predicates().filter_map(AST::operator) {
+            let error = AuthorizationError::ForbiddenOperator {
+                operator: operator.clone(),
+            };
+            if self.forbidden_operators.contains(&operator) && !errors.contains(&error) {
+                errors.push(error);
+            }
+        }
+        let mut used = Used::default();
+        used.visit_ast(&ast);
+        for syntax in used.patterns {
+            let error = AuthorizationError::ForbiddenPattern { syntax };
+            if self.forbidden_patterns.contains(&syntax) && !errors.contains(&error) {
+                errors.push(error);
+            }
+        }
+        if self.forbid_ranges
+            && ast
+                .predicates()
+                .any(|ast| matches!(ast, AST::Between { .. }))
+        {
+            errors.push(AuthorizationError::ForbiddenRange);
+        }
+        for function in used.functions {
+            let error = AuthorizationError::ForbiddenFunction {
+                function: function.clone(),
+            };
+            if self.forbidden_functions.contains(function) && !errors.contains(&error) {
+                errors.push(error);
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        Ok(AST::all_of(
+            self.scope.iter().cloned().chain(iter::once(ast)),
+        ))
+    }
+}
+
+/// The pattern syntaxes and functions a query uses.
+#[derive(Default)]
+struct Used<'ast> {
+    patterns: Vec<PatternSyntax>,
+    functions: Vec<&'ast Function>,
+}
+
+impl<'ast, P: 'ast> Visitor<'ast, P> for Used<'ast> {
+    fn visit_pattern(&mut self, pattern: &'ast Pattern) {
+        self.patterns.push(pattern.syntax());
+    }
+
+    fn visit_expr(&mut self, expr: &'ast Expr<P>) {
+        if let Expr::Call { function, .. } = expr {
+            self.functions.push(function);
+        }
+        walk_expr(self, expr)
+    }
+}
+
+/// A reason `QueryPolicy::authorize` rejected a query.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AuthorizationError<P> {
+    /// A field the policy forbids.
+    ForbiddenField { field: P },
+    /// A comparison operator the policy forbids.
+    ForbiddenOperator { operator: Operator },
+    /// A pattern syntax the policy forbids.
+    ForbiddenPattern { syntax: PatternSyntax },
+    /// A `between` or `in` range, when the policy forbids ranges.
+    ForbiddenRange,
+    /// A function the policy forbids.
+    ForbiddenFunction { function: Function },
+}
+
+impl<P: StructProperties> fmt::Display for AuthorizationError<P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AuthorizationError::ForbiddenField { field } => {
+                write!(f, "Field {} is not allowed", field)
+            }
+            AuthorizationError::ForbiddenOperator { operator } => {
+                write!(f, "Operator {} is not allowed", operator)
+            }
+            AuthorizationError::ForbiddenPattern { syntax } => {
+                let operator = match syntax {
+                    PatternSyntax::Like => "like",
+                    PatternSyntax::Glob => "glob",
+                };
+                write!(f, "Operator {} is not allowed", operator)
+            }
+            AuthorizationError::ForbiddenRange => write!(f, "Ranges are not allowed"),
+            AuthorizationError::ForbiddenFunction { function } => {
+                write!(f, "Function {} is not allowed", function)
+            }
+        }
+    }
+}
+
+impl<P: StructProperties> std::error::Error for AuthorizationError<P> {}
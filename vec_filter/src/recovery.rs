@@ -0,0 +1,364 @@
+use crate::parsers::{
+    convert_error, find_word, parse_string, parse_word, separator, validate_ast, ws0,
+};
+use crate::{ASTValidationError, Parser, StructProperties, Warning, AST};
+use nom::sequence::delimited;
+use std::fmt::Debug;
+use std::ops::Range;
+use std::str::FromStr;
+
+/// An error found in a query, along with the byte range of the query it applies to.
+#[derive(Debug, PartialEq)]
+pub struct Diagnostic<P: StructProperties> {
+    pub error: ASTValidationError<P>,
+    pub span: Range<usize>,
+}
+
+/// The result of `Parser::parse_with_recovery`: every error found in the query, and an AST built
+/// from the clauses that could be parsed and validated.
+#[derive(Debug, PartialEq)]
+pub struct ParseOutput<P: StructProperties> {
+    /// The valid clauses of the query, or `None` if no part of it could be kept. This is the same
+    /// AST that `Parser::parse` returns when `errors` is empty.
+    pub ast: Option<AST<P>>,
+    pub errors: Vec<Diagnostic<P>>,
+    /// The parts of `ast` that can never match or always match. See `AST::warnings`.
+    pub warnings: Vec<Warning<P>>,
+}
+
+impl Parser {
+    /// Parses a query without stopping at the first error, which is useful for editors that
+    /// highlight problems as the query is typed. The query is split into clauses at `&&` and `||`,
+    /// and each clause is parsed and validated on its own, so an error in one clause does not hide
+    /// errors in the others. The returned AST never matches an item that the query could not: a
+    /// clause with errors is dropped from an `||`, but drops the whole `&&` it is part of (the
+    /// other way around under `!`).
+    pub fn parse_with_recovery<P: StructProperties + PartialEq>(
+        &self,
+        input: &str,
+    ) -> ParseOutput<P>
+    where
+        <P as FromStr>::Err: Debug,
+    {
+        if let Err(error) = self.limits.check_input(input) {
+            return ParseOutput {
+                ast: None,
+                errors: vec![Diagnostic {
+                    error,
+                    span: 0..input.len(),
+                }],
+                warnings: Vec::new(),
+            };
+        }
+
+        let recovery = Recovery {
+            parser: self,
+            input,
+        };
+        let mut errors = Vec::new();
+        let ast = recovery.group(&chunks(input), 0..input.len(), false, &mut errors);
+        let warnings = ast.as_ref().map(AST::warnings).unwrap_or_default();
+        ParseOutput {
+            ast,
+            errors,
+            warnings,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Kind {
+    /// Whitespace or a comment.
+    Trivia,
+    /// A string literal, which may be unterminated.
+    Literal,
+    Word,
+    Punct,
+}
+
+#[derive(Debug)]
+pub(crate) struct Chunk {
+    pub(crate) kind: Kind,
+    pub(crate) span: Range<usize>,
+}
+
+/// Splits the input into words, punctuation, string literals and trivia. Strings and comments are
+/// kept whole so that parentheses and connectors inside them are not mistaken for syntax.
+pub(crate) fn chunks(input: &str) -> Vec<Chunk> {
+    let mut chunks = Vec::new();
+    let mut rest = input;
+    while let Some(c) = rest.chars().next() {
+        let (kind, len) = if let Ok((after, _)) = parse_string(rest) {
+            (Kind::Literal, rest.len() - after.len())
+        } else if let Ok((after, _)) = separator(rest) {
+            (Kind::Trivia, rest.len() - after.len())
+        } else if c == '"' || c == '\'' {
+            (Kind::Literal, rest.len())
+        } else if rest.starts_with("/*") {
+            (Kind::Trivia, rest.len())
+        } else if let Ok((after, _)) = parse_word(rest) {
+            (Kind::Word, rest.len() - after.len())
+        } else if rest.starts_with("&&") || rest.starts_with("||") {
+            (Kind::Punct, 2)
+        } else {
+            (Kind::Punct, c.len_utf8())
+        };
+        let start = input.len() - rest.len();
+        chunks.push(Chunk {
+            kind,
+            span: start..start + len,
+        });
+        rest = &rest[len..];
+    }
+    chunks
+}
+
+enum Connector {
+    And,
+    Or,
+}
+
+struct Recovery<'a> {
+    parser: &'a Parser,
+    input: &'a str,
+}
+
+impl<'a> Recovery<'a> {
+    fn text(&self, chunk: &Chunk) -> &'a str {
+        &self.input[chunk.span.clone()]
+    }
+
+    fn connector(&self, chunk: &Chunk) -> Option<Connector> {
+        match (chunk.kind, self.text(chunk)) {
+            (Kind::Punct, "&&") => Some(Connector::And),
+            (Kind::Punct, "||") => Some(Connector::Or),
+            (Kind::Word, word) if word.eq_ignore_ascii_case("and") => Some(Connector::And),
+            (Kind::Word, word) if word.eq_ignore_ascii_case("or") => Some(Connector::Or),
+            _ => None,
+        }
+    }
+
+    fn is_punct(&self, chunk: &Chunk, punct: &str) -> bool {
+        chunk.kind == Kind::Punct && self.text(chunk) == punct
+    }
+
+    /// Recovers the clauses of a group, joined by `&&` and `||`. `span` is the range the chunks
+    /// came from, used to report an empty group. A clause with errors stands for `false`, or for
+    /// `true` when the group is `negated`, so that the recovered group matches no more items than
+    /// the query could.
+    fn group<P: StructProperties>(
+        &self,
+        chunks: &[Chunk],
+        span: Range<usize>,
+        negated: bool,
+        errors: &mut Vec<Diagnostic<P>>,
+    ) -> Option<AST<P>>
+    where
+        <P as FromStr>::Err: Debug,
+    {
+        // Split at the connectors outside parentheses, leaving the `and` of `between` alone.
+        let mut clauses = vec![(None, 0)];
+        let (mut depth, mut between) = (0usize, false);
+        for (i, chunk) in chunks.iter().enumerate() {
+            if self.is_punct(chunk, "(") {
+                depth += 1;
+            } else if self.is_punct(chunk, ")") {
+                depth = depth.saturating_sub(1);
+            } else if depth == 0 && chunk.kind == Kind::Word {
+                let word = self.text(chunk);
+                if word.eq_ignore_ascii_case("between") {
+                    between = true;
+                    continue;
+                } else if between && word.eq_ignore_ascii_case("and") {
+                    between = false;
+                    continue;
+                }
+            }
+            if depth == 0 {
+                if let Some(connector) = self.connector(chunk) {
+                    clauses.push((Some(connector), i + 1));
+                    between = false;
+                }
+            }
+        }
+
+        let chained = clauses.len() > 1;
+        let mut ast: Option<AST<P>> = None;
+        for (index, (connector, start)) in clauses.iter().enumerate() {
+            let end = match clauses.get(index + 1) {
+                Some((_, next)) => next - 1,
+                None => chunks.len(),
+            };
+            let clause = trim(&chunks[*start..end]);
+            let recovered = if clause.is_empty() {
+                // Point at the gap between connectors, or at the whole group if it is empty.
+                let gap = match (start.checked_sub(1), chunks.get(end)) {
+                    (Some(before), Some(after)) => chunks[before].span.end..after.span.start,
+                    (Some(before), None) => chunks[before].span.clone(),
+                    (None, Some(after)) => after.span.clone(),
+                    (None, None) => span.clone(),
+                };
+                errors.push(Diagnostic {
+                    error: ASTValidationError::InvalidSyntax,
+                    span: gap,
+                });
+                None
+            } else {
+                self.clause(clause, chained, negated, errors)
+            };
+
+            ast = match (connector, ast, recovered) {
+                (None, _, first) => first,
+                (Some(Connector::And), Some(left), Some(right)) => {
+                    Some(AST::And(Box::new(left), Box::new(right)))
+                }
+                (Some(Connector::Or), Some(left), Some(right)) => {
+                    Some(AST::Or(Box::new(left), Box::new(right)))
+                }
+                // `false && x` and `!(true || x)` can never match, while `false || x` and
+                // `!(true && x)` match whatever `x` does.
+                (Some(Connector::And), left, right) if negated => left.or(right),
+                (Some(Connector::Or), left, right) if !negated => left.or(right),
+                _ => None,
+            };
+        }
+        ast
+    }
+
+    /// Recovers a single clause, which is either a comparison or a group in parentheses that may
+    /// be negated. Comparisons joined to other clauses must be in parentheses. `negated` is whether
+    /// the clause is inside an odd number of negations.
+    fn clause<P: StructProperties>(
+        &self,
+        chunks: &[Chunk],
+        chained: bool,
+        negated: bool,
+        errors: &mut Vec<Diagnostic<P>>,
+    ) -> Option<AST<P>>
+    where
+        <P as FromStr>::Err: Debug,
+    {
+        let span = chunks[0].span.start..chunks[chunks.len() - 1].span.end;
+
+        let (not, open) = match chunks {
+            [not, ..] if self.is_punct(not, "!") => (true, 1),
+            [not, ..] if not.kind == Kind::Word && self.text(not).eq_ignore_ascii_case("not") => {
+                (true, skip_trivia(chunks, 1))
+            }
+            _ => (false, 0),
+        };
+        if !chunks.get(open).is_some_and(|c| self.is_punct(c, "(")) {
+            if chained {
+                errors.push(Diagnostic {
+                    error: ASTValidationError::InvalidSyntax,
+                    span: span.clone(),
+                });
+            }
+            return self.comparison(span, errors);
+        }
+
+        let mut depth = 0;
+        let close = chunks[open..].iter().position(|chunk| {
+            if self.is_punct(chunk, "(") {
+                depth += 1;
+            } else if self.is_punct(chunk, ")") {
+                depth -= 1;
+            }
+            depth == 0
+        });
+        let inner = match close {
+            Some(close) => {
+                let close = open + close;
+                if close + 1 < chunks.len() {
+                    errors.push(Diagnostic {
+                        error: ASTValidationError::InvalidSyntax,
+                        span: chunks[skip_trivia(chunks, close + 1)].span.start..span.end,
+                    });
+                }
+                let inner_span = chunks[open].span.end..chunks[close].span.start;
+                self.group(&chunks[open + 1..close], inner_span, negated != not, errors)
+            }
+            None => {
+                // Report the unclosed parenthesis, but still look for errors inside it.
+                errors.push(Diagnostic {
+                    error: ASTValidationError::InvalidSyntax,
+                    span: chunks[open].span.clone(),
+                });
+                let inner_span = chunks[open].span.end..span.end;
+                self.group(&chunks[open + 1..], inner_span, negated != not, errors)
+            }
+        };
+        match not {
+            true => inner.map(|ast| AST::Not(Box::new(ast))),
+            false => inner,
+        }
+    }
+
+    fn comparison<P: StructProperties>(
+        &self,
+        span: Range<usize>,
+        errors: &mut Vec<Diagnostic<P>>,
+    ) -> Option<AST<P>>
+    where
+        <P as FromStr>::Err: Debug,
+    {
+        let clause = &self.input[span.clone()];
+        let ast = match delimited(ws0, |i| self.parser.parse_term(i), ws0)(clause) {
+            Ok(("", ast)) => ast,
+            Ok((rest, _)) => {
+                let start = span.end - rest.len();
+                errors.push(Diagnostic {
+                    error: ASTValidationError::InvalidSyntax,
+                    span: start..span.end,
+                });
+                return None;
+            }
+            Err(e) => {
+                let (error, span) = convert_error(self.input, clause, e);
+                errors.push(Diagnostic { error, span });
+                return None;
+            }
+        };
+
+        let mut found = Vec::new();
+        validate_ast(&ast, &mut found);
+        self.parser.limits.check_ast(&ast, &mut found);
+        if found.is_empty() {
+            return Some(ast);
+        }
+        for error in found {
+            let error_span = match &error {
+                ASTValidationError::InvalidField { field_name, .. } => {
+                    find_word(clause, field_name).map(|word| {
+                        let start =
+                            span.start + (word.as_ptr() as usize - clause.as_ptr() as usize);
+                        start..start + word.len()
+                    })
+                }
+                _ => None,
+            };
+            errors.push(Diagnostic {
+                error,
+                span: error_span.unwrap_or_else(|| span.clone()),
+            });
+        }
+        None
+    }
+}
+
+/// Removes the trivia from both ends of a run of chunks.
+fn trim(chunks: &[Chunk]) -> &[Chunk] {
+    let start = skip_trivia(chunks, 0);
+    let end = chunks
+        .iter()
+        .rposition(|chunk| chunk.kind != Kind::Trivia)
+        .map_or(start, |end| end + 1);
+    &chunks[start..end.max(start)]
+}
+
+fn skip_trivia(chunks: &[Chunk], from: usize) -> usize {
+    chunks[from..]
+        .iter()
+        .position(|chunk| chunk.kind != Kind::Trivia)
+        .map_or(chunks.len(), |i| from + i)
+}
@@ -0,0 +1,202 @@
+use crate::{FieldType, Value};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+type OperatorImplementation = Box<dyn Fn(&Value, &Value) -> bool + Send + Sync>;
+type FunctionImplementation = Box<dyn Fn(&[Value]) -> Option<Value> + Send + Sync>;
+
+/// A user-defined binary operator, e.g. `ip in_subnet "10.0.0.0/8"`.
+#[derive(Clone)]
+pub struct CustomOperator(Arc<OperatorDefinition>);
+
+struct OperatorDefinition {
+    name: String,
+    field_types: Vec<FieldType>,
+    value_type: FieldType,
+    implementation: OperatorImplementation,
+}
+
+impl CustomOperator {
+    pub fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    /// The types of field the operator can be applied to.
+    pub fn field_types(&self) -> &[FieldType] {
+        &self.0.field_types
+    }
+
+    /// The type of the value on the right-hand side of the operator.
+    pub fn value_type(&self) -> FieldType {
+        self.0.value_type
+    }
+
+    /// Evaluates the operator for a field value and the value from the query.
+    pub fn call(&self, field_value: &Value, value: &Value) -> bool {
+        (self.0.implementation)(field_value, value)
+    }
+}
+
+impl PartialEq for CustomOperator {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.name == other.0.name
+            && self.0.field_types == other.0.field_types
+            && self.0.value_type == other.0.value_type
+    }
+}
+
+impl Eq for CustomOperator {}
+
+impl std::hash::Hash for CustomOperator {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.name.hash(state);
+    }
+}
+
+impl fmt::Debug for CustomOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CustomOperator")
+            .field("name", &self.0.name)
+            .field("field_types", &self.0.field_types)
+            .field("value_type", &self.0.value_type)
+            .finish()
+    }
+}
+
+/// A user-defined scalar function, e.g. `domain(email) == "example.com"`.
+#[derive(Clone)]
+pub struct CustomFunction(Arc<FunctionDefinition>);
+
+struct FunctionDefinition {
+    name: String,
+    arguments: Vec<FieldType>,
+    return_type: FieldType,
+    implementation: FunctionImplementation,
+}
+
+impl CustomFunction {
+    pub fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    /// The types of the arguments the function accepts.
+    pub fn arguments(&self) -> &[FieldType] {
+        &self.0.arguments
+    }
+
+    /// The type of the value the function returns.
+    pub fn return_type(&self) -> FieldType {
+        self.0.return_type
+    }
+
+    /// Evaluates the function. Returns `None` if any argument could not be resolved.
+    pub fn call(&self, arguments: Vec<Option<Value>>) -> Option<Value> {
+        let arguments = arguments.into_iter().collect::<Option<Vec<_>>>()?;
+        (self.0.implementation)(&arguments)
+    }
+}
+
+impl PartialEq for CustomFunction {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.name == other.0.name
+            && self.0.arguments == other.0.arguments
+            && self.0.return_type == other.0.return_type
+    }
+}
+
+impl Eq for CustomFunction {}
+
+impl std::hash::Hash for CustomFunction {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.name.hash(state);
+    }
+}
+
+impl fmt::Debug for CustomFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CustomFunction")
+            .field("name", &self.0.name)
+            .field("arguments", &self.0.arguments)
+            .field("return_type", &self.0.return_type)
+            .finish()
+    }
+}
+
+/// A set of user-defined operators and functions that a `Parser` accepts in addition to the
+/// built-in ones.
+#[derive(Debug, Clone, Default)]
+pub struct Registry {
+    operators: HashMap<String, CustomOperator>,
+    functions: HashMap<String, CustomFunction>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a binary operator that can be applied to fields of `field_types` with a value of
+    /// `value_type`. Operator names must be identifiers and do not override built-in operators.
+    pub fn register_operator<F>(
+        &mut self,
+        name: &str,
+        field_types: Vec<FieldType>,
+        value_type: FieldType,
+        implementation: F,
+    ) -> &mut Self
+    where
+        F: Fn(&Value, &Value) -> bool + Send + Sync + 'static,
+    {
+        self.operators.insert(
+            name.to_string(),
+            CustomOperator(Arc::new(OperatorDefinition {
+                name: name.to_string(),
+                field_types,
+                value_type,
+                implementation: Box::new(implementation),
+            })),
+        );
+        self
+    }
+
+    /// Registers a function taking `arguments` and returning a value of `return_type`. Function
+    /// names are case-insensitive and do not override built-in functions.
+    pub fn register_function<F>(
+        &mut self,
+        name: &str,
+        arguments: Vec<FieldType>,
+        return_type: FieldType,
+        implementation: F,
+    ) -> &mut Self
+    where
+        F: Fn(&[Value]) -> Option<Value> + Send + Sync + 'static,
+    {
+        self.functions.insert(
+            name.to_lowercase(),
+            CustomFunction(Arc::new(FunctionDefinition {
+                name: name.to_string(),
+                arguments,
+                return_type,
+                implementation: Box::new(implementation),
+            })),
+        );
+        self
+    }
+
+    pub fn operator(&self, name: &str) -> Option<&CustomOperator> {
+        self.operators.get(name)
+    }
+
+    pub(crate) fn operator_names(&self) -> impl Iterator<Item = &str> {
+        self.operators.keys().map(String::as_str)
+    }
+
+    pub(crate) fn functions(&self) -> impl Iterator<Item = &CustomFunction> {
+        self.functions.values()
+    }
+
+    pub fn function(&self, name: &str) -> Option<&CustomFunction> {
+        self.functions.get(&name.to_lowercase())
+    }
+}
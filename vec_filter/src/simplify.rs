@@ -0,0 +1,302 @@
+use crate::{Operand, AST};
+
+/// The connective of a chain of `And` or `Or` nodes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Connective {
+    And,
+    Or,
+}
+
+impl Connective {
+    pub(crate) fn of<P>(ast: &AST<P>) -> Option<Self> {
+        match ast {
+            AST::And(..) => Some(Connective::And),
+            AST::Or(..) => Some(Connective::Or),
+            _ => None,
+        }
+    }
+
+    fn dual(self) -> Self {
+        match self {
+            Connective::And => Connective::Or,
+            Connective::Or => Connective::And,
+        }
+    }
+
+    /// The constant that leaves a chain unchanged: `true` for `&&` and `false` for `||`.
+    pub(crate) fn identity<P>(self) -> AST<P> {
+        match self {
+            Connective::And => AST::True,
+            Connective::Or => AST::False,
+        }
+    }
+
+    /// Joins the operands into a balanced tree, so a long chain is only as deep as the logarithm of
+    /// its length. An empty chain is the identity.
+    pub(crate) fn join<P>(self, mut operands: Vec<AST<P>>) -> AST<P> {
+        match operands.len() {
+            0 => return self.identity(),
+            1 => return operands.pop().unwrap(),
+            _ => {}
+        }
+        let right = operands.split_off(operands.len() / 2);
+        let left = Box::new(self.join(operands));
+        let right = Box::new(self.join(right));
+        match self {
+            Connective::And => AST::And(left, right),
+            Connective::Or => AST::Or(left, right),
+        }
+    }
+
+    /// Pushes the operands of a chain of this connective onto `operands`, or `ast` itself if it is
+    /// not such a chain.
+    pub(crate) fn flatten<P>(self, ast: AST<P>, operands: &mut Vec<AST<P>>) {
+        match (self, ast) {
+            (Connective::And, AST::And(left, right)) | (Connective::Or, AST::Or(left, right)) => {
+                self.flatten(*left, operands);
+                self.flatten(*right, operands);
+            }
+            (_, ast) => operands.push(ast),
+        }
+    }
+
+    /// Like `flatten`, by reference.
+    fn operands<'a, P>(self, ast: &'a AST<P>, operands: &mut Vec<&'a AST<P>>) {
+        match (self, ast) {
+            (Connective::And, AST::And(left, right)) | (Connective::Or, AST::Or(left, right)) => {
+                self.operands(left, operands);
+                self.operands(right, operands);
+            }
+            (_, ast) => operands.push(ast),
+        }
+    }
+}
+
+impl<P> AST<P> {
+    /// Returns the operands of a chain of `And` nodes, however it is nested, e.g. `a`, `b` and `c`
+    /// for `(a && b) && c`. Any other node is a chain of one.
+    pub fn conjuncts(&self) -> Vec<&AST<P>> {
+        let mut operands = Vec::new();
+        Connective::And.operands(self, &mut operands);
+        operands
+    }
+
+    /// Returns the operands of a chain of `Or` nodes, however it is nested. See `conjuncts`.
+    pub fn disjuncts(&self) -> Vec<&AST<P>> {
+        let mut operands = Vec::new();
+        Connective::Or.operands(self, &mut operands);
+        operands
+    }
+}
+
+impl<P: Clone + PartialEq> AST<P> {
+    /// Returns an equivalent AST with redundancy removed:
+    ///
+    /// * negations are pushed down to the comparisons with De Morgan's laws, removing double
+    ///   negations, and a negated comparison is replaced by the opposite operator where there is
+    ///   one (`!(name == "a")` becomes `name != "a"`);
+    /// * repeated operands of `&&` and `||` are removed (`a && a` becomes `a`);
+    /// * operands implied by another operand are absorbed (`a && (a || b)` becomes `a`);
+    /// * `true` and `false` are folded away (`a && true` becomes `a`, and `a || true` becomes
+    ///   `true`);
+    /// * chains of `&&` and `||` are rebuilt as balanced trees, however they were nested.
+    ///
+    /// The result matches exactly the same items as the original.
+    pub fn simplify(&self) -> AST<P> {
+        simplify(negation_normal_form(self.clone(), false))
+    }
+
+    /// Converts the AST to conjunctive normal form, an `&&` of `||`s of comparisons, which are
+    /// negated only where the opposite operator does not exist. The result is simplified like
+    /// `simplify`. Converting can make the AST exponentially larger.
+    pub fn to_cnf(&self) -> AST<P> {
+        normal_form(self.clone(), Connective::And)
+    }
+
+    /// Converts the AST to disjunctive normal form, an `||` of `&&`s of comparisons. See `to_cnf`.
+    pub fn to_dnf(&self) -> AST<P> {
+        normal_form(self.clone(), Connective::Or)
+    }
+}
+
+/// Pushes negations down to the predicates, negating `ast` itself if `negate` is set.
+fn negation_normal_form<P>(ast: AST<P>, negate: bool) -> AST<P> {
+    match ast {
+        AST::Not(ast) => negation_normal_form(*ast, !negate),
+        AST::True if negate => AST::False,
+        AST::False if negate => AST::True,
+        // De Morgan: !(a && b) is !a || !b, and !(a || b) is !a && !b.
+        AST::And(left, right) => {
+            let left = Box::new(negation_normal_form(*left, negate));
+            let right = Box::new(negation_normal_form(*right, negate));
+            match negate {
+                true => AST::Or(left, right),
+                false => AST::And(left, right),
+            }
+        }
+        AST::Or(left, right) => {
+            let left = Box::new(negation_normal_form(*left, negate));
+            let right = Box::new(negation_normal_form(*right, negate));
+            match negate {
+                true => AST::And(left, right),
+                false => AST::Or(left, right),
+            }
+        }
+        ast if negate => negate_predicate(ast),
+        ast => ast,
+    }
+}
+
+/// Negates a predicate, using the opposite operator if there is one that matches exactly the
+/// items the predicate does not.
+fn negate_predicate<P>(ast: AST<P>) -> AST<P> {
+    let negated = ast.operator().and_then(|operator| operator.negate());
+    match (ast, negated) {
+        (AST::Like { field, pattern }, _) => AST::NotLike { field, pattern },
+        (AST::NotLike { field, pattern }, _) => AST::Like { field, pattern },
+        // Unbound parameters never match, through either operator.
+        (ast, Some(_)) if has_param(&ast) => AST::Not(Box::new(ast)),
+        (
+            AST::Deferred {
+                expr,
+                operator: _,
+                operand,
+            },
+            Some(operator),
+        ) => AST::Deferred {
+            expr,
+            operator,
+            operand,
+        },
+        (
+            AST::Compare {
+                expr,
+                operator: _,
+                value,
+            },
+            Some(operator),
+        ) => AST::Compare {
+            expr,
+            operator,
+            value,
+        },
+        (
+            AST::Equals { field, value }
+            | AST::NotEquals { field, value }
+            | AST::In { field, value }
+            | AST::NotIn { field, value }
+            | AST::Contains { field, value }
+            | AST::NotContains { field, value }
+            | AST::StartsWith { field, value }
+            | AST::NotStartsWith { field, value }
+            | AST::EndsWith { field, value }
+            | AST::NotEndsWith { field, value }
+            | AST::RegexMatch { field, value }
+            | AST::NotRegexMatch { field, value },
+            Some(operator),
+        ) => AST::comparison(field, operator, value),
+        (ast, _) => AST::Not(Box::new(ast)),
+    }
+}
+
+/// Whether a predicate compares against an unbound parameter.
+fn has_param<P>(ast: &AST<P>) -> bool {
+    matches!(
+        ast,
+        AST::Deferred {
+            operand: Operand::Param(_),
+            ..
+        }
+    )
+}
+
+/// Simplifies an AST in negation normal form.
+fn simplify<P: Clone + PartialEq>(ast: AST<P>) -> AST<P> {
+    let connective = match Connective::of(&ast) {
+        Some(connective) => connective,
+        None => return ast,
+    };
+    let mut chain = Vec::new();
+    connective.flatten(ast, &mut chain);
+
+    // Simplifying an operand can turn it into a chain of the same connective, e.g. when both
+    // sides of an `||` are the same `&&`.
+    let (identity, absorbing) = (connective.identity(), connective.dual().identity());
+    let mut operands: Vec<AST<P>> = Vec::new();
+    for operand in chain {
+        let mut simplified = Vec::new();
+        connective.flatten(simplify(operand), &mut simplified);
+        for operand in simplified {
+            if operand == absorbing {
+                return absorbing;
+            }
+            if operand != identity && !operands.contains(&operand) {
+                operands.push(operand);
+            }
+        }
+    }
+
+    // An operand is absorbed by another whose dual chain is a subset of its own: `a && (a || b)`
+    // is `a`, and `a || (a && b)` is `a`. Smaller chains are kept first, so of two operands with
+    // the same chain in a different order only the first is kept.
+    let dual = connective.dual();
+    let chains: Vec<Vec<&AST<P>>> = operands
+        .iter()
+        .map(|operand| {
+            let mut chain = Vec::new();
+            dual.operands(operand, &mut chain);
+            chain
+        })
+        .collect();
+    let mut order: Vec<usize> = (0..operands.len()).collect();
+    order.sort_by_key(|&i| chains[i].len());
+    let mut kept = vec![false; operands.len()];
+    for i in order {
+        let absorbed = (0..operands.len())
+            .any(|j| kept[j] && chains[j].iter().all(|operand| chains[i].contains(operand)));
+        kept[i] = !absorbed;
+    }
+
+    let operands = operands
+        .into_iter()
+        .zip(kept)
+        .filter(|(_, kept)| *kept)
+        .map(|(operand, _)| operand)
+        .collect();
+    connective.join(operands)
+}
+
+/// Converts an AST to a chain of `outer` whose operands are chains of the dual connective.
+fn normal_form<P: Clone + PartialEq>(ast: AST<P>, outer: Connective) -> AST<P> {
+    let clauses = clauses(negation_normal_form(ast, false), outer)
+        .into_iter()
+        .map(|clause| outer.dual().join(clause))
+        .collect();
+    simplify(outer.join(clauses))
+}
+
+/// Returns the clauses of an AST in negation normal form: the operands of `outer`, each a list of
+/// the operands of the dual connective.
+fn clauses<P: Clone>(ast: AST<P>, outer: Connective) -> Vec<Vec<AST<P>>> {
+    let connective = match Connective::of(&ast) {
+        Some(connective) => connective,
+        None => return vec![vec![ast]],
+    };
+    let (left, right) = match ast {
+        AST::And(left, right) | AST::Or(left, right) => (*left, *right),
+        _ => unreachable!(),
+    };
+    let (mut left, right) = (clauses(left, outer), clauses(right, outer));
+    if connective == outer {
+        left.extend(right);
+        return left;
+    }
+    // Distribute the dual connective over `outer`: (a && b) || c is (a || c) && (b || c).
+    let mut product = Vec::with_capacity(left.len() * right.len());
+    for left in &left {
+        for right in &right {
+            product.push(left.iter().chain(right).cloned().collect());
+        }
+    }
+    product
+}
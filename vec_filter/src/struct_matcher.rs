@@ -1,7 +1,10 @@
-use crate::{Value, AST};
+#[cfg(feature = "chrono")]
+use crate::temporal;
+use crate::{Expr, Operand, Operator, Pattern, Value, AST};
 use core::fmt::Debug;
 use core::fmt::Display;
 use regex::Regex;
+use std::ops::{Bound, RangeBounds};
 use std::str::FromStr;
 
 pub trait StructProperties: FromStr + Sized + Debug + Display + Clone {
@@ -15,62 +18,154 @@ pub trait StructMatcher<P>: Sized {
 
     fn matches_ast(&self, ast: &AST<P>) -> bool {
         match ast {
-            AST::Equals { field: _, value: _ }
-            | AST::NotEquals { field: _, value: _ }
-            | AST::GreaterThan { field: _, value: _ }
-            | AST::LessThan { field: _, value: _ }
-            | AST::GreaterThanOrEqual { field: _, value: _ }
-            | AST::LessThanOrEqual { field: _, value: _ } => self.internal_matches_ast(ast),
-            AST::Contains { field, value } => self.matches_contains(field, value),
-            AST::StartsWith { field, value } => self.starts_with(field, value),
-            AST::EndsWith { field, value } => self.ends_with(field, value),
-            AST::RegexMatch { field, value } => self.regex_match(field, value),
-            AST::In { field, value } => self.matches_in(field, value),
+            AST::Equals { field, value }
+            | AST::NotEquals { field, value }
+            | AST::GreaterThan { field, value }
+            | AST::LessThan { field, value }
+            | AST::GreaterThanOrEqual { field, value }
+            | AST::LessThanOrEqual { field, value }
+            | AST::Contains { field, value }
+            | AST::StartsWith { field, value }
+            | AST::EndsWith { field, value }
+            | AST::RegexMatch { field, value }
+            | AST::In { field, value }
+            | AST::NotIn { field, value }
+            | AST::NotContains { field, value }
+            | AST::NotStartsWith { field, value }
+            | AST::NotEndsWith { field, value }
+            | AST::NotRegexMatch { field, value } => self.matches_operator(
+                self.get_property_value(field),
+                &ast.operator()
+                    .expect("comparison nodes always have an operator"),
+                value,
+            ),
+            AST::Compare {
+                expr,
+                operator,
+                value,
+            } => self.matches_operator(self.evaluate(expr), operator, value),
+            // An unbound parameter never matches, whatever the operator. `!(age == $age)`
+            // negates that, so it matches every item.
+            AST::Deferred {
+                operand: Operand::Param(_),
+                ..
+            } => false,
+            #[cfg(feature = "chrono")]
+            AST::Deferred {
+                expr,
+                operator,
+                operand: Operand::Relative(time),
+            } => {
+                let actual = self.evaluate(expr);
+                match (time.resolve(), &actual) {
+                    // A date is compared with a date-time as midnight UTC, as when parsed.
+                    (Some(value), Some(Value::DateTime(_))) => {
+                        self.matches_operator(actual, operator, &temporal::coerce(value))
+                    }
+                    (Some(value), _) => self.matches_operator(actual, operator, &value),
+                    (None, _) => false,
+                }
+            }
+            AST::Between {
+                field,
+                lower,
+                upper,
+            } => self.matches_between(self.get_property_value(field), lower, upper),
+            AST::Like { field, pattern } => {
+                self.matches_like(self.get_property_value(field), pattern)
+            }
+            AST::NotLike { field, pattern } => {
+                !self.matches_like(self.get_property_value(field), pattern)
+            }
             AST::And(_, _) | AST::Or(_, _) => self.matches_and_or(ast),
             AST::Not(expr) => !self.matches_ast(expr),
+            AST::True => true,
+            AST::False => false,
             AST::InvalidField { field_name: _ } => unimplemented!("This should never be called"),
         }
     }
 }
 
 trait StructMatcherExt<P>: StructMatcher<P> {
-    fn internal_matches_ast(&self, ast: &AST<P>) -> bool;
-    fn matches_contains(&self, field: &P, value: &Value) -> bool;
-    fn matches_in(&self, field: &P, value: &Value) -> bool;
+    fn evaluate(&self, expr: &Expr<P>) -> Option<Value>;
+    fn matches_operator(&self, actual: Option<Value>, operator: &Operator, value: &Value) -> bool;
+    fn internal_matches_ast(
+        &self,
+        actual: Option<Value>,
+        operator: &Operator,
+        value: &Value,
+    ) -> bool;
+    fn matches_contains(&self, actual: Option<Value>, value: &Value) -> bool;
+    fn matches_in(&self, actual: Option<Value>, value: &Value) -> bool;
     fn matches_and_or(&self, ast: &AST<P>) -> bool;
-    fn starts_with(&self, field: &P, value: &Value) -> bool;
-    fn ends_with(&self, field: &P, value: &Value) -> bool;
-    fn regex_match(&self, field: &P, pattern: &Value) -> bool;
+    fn matches_between(
+        &self,
+        actual: Option<Value>,
+        lower: &Bound<Value>,
+        upper: &Bound<Value>,
+    ) -> bool;
+    fn starts_with(&self, actual: Option<Value>, value: &Value) -> bool;
+    fn ends_with(&self, actual: Option<Value>, value: &Value) -> bool;
+    fn matches_like(&self, actual: Option<Value>, pattern: &Pattern) -> bool;
+    fn regex_match(&self, actual: Option<Value>, pattern: &Value) -> bool;
 }
 
 impl<T: StructMatcher<P>, P> StructMatcherExt<P> for T {
-    fn internal_matches_ast(&self, ast: &AST<P>) -> bool {
-        match ast {
-            AST::Equals { field, value } => self.get_property_value(field) == Some(value.clone()),
-            AST::NotEquals { field, value } => {
-                self.get_property_value(field) != Some(value.clone())
+    fn evaluate(&self, expr: &Expr<P>) -> Option<Value> {
+        match expr {
+            Expr::Field(field) => self.get_property_value(field),
+            Expr::Literal(value) => Some(value.clone()),
+            Expr::Call { function, args } => {
+                function.call(args.iter().map(|arg| self.evaluate(arg)).collect())
             }
-            AST::GreaterThan { field, value } => self
-                .get_property_value(field)
-                .map(|v| v > value.clone())
-                .unwrap_or(false),
-            AST::LessThan { field, value } => self
-                .get_property_value(field)
-                .map(|v| v < value.clone())
-                .unwrap_or(false),
-            AST::GreaterThanOrEqual { field, value } => self
-                .get_property_value(field)
-                .map(|v| v >= value.clone())
-                .unwrap_or(false),
-            AST::LessThanOrEqual { field, value } => self
-                .get_property_value(field)
-                .map(|v| v <= value.clone())
+        }
+    }
+
+    fn matches_operator(&self, actual: Option<Value>, operator: &Operator, value: &Value) -> bool {
+        match operator {
+            Operator::Equals
+            | Operator::NotEquals
+            | Operator::GreaterThan
+            | Operator::LessThan
+            | Operator::GreaterThanOrEqual
+            | Operator::LessThanOrEqual => self.internal_matches_ast(actual, operator, value),
+            Operator::Contains => self.matches_contains(actual, value),
+            Operator::StartsWith => self.starts_with(actual, value),
+            Operator::EndsWith => self.ends_with(actual, value),
+            Operator::RegexMatch => self.regex_match(actual, value),
+            Operator::In => self.matches_in(actual, value),
+            Operator::NotIn
+            | Operator::NotContains
+            | Operator::NotStartsWith
+            | Operator::NotEndsWith
+            | Operator::NotRegexMatch => {
+                !self.matches_operator(actual, &operator.negate().unwrap(), value)
+            }
+            Operator::Custom(operator) => actual
+                .map(|actual| operator.call(&actual, value))
                 .unwrap_or(false),
+        }
+    }
+
+    fn internal_matches_ast(
+        &self,
+        actual: Option<Value>,
+        operator: &Operator,
+        value: &Value,
+    ) -> bool {
+        match operator {
+            Operator::Equals => actual.as_ref() == Some(value),
+            Operator::NotEquals => actual.as_ref() != Some(value),
+            Operator::GreaterThan => actual.map(|v| v > *value).unwrap_or(false),
+            Operator::LessThan => actual.map(|v| v < *value).unwrap_or(false),
+            Operator::GreaterThanOrEqual => actual.map(|v| v >= *value).unwrap_or(false),
+            Operator::LessThanOrEqual => actual.map(|v| v <= *value).unwrap_or(false),
             _ => false,
         }
     }
-    fn matches_in(&self, field: &P, value: &Value) -> bool {
-        match (self.get_property_value(field), value) {
+
+    fn matches_in(&self, actual: Option<Value>, value: &Value) -> bool {
+        match (actual, value) {
             (Some(Value::String(ref s)), Value::String(ref sub)) => s.contains(sub),
             (Some(Value::VecString(ref v)), Value::String(ref sub)) => {
                 v.iter().any(|s| s.contains(sub))
@@ -81,6 +176,12 @@ impl<T: StructMatcher<P>, P> StructMatcherExt<P> for T {
             (Some(Value::VecString(ref v1)), Value::VecString(ref v2)) => {
                 v1.iter().any(|s| v2.iter().any(|sub| s.contains(sub)))
             }
+            (Some(Value::Int(ref n)), Value::Int(ref other)) => n == other,
+            (Some(Value::VecInt(ref v)), Value::Int(ref n)) => v.contains(n),
+            (Some(Value::Int(ref n)), Value::VecInt(ref v)) => v.contains(n),
+            (Some(Value::VecInt(ref v1)), Value::VecInt(ref v2)) => {
+                v1.iter().any(|n| v2.contains(n))
+            }
             _ => false,
         }
     }
@@ -97,8 +198,19 @@ impl<T: StructMatcher<P>, P> StructMatcherExt<P> for T {
         }
     }
 
-    fn starts_with(&self, field: &P, value: &Value) -> bool {
-        match (self.get_property_value(field), value) {
+    fn matches_between(
+        &self,
+        actual: Option<Value>,
+        lower: &Bound<Value>,
+        upper: &Bound<Value>,
+    ) -> bool {
+        actual
+            .map(|v| (lower.as_ref(), upper.as_ref()).contains(&&v))
+            .unwrap_or(false)
+    }
+
+    fn starts_with(&self, actual: Option<Value>, value: &Value) -> bool {
+        match (actual, value) {
             (Some(Value::String(ref s)), Value::String(ref prefix)) => s.starts_with(prefix),
             (Some(Value::VecString(ref v)), Value::String(ref prefix)) => {
                 v.iter().any(|s| s.starts_with(prefix))
@@ -107,8 +219,8 @@ impl<T: StructMatcher<P>, P> StructMatcherExt<P> for T {
         }
     }
 
-    fn ends_with(&self, field: &P, value: &Value) -> bool {
-        match (self.get_property_value(field), value) {
+    fn ends_with(&self, actual: Option<Value>, value: &Value) -> bool {
+        match (actual, value) {
             (Some(Value::String(ref s)), Value::String(ref suffix)) => s.ends_with(suffix),
             (Some(Value::VecString(ref v)), Value::String(ref suffix)) => {
                 v.iter().any(|s| s.ends_with(suffix))
@@ -117,30 +229,34 @@ impl<T: StructMatcher<P>, P> StructMatcherExt<P> for T {
         }
     }
 
-    fn matches_contains(&self, field: &P, value: &Value) -> bool {
+    fn matches_like(&self, actual: Option<Value>, pattern: &Pattern) -> bool {
+        match actual {
+            Some(Value::String(ref s)) => pattern.is_match(s),
+            Some(Value::VecString(ref v)) => v.iter().any(|s| pattern.is_match(s)),
+            _ => false,
+        }
+    }
+
+    fn matches_contains(&self, actual: Option<Value>, value: &Value) -> bool {
         let value = match value {
             Value::String(wrapped_value) => wrapped_value,
             _ => unimplemented!(),
         };
 
-        match self.get_property_value(field) {
-            Some(Value::String(ref s)) => {
-                s.contains(value)
-            }
-            Some(Value::VecString(ref v)) => {
-                v.iter().any(|s| s.contains(value))
-            }
+        match actual {
+            Some(Value::String(ref s)) => s.contains(value),
+            Some(Value::VecString(ref v)) => v.iter().any(|s| s.contains(value)),
             _ => false,
         }
     }
 
-    fn regex_match(&self, field: &P, pattern_value: &Value) -> bool {
+    fn regex_match(&self, actual: Option<Value>, pattern_value: &Value) -> bool {
         let pattern = match pattern_value {
             Value::String(pattern) => pattern,
             _ => unimplemented!(),
         };
 
-        match self.get_property_value(field) {
+        match actual {
             Some(Value::String(ref s)) => {
                 let regex = match Regex::new(pattern) {
                     Ok(regex) => regex,
@@ -0,0 +1,353 @@
+//! Date, time and duration literals, available with the `chrono` feature.
+
+use crate::parsers::{parse_value, ws0, ParseError, ParseResult, Rhs};
+use crate::{Operand, Value, Visitor, VisitorMut, AST};
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, Utc};
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take_while_m_n},
+    character::complete::{alphanumeric1, char, digit1, one_of},
+    combinator::{consumed, map, not, opt, peek, recognize, value},
+    error::ErrorKind,
+    multi::{many0, many1},
+    sequence::{delimited, pair, terminated, tuple},
+    Err,
+};
+use std::fmt;
+use std::sync::Arc;
+
+/// The time a `RelativeTime` is measured from.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum Anchor {
+    /// `now()`, the current date and time.
+    Now,
+    /// `today()`, the current date in UTC.
+    Today,
+}
+
+/// A time relative to when the query is matched: `now()` or `today()`, with durations added or
+/// subtracted, e.g. `now() - 7d`. It is resolved with the clock of the `Parser` that parsed it
+/// each time the query is applied, so a stored query keeps matching the last seven days.
+#[derive(Clone)]
+pub struct RelativeTime {
+    anchor: Anchor,
+    offsets: Vec<Duration>,
+    clock: Clock,
+}
+
+impl RelativeTime {
+    pub fn anchor(&self) -> Anchor {
+        self.anchor
+    }
+
+    /// The durations added to the anchor, in order. Subtracted durations are negative.
+    pub fn offsets(&self) -> &[Duration] {
+        &self.offsets
+    }
+
+    /// Returns the current value: a `Value::DateTime` for `now()` or a `Value::Date` for
+    /// `today()`, or `None` if adding the offsets overflows.
+    pub fn resolve(&self) -> Option<Value> {
+        let now = self.clock.now();
+        let anchor = match self.anchor {
+            Anchor::Now => Value::DateTime(now),
+            Anchor::Today => Value::Date(now.date_naive()),
+        };
+        self.offsets
+            .iter()
+            .try_fold(anchor, |value, offset| add(value, *offset))
+    }
+}
+
+/// Relative times are equal if they are written the same, whatever their clocks.
+impl PartialEq for RelativeTime {
+    fn eq(&self, other: &Self) -> bool {
+        self.anchor == other.anchor && self.offsets == other.offsets
+    }
+}
+
+impl fmt::Debug for RelativeTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RelativeTime")
+            .field("anchor", &self.anchor)
+            .field("offsets", &self.offsets)
+            .finish()
+    }
+}
+
+/// Writes the relative time in query syntax, e.g. `now() - 7d`.
+impl fmt::Display for RelativeTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.anchor {
+            Anchor::Now => f.write_str("now()")?,
+            Anchor::Today => f.write_str("today()")?,
+        }
+        for offset in &self.offsets {
+            match *offset < Duration::zero() {
+                true => {
+                    f.write_str(" - ")?;
+                    format_duration(&-*offset, f)?;
+                }
+                false => {
+                    f.write_str(" + ")?;
+                    format_duration(offset, f)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The source of the current time used to resolve `now()` and `today()`.
+#[derive(Clone)]
+pub(crate) struct Clock(Arc<dyn Fn() -> DateTime<Utc> + Send + Sync>);
+
+impl Clock {
+    pub(crate) fn new<F>(clock: F) -> Self
+    where
+        F: Fn() -> DateTime<Utc> + Send + Sync + 'static,
+    {
+        Clock(Arc::new(clock))
+    }
+
+    pub(crate) fn now(&self) -> DateTime<Utc> {
+        (self.0)()
+    }
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Clock::new(Utc::now)
+    }
+}
+
+impl fmt::Debug for Clock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Clock")
+    }
+}
+
+/// Reads the clock of each relative time in `ast` once and returns a copy in which those times
+/// always resolve to what was read, so that every item and node matched by one `apply` sees the
+/// same `now()`. Returns `None` if `ast` has no relative times.
+pub(crate) fn read_clocks<P: Clone>(ast: &AST<P>) -> Option<AST<P>> {
+    let mut finder = FindRelative(false);
+    finder.visit_ast(ast);
+    if !finder.0 {
+        return None;
+    }
+    let mut ast = ast.clone();
+    ReadClocks(Vec::new()).visit_ast_mut(&mut ast);
+    Some(ast)
+}
+
+struct FindRelative(bool);
+
+impl<'ast, P: 'ast> Visitor<'ast, P> for FindRelative {
+    fn visit_operand(&mut self, operand: &'ast Operand) {
+        self.0 |= matches!(operand, Operand::Relative(_));
+    }
+}
+
+/// Replaces the clock of each relative time with the time it read, reading each clock only once.
+struct ReadClocks(Vec<(Clock, DateTime<Utc>)>);
+
+impl<P> VisitorMut<P> for ReadClocks {
+    fn visit_operand_mut(&mut self, operand: &mut Operand) {
+        let time = match operand {
+            Operand::Relative(time) => time,
+            Operand::Param(_) => return,
+        };
+        let now = match self
+            .0
+            .iter()
+            .find(|(clock, _)| Arc::ptr_eq(&clock.0, &time.clock.0))
+        {
+            Some((_, now)) => *now,
+            None => {
+                let now = time.clock.now();
+                self.0.push((time.clock.clone(), now));
+                now
+            }
+        };
+        time.clock = Clock::new(move || now);
+    }
+}
+
+fn digits(count: usize) -> impl Fn(&str) -> ParseResult<'_, &str> {
+    move |input| take_while_m_n(count, count, |c: char| c.is_ascii_digit())(input)
+}
+
+fn syntax_error(input: &str) -> Err<ParseError<&str>> {
+    Err::Error(ParseError::Nom(input, ErrorKind::Verify))
+}
+
+/// Parses a date, a date-time or a duration literal: `2024-01-01`, `2024-01-01T09:30:00Z`,
+/// `2024-01-01T09:30:00.5+02:00` or `1h30m`.
+pub(crate) fn parse_literal(input: &str) -> ParseResult<'_, Value> {
+    alt((parse_datetime, parse_date, parse_duration))(input)
+}
+
+fn parse_date_part(input: &str) -> ParseResult<'_, &str> {
+    recognize(tuple((
+        digits(4),
+        char('-'),
+        digits(2),
+        char('-'),
+        digits(2),
+    )))(input)
+}
+
+fn parse_datetime(input: &str) -> ParseResult<'_, Value> {
+    let (rest, literal) = recognize(tuple((
+        parse_date_part,
+        one_of("Tt"),
+        digits(2),
+        char(':'),
+        digits(2),
+        char(':'),
+        digits(2),
+        opt(pair(char('.'), digit1)),
+        alt((
+            recognize(one_of("Zz")),
+            recognize(tuple((one_of("+-"), digits(2), char(':'), digits(2)))),
+        )),
+    )))(input)?;
+    match DateTime::parse_from_rfc3339(literal) {
+        Ok(datetime) => Ok((rest, Value::DateTime(datetime.with_timezone(&Utc)))),
+        Err(_) => Err(syntax_error(input)),
+    }
+}
+
+fn parse_date(input: &str) -> ParseResult<'_, Value> {
+    let (rest, literal) = parse_date_part(input)?;
+    match NaiveDate::parse_from_str(literal, "%Y-%m-%d") {
+        Ok(date) => Ok((rest, Value::Date(date))),
+        Err(_) => Err(syntax_error(input)),
+    }
+}
+
+fn parse_duration(input: &str) -> ParseResult<'_, Value> {
+    map(duration, Value::Duration)(input)
+}
+
+/// Parses a duration made of one or more amounts with a unit, e.g. `7d`, `-90s` or `1h30m`.
+/// The units are `w`, `d`, `h`, `m`, `s` and `ms`.
+fn duration(input: &str) -> ParseResult<'_, Duration> {
+    let (rest, (literal, (negative, parts))) = consumed(terminated(
+        pair(
+            opt(char('-')),
+            many1(pair(
+                digit1,
+                alt((tag("ms"), tag("w"), tag("d"), tag("h"), tag("m"), tag("s"))),
+            )),
+        ),
+        not(peek(alphanumeric1)),
+    ))(input)?;
+
+    let milliseconds = parts.iter().try_fold(0i64, |total, (amount, unit)| {
+        let scale = match *unit {
+            "w" => 7 * 24 * 60 * 60 * 1000,
+            "d" => 24 * 60 * 60 * 1000,
+            "h" => 60 * 60 * 1000,
+            "m" => 60 * 1000,
+            "s" => 1000,
+            _ => 1,
+        };
+        amount
+            .parse::<i64>()
+            .ok()?
+            .checked_mul(scale)?
+            .checked_add(total)
+    });
+    match milliseconds.and_then(Duration::try_milliseconds) {
+        Some(duration) if negative.is_some() => Ok((rest, -duration)),
+        Some(duration) => Ok((rest, duration)),
+        None => Err(Err::Failure(ParseError::NumberOutOfRange { literal })),
+    }
+}
+
+/// Parses a value that may be relative to the current time: `now()`, `today()`, or a value
+/// followed by durations to add or subtract, e.g. `now() - 7d` or `2024-01-01 + 1w`. A value
+/// relative to `now()` or `today()` is kept as a `RelativeTime` resolved with `clock` when the
+/// query is matched, and other values are added up when the query is parsed.
+pub(crate) fn parse_relative<'a>(input: &'a str, clock: &Clock) -> ParseResult<'a, Rhs> {
+    if let Ok((rest, anchor)) = parse_anchor(input) {
+        let (rest, offsets) = parse_offsets(rest)?;
+        let time = RelativeTime {
+            anchor,
+            offsets,
+            clock: clock.clone(),
+        };
+        // Offsets that overflow are rejected like those on a literal, rather than never matching.
+        time.resolve().ok_or_else(|| syntax_error(input))?;
+        return Ok((rest, Rhs::Deferred(Operand::Relative(time))));
+    }
+
+    let (rest, base) = parse_value(input)?;
+    let (rest, offsets) = parse_offsets(rest)?;
+    let value = offsets
+        .into_iter()
+        .try_fold(base, add)
+        .ok_or_else(|| syntax_error(input))?;
+    Ok((rest, Rhs::Value(value)))
+}
+
+fn parse_anchor(input: &str) -> ParseResult<'_, Anchor> {
+    alt((
+        value(Anchor::Now, tuple((tag("now"), ws0, tag("()")))),
+        value(Anchor::Today, tuple((tag("today"), ws0, tag("()")))),
+    ))(input)
+}
+
+/// Parses durations to add or subtract, e.g. ` - 7d + 12h`, returning subtracted ones negated.
+fn parse_offsets(input: &str) -> ParseResult<'_, Vec<Duration>> {
+    many0(map(
+        pair(delimited(ws0, one_of("+-"), ws0), duration),
+        |(sign, duration)| match sign {
+            '-' => -duration,
+            _ => duration,
+        },
+    ))(input)
+}
+
+fn add(value: Value, duration: Duration) -> Option<Value> {
+    match value {
+        Value::DateTime(datetime) => datetime.checked_add_signed(duration).map(Value::DateTime),
+        Value::Date(date) => date.checked_add_signed(duration).map(Value::Date),
+        Value::Duration(other) => other.checked_add(&duration).map(Value::Duration),
+        _ => None,
+    }
+}
+
+/// Converts a date compared against a date-time field to midnight UTC on that date.
+pub(crate) fn coerce(value: Value) -> Value {
+    match value {
+        Value::Date(date) => Value::DateTime(date.and_time(NaiveTime::MIN).and_utc()),
+        value => value,
+    }
+}
+
+/// Writes a duration in the literal syntax, e.g. `1d12h` or `-500ms`.
+pub(crate) fn format_duration(duration: &Duration, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    if duration.is_zero() {
+        return f.write_str("0s");
+    }
+    if *duration < Duration::zero() {
+        f.write_str("-")?;
+    }
+    let mut milliseconds = duration.num_milliseconds().unsigned_abs();
+    for (unit, scale) in [
+        ("d", 24 * 60 * 60 * 1000),
+        ("h", 60 * 60 * 1000),
+        ("m", 60 * 1000),
+        ("s", 1000),
+        ("ms", 1),
+    ] {
+        if milliseconds >= scale {
+            write!(f, "{}{}", milliseconds / scale, unit)?;
+            milliseconds %= scale;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,385 @@
+use crate::{Expr, Operand, Pattern, Value, AST};
+use std::ops::Bound;
+
+/// Walks an `AST` by reference. Every method has a default that visits the children of the node,
+/// so an implementation only overrides the nodes it is interested in. An overriding method can
+/// call the matching `walk_*` function to keep visiting the children.
+///
+/// `visit_ast` is called for every node, `visit_predicate` for every comparison, range, pattern
+/// and invalid field (the leaves of the tree), and `visit_field`, `visit_value`, `visit_pattern`
+/// and `visit_operand` for the parts of each predicate, including the fields and literals inside
+/// function calls.
+pub trait Visitor<'ast, P: 'ast> {
+    fn visit_ast(&mut self, ast: &'ast AST<P>) {
+        walk_ast(self, ast)
+    }
+
+    fn visit_predicate(&mut self, ast: &'ast AST<P>) {
+        walk_predicate(self, ast)
+    }
+
+    fn visit_expr(&mut self, expr: &'ast Expr<P>) {
+        walk_expr(self, expr)
+    }
+
+    fn visit_field(&mut self, _field: &'ast P) {}
+
+    fn visit_value(&mut self, _value: &'ast Value) {}
+
+    fn visit_pattern(&mut self, _pattern: &'ast Pattern) {}
+
+    fn visit_operand(&mut self, _operand: &'ast Operand) {}
+}
+
+/// Visits the children of a node: both sides of `And` and `Or`, the inside of `Not`, or the
+/// predicate itself. `True` and `False` have no children.
+pub fn walk_ast<'ast, P: 'ast, V: Visitor<'ast, P> + ?Sized>(visitor: &mut V, ast: &'ast AST<P>) {
+    match ast {
+        AST::And(left, right) | AST::Or(left, right) => {
+            visitor.visit_ast(left);
+            visitor.visit_ast(right);
+        }
+        AST::Not(ast) => visitor.visit_ast(ast),
+        AST::True | AST::False => {}
+        predicate => visitor.visit_predicate(predicate),
+    }
+}
+
+/// Visits the field or expression, the values and the pattern of a predicate.
+pub fn walk_predicate<'ast, P: 'ast, V: Visitor<'ast, P> + ?Sized>(
+    visitor: &mut V,
+    ast: &'ast AST<P>,
+) {
+    match ast {
+        AST::Equals { field, value }
+        | AST::NotEquals { field, value }
+        | AST::In { field, value }
+        | AST::NotIn { field, value }
+        | AST::Contains { field, value }
+        | AST::NotContains { field, value }
+        | AST::GreaterThan { field, value }
+        | AST::LessThan { field, value }
+        | AST::GreaterThanOrEqual { field, value }
+        | AST::LessThanOrEqual { field, value }
+        | AST::StartsWith { field, value }
+        | AST::NotStartsWith { field, value }
+        | AST::EndsWith { field, value }
+        | AST::NotEndsWith { field, value }
+        | AST::RegexMatch { field, value }
+        | AST::NotRegexMatch { field, value } => {
+            visitor.visit_field(field);
+            visitor.visit_value(value);
+        }
+        AST::Like { field, pattern } | AST::NotLike { field, pattern } => {
+            visitor.visit_field(field);
+            visitor.visit_pattern(pattern);
+        }
+        AST::Between {
+            field,
+            lower,
+            upper,
+        } => {
+            visitor.visit_field(field);
+            for bound in [lower, upper] {
+                if let Bound::Included(value) | Bound::Excluded(value) = bound {
+                    visitor.visit_value(value);
+                }
+            }
+        }
+        AST::Compare { expr, value, .. } => {
+            visitor.visit_expr(expr);
+            visitor.visit_value(value);
+        }
+        AST::Deferred { expr, operand, .. } => {
+            visitor.visit_expr(expr);
+            visitor.visit_operand(operand);
+        }
+        AST::InvalidField { .. } => {}
+        AST::And(..) | AST::Or(..) | AST::Not(..) | AST::True | AST::False => {
+            walk_ast(visitor, ast)
+        }
+    }
+}
+
+/// Visits the field, the literal or the arguments of an expression.
+pub fn walk_expr<'ast, P: 'ast, V: Visitor<'ast, P> + ?Sized>(
+    visitor: &mut V,
+    expr: &'ast Expr<P>,
+) {
+    match expr {
+        Expr::Field(field) => visitor.visit_field(field),
+        Expr::Literal(value) => visitor.visit_value(value),
+        Expr::Call { args, .. } => {
+            for arg in args {
+                visitor.visit_expr(arg);
+            }
+        }
+    }
+}
+
+/// Walks an `AST` by mutable reference, to change it in place. This mirrors `Visitor`.
+pub trait VisitorMut<P> {
+    fn visit_ast_mut(&mut self, ast: &mut AST<P>) {
+        walk_ast_mut(self, ast)
+    }
+
+    fn visit_predicate_mut(&mut self, ast: &mut AST<P>) {
+        walk_predicate_mut(self, ast)
+    }
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr<P>) {
+        walk_expr_mut(self, expr)
+    }
+
+    fn visit_field_mut(&mut self, _field: &mut P) {}
+
+    fn visit_value_mut(&mut self, _value: &mut Value) {}
+
+    fn visit_pattern_mut(&mut self, _pattern: &mut Pattern) {}
+
+    fn visit_operand_mut(&mut self, _operand: &mut Operand) {}
+}
+
+/// Visits the children of a node. See `walk_ast`.
+pub fn walk_ast_mut<P, V: VisitorMut<P> + ?Sized>(visitor: &mut V, ast: &mut AST<P>) {
+    match ast {
+        AST::And(left, right) | AST::Or(left, right) => {
+            visitor.visit_ast_mut(left);
+            visitor.visit_ast_mut(right);
+        }
+        AST::Not(ast) => visitor.visit_ast_mut(ast),
+        AST::True | AST::False => {}
+        predicate => visitor.visit_predicate_mut(predicate),
+    }
+}
+
+/// Visits the parts of a predicate. See `walk_predicate`.
+pub fn walk_predicate_mut<P, V: VisitorMut<P> + ?Sized>(visitor: &mut V, ast: &mut AST<P>) {
+    match ast {
+        AST::Equals { field, value }
+        | AST::NotEquals { field, value }
+        | AST::In { field, value }
+        | AST::NotIn { field, value }
+        | AST::Contains { field, value }
+        | AST::NotContains { field, value }
+        | AST::GreaterThan { field, value }
+        | AST::LessThan { field, value }
+        | AST::GreaterThanOrEqual { field, value }
+        | AST::LessThanOrEqual { field, value }
+        | AST::StartsWith { field, value }
+        | AST::NotStartsWith { field, value }
+        | AST::EndsWith { field, value }
+        | AST::NotEndsWith { field, value }
+        | AST::RegexMatch { field, value }
+        | AST::NotRegexMatch { field, value } => {
+            visitor.visit_field_mut(field);
+            visitor.visit_value_mut(value);
+        }
+        AST::Like { field, pattern } | AST::NotLike { field, pattern } => {
+            visitor.visit_field_mut(field);
+            visitor.visit_pattern_mut(pattern);
+        }
+        AST::Between {
+            field,
+            lower,
+            upper,
+        } => {
+            visitor.visit_field_mut(field);
+            for bound in [lower, upper] {
+                if let Bound::Included(value) | Bound::Excluded(value) = bound {
+                    visitor.visit_value_mut(value);
+                }
+            }
+        }
+        AST::Compare { expr, value, .. } => {
+            visitor.visit_expr_mut(expr);
+            visitor.visit_value_mut(value);
+        }
+        AST::Deferred { expr, operand, .. } => {
+            visitor.visit_expr_mut(expr);
+            visitor.visit_operand_mut(operand);
+        }
+        AST::InvalidField { .. } => {}
+        AST::And(..) | AST::Or(..) | AST::Not(..) | AST::True | AST::False => {
+            walk_ast_mut(visitor, ast)
+        }
+    }
+}
+
+/// Visits the parts of an expression. See `walk_expr`.
+pub fn walk_expr_mut<P, V: VisitorMut<P> + ?Sized>(visitor: &mut V, expr: &mut Expr<P>) {
+    match expr {
+        Expr::Field(field) => visitor.visit_field_mut(field),
+        Expr::Literal(value) => visitor.visit_value_mut(value),
+        Expr::Call { args, .. } => {
+            for arg in args {
+                visitor.visit_expr_mut(arg);
+            }
+        }
+    }
+}
+
+/// Rebuilds an `AST<P>` as an `AST<Q>`, converting each field with `fold_field`. Values and
+/// patterns are kept as they are unless `fold_value` or `fold_pattern` is overridden, and
+/// operands are always kept.
+pub trait Fold<P, Q> {
+    fn fold_ast(&mut self, ast: AST<P>) -> AST<Q> {
+        fold_ast(self, ast)
+    }
+
+    fn fold_expr(&mut self, expr: Expr<P>) -> Expr<Q> {
+        fold_expr(self, expr)
+    }
+
+    fn fold_field(&mut self, field: P) -> Q;
+
+    fn fold_value(&mut self, value: Value) -> Value {
+        value
+    }
+
+    fn fold_pattern(&mut self, pattern: Pattern) -> Pattern {
+        pattern
+    }
+}
+
+/// Folds the children of a node and rebuilds it.
+pub fn fold_ast<P, Q, F: Fold<P, Q> + ?Sized>(folder: &mut F, ast: AST<P>) -> AST<Q> {
+    let operator = ast.operator();
+    match ast {
+        AST::Equals { field, value }
+        | AST::NotEquals { field, value }
+        | AST::In { field, value }
+        | AST::NotIn { field, value }
+        | AST::Contains { field, value }
+        | AST::NotContains { field, value }
+        | AST::GreaterThan { field, value }
+        | AST::LessThan { field, value }
+        | AST::GreaterThanOrEqual { field, value }
+        | AST::LessThanOrEqual { field, value }
+        | AST::StartsWith { field, value }
+        | AST::NotStartsWith { field, value }
+        | AST::EndsWith { field, value }
+        | AST::NotEndsWith { field, value }
+        | AST::RegexMatch { field, value }
+        | AST::NotRegexMatch { field, value } => AST::comparison(
+            folder.fold_field(field),
+            operator.expect("comparison nodes always have an operator"),
+            folder.fold_value(value),
+        ),
+        AST::Like { field, pattern } => AST::Like {
+            field: folder.fold_field(field),
+            pattern: folder.fold_pattern(pattern),
+        },
+        AST::NotLike { field, pattern } => AST::NotLike {
+            field: folder.fold_field(field),
+            pattern: folder.fold_pattern(pattern),
+        },
+        AST::Between {
+            field,
+            lower,
+            upper,
+        } => AST::Between {
+            field: folder.fold_field(field),
+            lower: lower.map(|value| folder.fold_value(value)),
+            upper: upper.map(|value| folder.fold_value(value)),
+        },
+        AST::Compare {
+            expr,
+            operator,
+            value,
+        } => AST::Compare {
+            expr: folder.fold_expr(expr),
+            operator,
+            value: folder.fold_value(value),
+        },
+        AST::Deferred {
+            expr,
+            operator,
+            operand,
+        } => AST::Deferred {
+            expr: folder.fold_expr(expr),
+            operator,
+            operand,
+        },
+        AST::InvalidField { field_name } => AST::InvalidField { field_name },
+        AST::And(left, right) => AST::And(
+            Box::new(folder.fold_ast(*left)),
+            Box::new(folder.fold_ast(*right)),
+        ),
+        AST::Or(left, right) => AST::Or(
+            Box::new(folder.fold_ast(*left)),
+            Box::new(folder.fold_ast(*right)),
+        ),
+        AST::Not(ast) => AST::Not(Box::new(folder.fold_ast(*ast))),
+        AST::True => AST::True,
+        AST::False => AST::False,
+    }
+}
+
+/// Folds the field, the literal or the arguments of an expression.
+pub fn fold_expr<P, Q, F: Fold<P, Q> + ?Sized>(folder: &mut F, expr: Expr<P>) -> Expr<Q> {
+    match expr {
+        Expr::Field(field) => Expr::Field(folder.fold_field(field)),
+        Expr::Literal(value) => Expr::Literal(folder.fold_value(value)),
+        Expr::Call { function, args } => Expr::Call {
+            function,
+            args: args.into_iter().map(|arg| folder.fold_expr(arg)).collect(),
+        },
+    }
+}
+
+/// A `Fold` that converts each field with a closure.
+struct MapFields<F>(F);
+
+impl<P, Q, F: FnMut(P) -> Q> Fold<P, Q> for MapFields<F> {
+    fn fold_field(&mut self, field: P) -> Q {
+        (self.0)(field)
+    }
+}
+
+impl<P> AST<P> {
+    /// Returns the predicates of the query, the nodes that are not `And`, `Or`, `Not`, `True` or
+    /// `False`, from left to right.
+    pub fn predicates(&self) -> impl Iterator<Item = &AST<P>> + '_ {
+        struct Predicates<'ast, P>(Vec<&'ast AST<P>>);
+
+        impl<'ast, P> Visitor<'ast, P> for Predicates<'ast, P> {
+            fn visit_predicate(&mut self, ast: &'ast AST<P>) {
+                self.0.push(ast);
+            }
+        }
+
+        let mut predicates = Predicates(Vec::new());
+        predicates.visit_ast(self);
+        predicates.0.into_iter()
+    }
+
+    /// Returns every field referenced by the query, including the arguments of function calls,
+    /// from left to right. A field is returned once for each time it is referenced.
+    pub fn fields(&self) -> impl Iterator<Item = &P> + '_ {
+        struct Fields<'ast, P>(Vec<&'ast P>);
+
+        impl<'ast, P> Visitor<'ast, P> for Fields<'ast, P> {
+            fn visit_field(&mut self, field: &'ast P) {
+                self.0.push(field);
+            }
+        }
+
+        let mut fields = Fields(Vec::new());
+        fields.visit_ast(self);
+        fields.0.into_iter()
+    }
+
+    /// Converts the fields of the query with `f`, e.g. to map the fields of a public struct that
+    /// users query onto the fields of an internal one.
+    pub fn map_fields<Q, F: FnMut(P) -> Q>(self, f: F) -> AST<Q> {
+        MapFields(f).fold_ast(self)
+    }
+}
+
+impl<P> Expr<P> {
+    /// Converts the fields of the expression with `f`. See `AST::map_fields`.
+    pub fn map_fields<Q, F: FnMut(P) -> Q>(self, f: F) -> Expr<Q> {
+        MapFields(f).fold_expr(self)
+    }
+}
@@ -0,0 +1,164 @@
+use std::ops::Bound;
+use vec_filter::{parse_query, Filterable, Pattern, QueryBuilder, Value, AST};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[derive(Debug, Clone, PartialEq, Filterable)]
+    #[filterable(builder)]
+    pub struct Person {
+        pub name: String,
+        pub age: u32,
+        pub height: f64,
+        pub interests: Vec<String>,
+        pub scores: Vec<i32>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Filterable)]
+    #[filterable(builder = "OrderQuery")]
+    pub struct Order {
+        pub total: f64,
+    }
+
+    /// Without `#[filterable(builder)]`, the derive leaves `ItemFilter` and `filter` to the struct.
+    #[allow(dead_code)]
+    #[derive(Debug, Clone, PartialEq, Filterable)]
+    pub struct Item {
+        pub name: String,
+    }
+
+    pub struct ItemFilter;
+
+    impl Item {
+        pub fn filter() -> ItemFilter {
+            ItemFilter
+        }
+    }
+
+    fn parse(input: &str) -> AST<PersonProperties> {
+        parse_query::<PersonProperties>(input).unwrap()
+    }
+
+    fn person(name: &str, age: u32, interests: &[&str]) -> Person {
+        Person {
+            name: name.to_string(),
+            age,
+            height: 1.7,
+            interests: interests.iter().map(|s| s.to_string()).collect(),
+            scores: vec![],
+        }
+    }
+
+    #[rstest]
+    #[case::equals(Person::filter().name().eq("Alice"), "name == \"Alice\"")]
+    #[case::equals_string(Person::filter().name().eq(String::from("Alice")), "name == \"Alice\"")]
+    #[case::not_equals(Person::filter().age().ne(30), "age != 30")]
+    #[case::greater_than(Person::filter().age().gt(30), "age > 30")]
+    #[case::greater_than_or_equal(Person::filter().height().ge(1.5), "height >= 1.5")]
+    #[case::less_than(Person::filter().age().lt(30), "age < 30")]
+    #[case::less_than_or_equal(Person::filter().age().le(30), "age <= 30")]
+    #[case::largest_int(Person::filter().age().le(i32::MAX), "age <= 2147483647")]
+    #[case::between(Person::filter().age().between(20, 30), "age between 20 and 30")]
+    #[case::range(Person::filter().age().in_range(20..30), "age in 20..30")]
+    #[case::range_inclusive(Person::filter().age().in_range(20..=30), "age in 20..=30")]
+    #[case::int_in(Person::filter().age().is_in(vec![1, 2]), "age in [1,2]")]
+    #[case::int_not_in(Person::filter().age().not_in([1, 2]), "age not in [1,2]")]
+    #[case::contains(Person::filter().name().contains("li"), "name contains \"li\"")]
+    #[case::not_contains(Person::filter().name().not_contains("li"), "name not contains \"li\"")]
+    #[case::starts_with(Person::filter().name().starts_with("A"), "name startswith \"A\"")]
+    #[case::not_starts_with(
+        Person::filter().name().not_starts_with("A"),
+        "name not startswith \"A\""
+    )]
+    #[case::ends_with(Person::filter().name().ends_with("e"), "name endswith \"e\"")]
+    #[case::not_ends_with(Person::filter().name().not_ends_with("e"), "name not endswith \"e\"")]
+    #[case::regex_match(Person::filter().name().regex_match("^A"), "name regexmatch \"^A\"")]
+    #[case::not_regex_match(
+        Person::filter().name().not_regex_match("^A"),
+        "name not regexmatch \"^A\""
+    )]
+    #[case::string_in(Person::filter().name().is_in(["Al", "Bo"]), "name in [\"Al\",\"Bo\"]")]
+    #[case::like(Person::filter().name().like(Pattern::like("A%").unwrap()), "name like \"A%\"")]
+    #[case::not_glob(
+        Person::filter().name().not_like(Pattern::glob("A*").unwrap()),
+        "name !glob \"A*\""
+    )]
+    #[case::list_in(Person::filter().interests().is_in(["chess"]), "interests in [\"chess\"]")]
+    #[case::list_like(
+        Person::filter().interests().like(Pattern::like("c%").unwrap()),
+        "interests like \"c%\""
+    )]
+    #[case::int_list_in(Person::filter().scores().is_in([1, 2]), "scores in [1,2]")]
+    #[case::and(
+        Person::filter().name().eq("Alice").and(Person::filter().age().gt(30)),
+        "(name == \"Alice\") && (age > 30)"
+    )]
+    #[case::or(
+        Person::filter().age().lt(18).or(Person::filter().age().gt(65)),
+        "(age < 18) || (age > 65)"
+    )]
+    #[case::negate(Person::filter().name().eq("Alice").negate(), "!(name == \"Alice\")")]
+    fn test_builder(#[case] ast: AST<PersonProperties>, #[case] expected: &str) {
+        assert_eq!(ast, parse(expected));
+    }
+
+    #[test]
+    fn test_full_range_is_true() {
+        assert_eq!(Person::filter().age().in_range(..), AST::True);
+    }
+
+    #[test]
+    fn test_open_range() {
+        assert_eq!(
+            Person::filter().age().in_range(20..),
+            AST::Between {
+                field: PersonProperties::age,
+                lower: Bound::Included(Value::Int(20)),
+                upper: Bound::Unbounded,
+            }
+        );
+    }
+
+    #[rstest]
+    #[case::open(Person::filter().age().in_range(20..))]
+    #[case::full_between(AST::Between {
+        field: PersonProperties::age,
+        lower: Bound::Unbounded,
+        upper: Bound::Unbounded,
+    })]
+    fn test_range_display_round_trips(#[case] ast: AST<PersonProperties>) {
+        let people = vec![person("Alice", 35, &[]), person("Bob", 15, &[])];
+        let reparsed = parse(&ast.to_string());
+        assert_eq!(reparsed.apply(&people), ast.apply(&people));
+        assert_eq!(reparsed.to_string(), ast.to_string());
+    }
+
+    #[test]
+    fn test_builder_apply() {
+        let people = vec![
+            person("Alice", 35, &["chess"]),
+            person("Bob", 40, &["go"]),
+            person("Anna", 25, &["chess"]),
+        ];
+        let ast = Person::filter()
+            .name()
+            .starts_with("A")
+            .and(Person::filter().interests().is_in(["chess"]))
+            .and(Person::filter().age().gt(30));
+        assert_eq!(ast.apply(&people), vec![people[0].clone()]);
+    }
+
+    #[test]
+    fn test_named_builder() {
+        let ast = Order::filter().total().gt(1.5);
+        assert_eq!(ast, OrderQuery.total().gt(1.5));
+        assert_eq!(ast, parse_query::<OrderProperties>("total > 1.5").unwrap());
+    }
+
+    #[test]
+    fn test_builder_is_opt_in() {
+        let _: ItemFilter = Item::filter();
+    }
+}
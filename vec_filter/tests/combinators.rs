@@ -0,0 +1,148 @@
+use vec_filter::{parse_query, tokenize, Filterable, Parser, QueryStyle, TokenKind, Warning, AST};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[derive(Debug, Clone, PartialEq, Filterable)]
+    pub struct Person {
+        pub name: String,
+        pub age: u32,
+    }
+
+    fn parse(input: &str) -> AST<PersonProperties> {
+        parse_query::<PersonProperties>(input).unwrap()
+    }
+
+    fn people() -> Vec<Person> {
+        vec![
+            Person {
+                name: "Alice".to_string(),
+                age: 35,
+            },
+            Person {
+                name: "Bob".to_string(),
+                age: 20,
+            },
+        ]
+    }
+
+    #[rstest]
+    #[case::and(parse("age > 30") & parse("name == \"Alice\""), "(age > 30) && (name == \"Alice\")")]
+    #[case::or(parse("age > 30") | parse("age < 10"), "(age > 30) || (age < 10)")]
+    #[case::not(!parse("age > 30"), "!(age > 30)")]
+    #[case::chain(
+        parse("age > 1") & parse("age > 2") | !parse("age > 3"),
+        "(age > 1) && (age > 2) || !(age > 3)"
+    )]
+    #[case::all_of(
+        AST::all_of(vec![parse("age > 1"), parse("age > 2"), parse("age > 3")]),
+        "(age > 1) && (age > 2) && (age > 3)"
+    )]
+    #[case::any_of(
+        AST::any_of(vec![parse("age > 1"), parse("age > 2")]),
+        "(age > 1) || (age > 2)"
+    )]
+    #[case::all_of_one(AST::all_of(vec![parse("age > 1")]), "age > 1")]
+    #[case::all_of_none(AST::all_of(vec![]), "true")]
+    #[case::any_of_none(AST::any_of(vec![]), "false")]
+    #[case::always(AST::always(), "true")]
+    #[case::never(AST::never(), "false")]
+    fn test_combinators(#[case] ast: AST<PersonProperties>, #[case] expected: &str) {
+        assert_eq!(ast, parse(expected));
+    }
+
+    #[rstest]
+    #[case::always(AST::always(), vec![0, 1])]
+    #[case::never(AST::never(), vec![])]
+    #[case::negated(!AST::never(), vec![0, 1])]
+    #[case::restricted(parse("true") & parse("age > 30"), vec![0])]
+    #[case::widened(parse("(false) || (age > 30)"), vec![0])]
+    fn test_constants_apply(#[case] ast: AST<PersonProperties>, #[case] expected: Vec<usize>) {
+        let people = people();
+        let expected: Vec<Person> = expected.iter().map(|&i| people[i].clone()).collect();
+        assert_eq!(ast.apply(&people), expected);
+    }
+
+    #[rstest]
+    #[case::always("true", AST::True)]
+    #[case::never("false", AST::False)]
+    #[case::case_insensitive(" TRUE ", AST::True)]
+    #[case::group("!(false)", AST::Not(Box::new(AST::False)))]
+    #[case::operand(
+        "(true) && (age > 1)",
+        AST::And(Box::new(AST::True), Box::new(parse("age > 1")))
+    )]
+    fn test_parse_constants(#[case] input: &str, #[case] expected: AST<PersonProperties>) {
+        assert_eq!(parse(input), expected);
+    }
+
+    #[rstest]
+    #[case::field_like("trueness > 1")]
+    #[case::compared("true == 1")]
+    #[case::unbracketed("true && (age > 1)")]
+    fn test_parse_constant_errors(#[case] input: &str) {
+        assert!(parse_query::<PersonProperties>(input).is_err());
+    }
+
+    #[rstest]
+    #[case::always(AST::always(), "true")]
+    #[case::operand(AST::never() | parse("age > 1"), "(false) || (age > 1)")]
+    #[case::negated(!AST::always(), "!(true)")]
+    fn test_display_constants(#[case] ast: AST<PersonProperties>, #[case] expected: &str) {
+        assert_eq!(ast.to_string(), expected);
+        assert_eq!(parse(expected), ast);
+        assert_eq!(parse(&ast.to_query(QueryStyle::Keywords)), ast);
+    }
+
+    #[test]
+    fn test_recover_constants() {
+        let output = Parser::new().parse_with_recovery::<PersonProperties>("(true) || (agee > 1)");
+        assert_eq!(output.errors.len(), 1);
+        assert_eq!(output.ast, Some(AST::True));
+    }
+
+    #[test]
+    fn test_tokenize_constants() {
+        let kinds: Vec<TokenKind> = tokenize("(true) && (age > 1)")
+            .into_iter()
+            .map(|token| token.kind)
+            .filter(|kind| *kind != TokenKind::Whitespace)
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                TokenKind::Punctuation,
+                TokenKind::Logical,
+                TokenKind::Punctuation,
+                TokenKind::Logical,
+                TokenKind::Punctuation,
+                TokenKind::Field,
+                TokenKind::Operator,
+                TokenKind::Number,
+                TokenKind::Punctuation,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_constants_analysis() {
+        let ast = parse("age > 30");
+        assert_eq!(ast.implies(&AST::always()), Some(true));
+        assert_eq!(AST::never().implies(&ast), Some(true));
+        assert_eq!(AST::always().implies(&ast), Some(false));
+        assert_eq!(parse("(true) && (false)").warnings(), vec![]);
+        let ast = AST::always() & parse("(age > 30) || (age <= 30) || (age > 1)");
+        assert_eq!(ast.warnings(), vec![]);
+        let ast = AST::never() | parse("(age > 30) && (age < 20)");
+        assert_eq!(ast.warnings(), vec![Warning::Contradiction(ast)]);
+    }
+
+    #[test]
+    fn test_constants_are_not_predicates() {
+        let ast = AST::always() & parse("age > 30");
+        assert_eq!(ast.predicates().count(), 1);
+        assert_eq!(ast.fields().count(), 1);
+    }
+}
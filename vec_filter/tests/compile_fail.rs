@@ -0,0 +1,7 @@
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_compile_fail() {
+        trybuild::TestCases::new().compile_fail("tests/compile_fail/*.rs");
+    }
+}
@@ -0,0 +1,9 @@
+use vec_filter::Filterable;
+
+#[derive(Debug, Clone, PartialEq, Filterable)]
+#[filterable(bulider)]
+pub struct Person {
+    pub age: u32,
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use vec_filter::{Filterable, QueryBuilder};
+
+#[derive(Debug, Clone, PartialEq, Filterable)]
+
+#[filterable(builder)]
+pub struct Person {
+    pub interests: Vec<String>,
+}
+
+fn main() {
+    Person::filter().interests().eq(vec!["chess".to_string()]);
+}
@@ -0,0 +1,10 @@
+use vec_filter::{filter, Filterable};
+
+#[derive(Debug, Clone, PartialEq, Filterable)]
+pub struct Person {
+    pub age: u32,
+}
+
+fn main() {
+    filter!(Person, age > 30);
+}
@@ -0,0 +1,12 @@
+use vec_filter::{Filterable, QueryBuilder};
+
+#[derive(Debug, Clone, PartialEq, Filterable)]
+
+#[filterable(builder)]
+pub struct Person {
+    pub age: u32,
+}
+
+fn main() {
+    Person::filter().age().gt(u32::MAX);
+}
@@ -0,0 +1,15 @@
+use vec_filter::{filter, Filterable};
+
+#[derive(Debug, Clone, PartialEq, Filterable)]
+#[filterable(builder)]
+pub struct Person {
+    pub name: String,
+}
+
+fn main() {
+    filter!(Person, name glob "[a");
+    filter!(Person, name like "100!" escape "!");
+    filter!(Person, name iglob "[z-a]");
+    filter!(Person, name regexmatch "(a");
+    filter!(Person, name not regexmatch "[");
+}
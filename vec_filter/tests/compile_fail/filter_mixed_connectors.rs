@@ -0,0 +1,12 @@
+use vec_filter::{filter, Filterable};
+
+#[derive(Debug, Clone, PartialEq, Filterable)]
+#[filterable(builder)]
+pub struct Person {
+    pub age: u32,
+}
+
+fn main() {
+    filter!(Person, age > 1 || age > 2 && age > 3);
+    filter!(Person, age > 1 and age > 2 or age > 3);
+}
@@ -0,0 +1,12 @@
+use vec_filter::{filter, Filterable};
+
+#[derive(Debug, Clone, PartialEq, Filterable)]
+#[filterable(builder)]
+pub struct Person {
+    pub name: String,
+    pub age: u32,
+}
+
+fn main() {
+    filter!(Person, agee > 30);
+}
@@ -0,0 +1,14 @@
+use vec_filter::{filter, Filterable};
+
+#[derive(Debug, Clone, PartialEq, Filterable)]
+#[filterable(builder)]
+pub struct Person {
+    pub name: String,
+    pub age: u32,
+}
+
+fn main() {
+    filter!(Person, name contians "a");
+    filter!(Person, name not between "a" and "b");
+    filter!(Person, age > 30 xor age < 10);
+}
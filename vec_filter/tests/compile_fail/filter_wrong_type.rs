@@ -0,0 +1,16 @@
+use vec_filter::{filter, Filterable};
+
+#[derive(Debug, Clone, PartialEq, Filterable)]
+#[filterable(builder)]
+pub struct Person {
+    pub name: String,
+    pub age: u32,
+    pub interests: Vec<String>,
+}
+
+fn main() {
+    filter!(Person, age == "30");
+    filter!(Person, name > "a");
+    filter!(Person, age contains "3");
+    filter!(Person, interests == "chess");
+}
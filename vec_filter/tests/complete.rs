@@ -0,0 +1,162 @@
+use vec_filter::{
+    complete, Completion, CompletionKind, FieldType, Filterable, Parser, Registry, Value,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[allow(dead_code)]
+    #[derive(Debug, Clone, PartialEq, Filterable)]
+    pub struct Person {
+        pub name: String,
+        pub age: u32,
+        pub interests: Vec<String>,
+    }
+
+    const FUNCTIONS: [&str; 6] = [
+        "lower(",
+        "upper(",
+        "trim(",
+        "substring(",
+        "abs(",
+        "coalesce(",
+    ];
+
+    /// Completes `input` at the position of the last `|` in it.
+    fn complete_at(input: &str) -> Vec<Completion> {
+        let cursor = input.rfind('|').unwrap();
+        let input = format!("{}{}", &input[..cursor], &input[cursor + 1..]);
+        complete::<PersonProperties>(&input, cursor)
+    }
+
+    fn texts(completions: &[Completion]) -> Vec<&str> {
+        completions.iter().map(|c| c.text.as_str()).collect()
+    }
+
+    #[rstest]
+    #[case::start("|", vec!["name", "age", "interests"])]
+    #[case::in_group("(age > 1) || (|", vec!["name", "age", "interests"])]
+    #[case::nested_group("!((|", vec!["name", "age", "interests"])]
+    fn test_field_completions(#[case] input: &str, #[case] fields: Vec<&str>) {
+        let completions = complete_at(input);
+        let expected: Vec<&str> = fields.into_iter().chain(FUNCTIONS).collect();
+        assert_eq!(texts(&completions), expected);
+        assert_eq!(completions[0].kind, CompletionKind::Field);
+        assert_eq!(completions[3].kind, CompletionKind::Function);
+    }
+
+    #[rstest]
+    #[case::int("age |", vec!["==", "!=", ">", ">=", "<", "<=", "in", "not in", "between"])]
+    #[case::string("name |", vec![
+        "==", "!=", "contains", "startswith", "endswith", "regexmatch", "in", "not contains",
+        "not startswith", "not endswith", "!~", "not in", "between", "like", "ilike", "glob",
+        "iglob",
+    ])]
+    #[case::list("interests |", vec!["==", "!=", "in", "not in", "like", "ilike", "glob", "iglob"])]
+    #[case::function("abs(age) |", vec!["==", "!=", ">", ">=", "<", "<=", "in", "not in"])]
+    #[case::negated("name not |", vec!["contains", "startswith", "endswith", "regexmatch", "in", "like", "ilike", "glob", "iglob"])]
+    #[case::partial_word("name st|", vec!["startswith"])]
+    #[case::partial_symbol("age >|", vec![">", ">="])]
+    #[case::partial_negated("name !con|", vec!["contains"])]
+    fn test_operator_completions(#[case] input: &str, #[case] expected: Vec<&str>) {
+        let completions = complete_at(input);
+        assert_eq!(texts(&completions), expected);
+        assert!(completions
+            .iter()
+            .all(|c| c.kind == CompletionKind::Operator));
+    }
+
+    #[rstest]
+    #[case::after_group("(age > 1) |", vec!["&&", "||", "and", "or"])]
+    #[case::after_between("(age between 1 and 5) |", vec!["&&", "||", "and", "or"])]
+    #[case::after_negated_group("not (age > 1) |", vec!["&&", "||", "and", "or"])]
+    #[case::partial_keyword("(age > 1) O|", vec!["or"])]
+    fn test_connector_completions(#[case] input: &str, #[case] expected: Vec<&str>) {
+        let completions = complete_at(input);
+        assert_eq!(texts(&completions), expected);
+        assert!(completions
+            .iter()
+            .all(|c| c.kind == CompletionKind::Connector));
+    }
+
+    #[test]
+    fn test_group_after_connector() {
+        assert_eq!(
+            complete_at("(age > 1) && |"),
+            vec![Completion {
+                kind: CompletionKind::Group,
+                text: "(".to_string(),
+                span: 13..13,
+            }]
+        );
+    }
+
+    #[rstest]
+    #[case::unknown_field("agee |")]
+    #[case::value("age > |")]
+    #[case::between_upper_bound("age between 1 and |")]
+    #[case::complete_comparison("age > 1 |")]
+    #[case::unclosed_string("name == \"(a |")]
+    #[case::not_a_field("(age > 1) && (name == \"a\") && xyz|")]
+    fn test_no_completions(#[case] input: &str) {
+        assert_eq!(complete_at(input), vec![]);
+    }
+
+    #[rstest]
+    #[case::partial("na|", 0..2, "name")]
+    #[case::middle_of_word("na|me == \"a\"", 0..4, "name")]
+    #[case::operator_after_cursor("age >|= 1", 4..6, ">")]
+    #[case::after_whitespace("(age > 1)   |", 12..12, "&&")]
+    fn test_replacement_span(
+        #[case] input: &str,
+        #[case] span: std::ops::Range<usize>,
+        #[case] first: &str,
+    ) {
+        let completions = complete_at(input);
+        assert_eq!(completions[0].text, first);
+        assert!(completions.iter().all(|c| c.span == span));
+    }
+
+    #[test]
+    fn test_cursor_outside_input() {
+        assert_eq!(complete::<PersonProperties>("é", 1), vec![]);
+        assert_eq!(complete::<PersonProperties>("age", 10), vec![]);
+    }
+
+    #[test]
+    fn test_registry_completions() {
+        let mut registry = Registry::new();
+        registry
+            .register_operator(
+                "sounds_like",
+                vec![FieldType::String],
+                FieldType::String,
+                |_, _| true,
+            )
+            .register_function(
+                "initials",
+                vec![FieldType::String],
+                FieldType::String,
+                |_| Some(Value::String(String::new())),
+            );
+        let parser = Parser::new().registry(registry);
+
+        let operators = parser.complete::<PersonProperties>("name so", 7);
+        assert_eq!(texts(&operators), vec!["sounds_like"]);
+        assert!(parser.complete::<PersonProperties>("age so", 6).is_empty());
+        let functions = parser.complete::<PersonProperties>("in", 2);
+        assert_eq!(texts(&functions), vec!["interests", "initials("]);
+    }
+
+    #[test]
+    fn test_limits_apply() {
+        let nested = format!("{}name{} ", "lower(".repeat(3000), ")".repeat(3000));
+        assert_eq!(complete::<PersonProperties>(&nested, nested.len()), vec![]);
+
+        let parser = Parser::new().max_input_length(5);
+        assert!(parser.complete::<PersonProperties>("age ", 4).len() > 1);
+        assert_eq!(parser.complete::<PersonProperties>("age   ", 6), vec![]);
+    }
+}
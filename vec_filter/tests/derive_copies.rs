@@ -0,0 +1,59 @@
+use vec_filter::{complete, CompletionKind, Filterable, Pattern};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[allow(dead_code)]
+    #[derive(Debug, Clone, PartialEq, Filterable)]
+    pub struct Person {
+        pub name: String,
+    }
+
+    /// The `KEYWORDS` list of `filter!`, which cannot share the parser's list because the derive
+    /// crate does not depend on this one.
+    fn derive_keywords() -> Vec<String> {
+        let source = include_str!("../../vec_filter_derive/src/query.rs");
+        let start = source.find("const KEYWORDS").unwrap();
+        let end = start + source[start..].find("];").unwrap();
+        source[start..end]
+            .split('"')
+            .skip(1)
+            .step_by(2)
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_filter_macro_keywords_match_the_parser() {
+        // The operators that can follow `not` are the negatable operators written as words.
+        let negatable: Vec<String> = complete::<PersonProperties>("name not ", 9)
+            .into_iter()
+            .inspect(|completion| assert_eq!(completion.kind, CompletionKind::Operator))
+            .map(|completion| completion.text)
+            .collect();
+        assert!(!negatable.is_empty());
+        assert_eq!(derive_keywords(), negatable);
+    }
+
+    /// The source of the translation functions in a `pattern.rs`.
+    fn translations(source: &str) -> String {
+        let start = source.find("fn translate_like").unwrap();
+        let glob = source.find("fn translate_glob").unwrap();
+        let end = glob + source[glob..].find("\n}\n").unwrap();
+        source[start..end].to_string()
+    }
+
+    #[test]
+    fn test_filter_macro_patterns_match_the_parser() {
+        let derive = include_str!("../../vec_filter_derive/src/pattern.rs");
+        assert_eq!(
+            translations(derive),
+            translations(include_str!("../src/pattern.rs"))
+        );
+        assert!(derive.contains(&format!(
+            "DEFAULT_ESCAPE: char = {:?};",
+            Pattern::DEFAULT_ESCAPE
+        )));
+    }
+}
@@ -0,0 +1,88 @@
+use vec_filter::{filter, parse_query, Filterable, AST};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[derive(Debug, Clone, PartialEq, Filterable)]
+    #[filterable(builder)]
+    pub struct Person {
+        pub name: String,
+        pub age: u32,
+        pub height: f64,
+        pub balance: i32,
+        pub interests: Vec<String>,
+    }
+
+    const MIN_AGE: i32 = 18;
+
+    fn parse(input: &str) -> AST<PersonProperties> {
+        parse_query::<PersonProperties>(input).unwrap()
+    }
+
+    #[rstest]
+    #[case::equals(filter!(Person, name == "Alice"), "name == \"Alice\"")]
+    #[case::not_equals(filter!(Person, age != 30), "age != 30")]
+    #[case::greater_than(filter!(Person, age > 30), "age > 30")]
+    #[case::greater_than_or_equal(filter!(Person, height >= 1.5), "height >= 1.5")]
+    #[case::less_than(filter!(Person, age < 30), "age < 30")]
+    #[case::less_than_or_equal(filter!(Person, age <= 30), "age <= 30")]
+    #[case::negative(filter!(Person, balance>=-30), "balance >= -30")]
+    #[case::contains(filter!(Person, name contains "li"), "name contains \"li\"")]
+    #[case::not_contains(filter!(Person, name not contains "li"), "name not contains \"li\"")]
+    #[case::bang_starts_with(filter!(Person, name !startswith "A"), "name !startswith \"A\"")]
+    #[case::ends_with(filter!(Person, name endswith "e"), "name endswith \"e\"")]
+    #[case::regex_match(filter!(Person, name regexmatch "^A"), "name regexmatch \"^A\"")]
+    #[case::not_regex_match(filter!(Person, name not regexmatch "^A"), "name !~ \"^A\"")]
+    #[case::in_list(filter!(Person, age in [1, 2]), "age in [1,2]")]
+    #[case::not_in_list(filter!(Person, name not in ["a", "b"]), "name not in [\"a\",\"b\"]")]
+    #[case::list_field(filter!(Person, interests in ["chess"]), "interests in [\"chess\"]")]
+    #[case::range(filter!(Person, age in 20..30), "age in 20..30")]
+    #[case::inclusive_range(filter!(Person, age in 20..=30), "age in 20..=30")]
+    #[case::not_in_range(filter!(Person, age not in 20..30), "age not in 20..30")]
+    #[case::between(filter!(Person, age between 20 and 30), "age between 20 and 30")]
+    #[case::like(filter!(Person, name like "A%"), "name like \"A%\"")]
+    #[case::not_ilike(filter!(Person, name not ilike "a%"), "name not ilike \"a%\"")]
+    #[case::glob(filter!(Person, name glob "A*"), "name glob \"A*\"")]
+    #[case::escape(filter!(Person, name like "100!%" escape "!"), "name like \"100!%\" escape \"!\"")]
+    #[case::keywords(filter!(Person, name NOT CONTAINS "a"), "name not contains \"a\"")]
+    #[case::and(
+        filter!(Person, age > 30 && name == "Alice"),
+        "(age > 30) && (name == \"Alice\")"
+    )]
+    #[case::chain(
+        filter!(Person, age > 1 && age > 2 && age > 3),
+        "(age > 1) && (age > 2) && (age > 3)"
+    )]
+    #[case::or_chain(
+        filter!(Person, age > 1 || age > 2 or age > 3),
+        "(age > 1) || (age > 2) || (age > 3)"
+    )]
+    #[case::parentheses(
+        filter!(Person, (age > 1 || age > 2) && age > 3),
+        "((age > 1) || (age > 2)) && (age > 3)"
+    )]
+    #[case::word_connectives(
+        filter!(Person, not (age > 1) and (age > 2 or age > 3)),
+        "!(age > 1) && ((age > 2) || (age > 3))"
+    )]
+    #[case::negation(filter!(Person, !(name == "Alice")), "!(name == \"Alice\")")]
+    #[case::constant(filter!(Person, age >= MIN_AGE), "age >= 18")]
+    #[case::block(filter!(Person, name == { "Al".to_string() + "ice" }), "name == \"Alice\"")]
+    #[case::trailing_comma(filter!(Person, age > 30,), "age > 30")]
+    fn test_filter_macro(#[case] ast: AST<PersonProperties>, #[case] expected: &str) {
+        assert_eq!(ast, parse(expected));
+    }
+
+    #[test]
+    fn test_filter_macro_variables() {
+        let min = 20;
+        let names = ["Alice", "Bob"];
+        let ast = filter!(Person, age between min and { min + 10 } && name in [{ names[0] }]);
+        assert_eq!(
+            ast,
+            parse("(age between 20 and 30) && (name in [\"Alice\"])")
+        );
+    }
+}
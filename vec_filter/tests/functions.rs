@@ -0,0 +1,127 @@
+use vec_filter::{
+    parse_query, ASTValidationError, Expr, FieldType, Filterable, Function, Operator, Value, AST,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[derive(Debug, Clone, PartialEq, Filterable)]
+    pub struct User {
+        pub name: String,
+        pub nickname: String,
+        pub email: String,
+        pub balance: i32,
+        pub tags: Vec<String>,
+    }
+
+    fn fields(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn users() -> Vec<User> {
+        vec![
+            User {
+                name: "Alice".to_string(),
+                nickname: "".to_string(),
+                email: "Alice@Example.com".to_string(),
+                balance: -20,
+                tags: vec!["Admin".to_string()],
+            },
+            User {
+                name: "Robert".to_string(),
+                nickname: "Bob".to_string(),
+                email: "  bob@example.org ".to_string(),
+                balance: 5,
+                tags: vec!["user".to_string()],
+            },
+        ]
+    }
+
+    #[rstest]
+    #[case::lower("lower(email) endswith \"@example.com\"", vec![0])]
+    #[case::upper("upper(name) == \"ALICE\"", vec![0])]
+    #[case::trim("trim(email) == \"bob@example.org\"", vec![1])]
+    #[case::substring("substring(name, 0, 3) == \"Rob\"", vec![1])]
+    #[case::substring_without_length("substring(name, 2) == \"ice\"", vec![0])]
+    #[case::abs("abs(balance) > 10", vec![0])]
+    #[case::coalesce("coalesce(nickname, name) == \"Bob\"", vec![1])]
+    #[case::coalesce_empty("coalesce(nickname, name) == \"Alice\"", vec![0])]
+    #[case::coalesce_literal("coalesce(nickname, \"none\") == \"none\"", vec![0])]
+    #[case::nested("lower(trim(email)) startswith \"bob\"", vec![1])]
+    #[case::case_insensitive_name("LOWER(name) == \"alice\"", vec![0])]
+    #[case::list_argument("lower(tags) in [\"admin\"]", vec![0])]
+    #[case::int_in_list("abs(balance) in [20,30]", vec![0])]
+    #[case::int_field_in_list("balance in [5,6]", vec![1])]
+    #[case::in_brackets("(lower(name) == \"alice\") || (abs(balance) == 5)", vec![0, 1])]
+    fn test_function_filtering(#[case] input: &str, #[case] expected_indices: Vec<usize>) {
+        let users = users();
+        let ast = parse_query(input).unwrap_or_else(|err| {
+            panic!("Failed to parse input '{}': {:?}", input, err);
+        });
+
+        let filtered_users: Vec<User> = ast.apply(&users);
+        let expected_users: Vec<User> = expected_indices
+            .iter()
+            .map(|index| users[*index].clone())
+            .collect();
+
+        assert_eq!(filtered_users, expected_users);
+    }
+
+    #[test]
+    fn test_function_ast_output() {
+        let ast = parse_query::<UserProperties>("coalesce(nickname, name) == \"Bob\"").unwrap();
+        assert_eq!(
+            ast,
+            AST::Compare {
+                expr: Expr::Call {
+                    function: Function::Coalesce,
+                    args: vec![
+                        Expr::Field(UserProperties::nickname),
+                        Expr::Field(UserProperties::name)
+                    ],
+                },
+                operator: Operator::Equals,
+                value: Value::String("Bob".to_string()),
+            }
+        );
+    }
+
+    #[rstest]
+    #[case::unknown_function("reverse(name) == \"a\"", ASTValidationError::UnknownFunction { name: "reverse".to_string() })]
+    #[case::unknown_argument_field("lower(nmae) == \"a\"", ASTValidationError::InvalidField {
+        field_name: "nmae".to_string(),
+        suggestions: vec!["name".to_string()],
+        valid_fields: fields(&["name", "nickname", "email", "balance", "tags"]),
+    })]
+    #[case::wrong_argument_type("abs(name) == 1", ASTValidationError::InvalidArguments {
+        function: Function::Abs,
+        arguments: vec![FieldType::String],
+    })]
+    #[case::wrong_argument_count("lower(name, email) == \"a\"", ASTValidationError::InvalidArguments {
+        function: Function::Lower,
+        arguments: vec![FieldType::String, FieldType::String],
+    })]
+    #[case::mixed_coalesce("coalesce(name, balance) == \"a\"", ASTValidationError::InvalidArguments {
+        function: Function::Coalesce,
+        arguments: vec![FieldType::String, FieldType::Int],
+    })]
+    #[case::wrong_return_type("abs(balance) == \"a\"", ASTValidationError::IncompatibleExpression {
+        ast: Box::new(AST::Compare {
+            expr: Expr::Call { function: Function::Abs, args: vec![Expr::Field(UserProperties::balance)] },
+            operator: Operator::Equals,
+            value: Value::String("a".to_string()),
+        }),
+        value_type: FieldType::Int,
+        valid_values: vec![Value::Int(0)],
+    })]
+    fn test_function_errors(
+        #[case] input: &str,
+        #[case] expected_error: ASTValidationError<UserProperties>,
+    ) {
+        let result = parse_query::<UserProperties>(input);
+        assert_eq!(result.unwrap_err()[0], expected_error);
+    }
+}
@@ -0,0 +1,67 @@
+use vec_filter::{parse_query, Filterable, AST};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[allow(dead_code)]
+    #[derive(Debug, Clone, PartialEq, Filterable)]
+    pub struct Person {
+        pub name: String,
+        pub age: u32,
+        pub height: f64,
+        pub interests: Vec<String>,
+    }
+
+    fn parse(input: &str) -> AST<PersonProperties> {
+        parse_query::<PersonProperties>(input).unwrap()
+    }
+
+    #[rstest]
+    #[case::narrower_range("age > 40", "age > 30")]
+    #[case::same_query("name like \"a%\"", "name like \"a%\"")]
+    #[case::integer_gap("age > 30", "age >= 31")]
+    #[case::value_in_range("age == 35", "age between 30 and 40")]
+    #[case::list_subset("age in [1,2]", "age in [1,2,3]")]
+    #[case::value_in_list("age == 2", "age in [1,2,3]")]
+    #[case::value_matches_pattern("name == \"alice\"", "name like \"a%\"")]
+    #[case::conjunction("(age > 30) && (name == \"a\")", "age > 30")]
+    #[case::disjunction("age > 30", "(age > 30) || (name == \"a\")")]
+    #[case::split_range("(age > 40) || (age < 10)", "age != 20")]
+    #[case::scope(
+        "(name == \"a\") && (age between 20 and 25)",
+        "(age >= 18) && (name in [\"a\"])"
+    )]
+    #[case::negation("!(age <= 40) && (age >= 0)", "age > 30")]
+    #[case::contradiction("(age > 30) && (age < 20)", "name == \"a\"")]
+    #[case::float_range("height > 2.0", "height >= 1.5")]
+    fn test_implies(#[case] a: &str, #[case] b: &str) {
+        assert_eq!(parse(a).implies(&parse(b)), Some(true));
+    }
+
+    #[rstest]
+    #[case::wider_range("age > 30", "age > 40")]
+    #[case::large_gap("age > 30", "age > 100000")]
+    #[case::other_field("age > 30", "name == \"a\"")]
+    #[case::list_superset("age in [1,2,3]", "age in [1,2]")]
+    #[case::pattern("name like \"a%\"", "name == \"alice\"")]
+    #[case::prefix("name startswith \"a\"", "name == \"a\"")]
+    #[case::disjunction("(age > 30) || (name == \"a\")", "age > 30")]
+    #[case::list_field("interests in [\"chess\"]", "interests in [\"go\"]")]
+    #[case::float_range("height >= 1.5", "height > 2.0")]
+    fn test_does_not_imply(#[case] a: &str, #[case] b: &str) {
+        assert_eq!(parse(a).implies(&parse(b)), Some(false));
+    }
+
+    #[rstest]
+    // The only ages in range are negative, which a `u32` field cannot hold.
+    #[case::negative_integers("age < 0", "age == 5")]
+    // An item without an age matches the first query but not the second.
+    #[case::missing_value("!(age <= 40)", "age > 30")]
+    #[case::expression("lower(name) == \"a\"", "name == \"a\"")]
+    #[case::parameter("age > $min", "age > 30")]
+    fn test_unknown(#[case] a: &str, #[case] b: &str) {
+        assert_eq!(parse(a).implies(&parse(b)), None);
+    }
+}
@@ -0,0 +1,188 @@
+use vec_filter::{
+    parse_query, ASTValidationError, Filterable, Function, Operator, Parser, PatternSyntax,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[derive(Debug, Clone, PartialEq, Filterable)]
+    pub struct User {
+        pub name: String,
+        pub age: u32,
+        pub tags: Vec<String>,
+    }
+
+    fn parser() -> Parser {
+        Parser::new()
+            .max_input_length(200)
+            .max_depth(3)
+            .max_list_length(3)
+            .max_regex_size(10_000)
+            .allowed_operators(vec![
+                Operator::Equals,
+                Operator::In,
+                Operator::GreaterThan,
+                Operator::RegexMatch,
+            ])
+            .allowed_patterns(vec![PatternSyntax::Like])
+            .allowed_functions(vec![Function::Lower])
+    }
+
+    #[rstest]
+    #[case::simple("name == \"Alice\"")]
+    #[case::at_depth("((!(age > 1)))")]
+    #[case::parentheses_in_string("name == \"((((((\"")]
+    #[case::parentheses_in_single_quoted_string("name == '(((\\'(((('")]
+    #[case::parentheses_in_raw_string("name == r#\"(((\"((((\"#")]
+    #[case::parentheses_in_comments("name == \"a\" // ((((\n /* (((( */")]
+    #[case::list_at_limit("name in [\"a\",\"b\",\"c\"]")]
+    #[case::small_regex("name regexmatch \"^[a-z]+$\"")]
+    #[case::between_is_allowed("age between 1 and 5")]
+    #[case::allowed_pattern("name like \"A%\"")]
+    #[case::allowed_case_insensitive_pattern("name not ilike \"a%\"")]
+    #[case::allowed_function("lower(name) == \"alice\"")]
+    fn test_within_limits(#[case] input: &str) {
+        if let Err(err) = parser().parse::<UserProperties>(input) {
+            panic!("Failed to parse input '{}': {:?}", input, err);
+        }
+    }
+
+    #[rstest]
+    #[case::input_too_long(
+        &format!("name == \"{}\"", "a".repeat(200)),
+        ASTValidationError::InputTooLong { length: 210, max: 200 }
+    )]
+    #[case::too_deep("((((age > 1))))", ASTValidationError::NestingTooDeep { depth: 4, max: 3 })]
+    #[case::too_deep_in_call(
+        "((lower(lower(name)) == \"a\"))",
+        ASTValidationError::NestingTooDeep { depth: 4, max: 3 }
+    )]
+    #[case::list_too_long(
+        "name in [\"a\",\"b\",\"c\",\"d\"]",
+        ASTValidationError::ListTooLong { length: 4, max: 3 }
+    )]
+    #[case::regex_too_large(
+        "name regexmatch \"\\\\w{100}{100}\"",
+        ASTValidationError::RegexTooLarge { pattern: "\\w{100}{100}".to_string(), max: 10_000 }
+    )]
+    #[case::operator_not_allowed(
+        "(age > 1) && (name contains \"a\")",
+        ASTValidationError::OperatorNotAllowed { operator: Operator::Contains }
+    )]
+    #[case::negated_operator_not_allowed(
+        "name not in [\"a\"]",
+        ASTValidationError::OperatorNotAllowed { operator: Operator::NotIn }
+    )]
+    #[case::pattern_not_allowed(
+        "name glob \"A*\"",
+        ASTValidationError::PatternNotAllowed { syntax: PatternSyntax::Glob }
+    )]
+    #[case::negated_pattern_not_allowed(
+        "name not iglob \"a*\"",
+        ASTValidationError::PatternNotAllowed { syntax: PatternSyntax::Glob }
+    )]
+    #[case::function_not_allowed(
+        "upper(name) == \"A\"",
+        ASTValidationError::FunctionNotAllowed { function: Function::Upper }
+    )]
+    #[case::nested_function_not_allowed(
+        "lower(trim(name)) == \"a\"",
+        ASTValidationError::FunctionNotAllowed { function: Function::Trim }
+    )]
+    fn test_limit_errors(
+        #[case] input: &str,
+        #[case] expected_error: ASTValidationError<UserProperties>,
+    ) {
+        let errors = parser().parse::<UserProperties>(input).unwrap_err();
+        assert_eq!(errors, vec![expected_error]);
+    }
+
+    #[test]
+    fn test_limited_parser_filters() {
+        let users = vec![
+            User {
+                name: "Alice".to_string(),
+                age: 30,
+                tags: vec!["admin".to_string()],
+            },
+            User {
+                name: "Bob".to_string(),
+                age: 20,
+                tags: vec![],
+            },
+        ];
+        let ast = parser()
+            .parse::<UserProperties>("(age > 25) && (name regexmatch \"^A\")")
+            .unwrap();
+        assert_eq!(ast.apply(&users), vec![users[0].clone()]);
+    }
+
+    #[test]
+    fn test_deep_nesting_is_rejected_before_parsing() {
+        let input = format!("{}age > 1{}", "(".repeat(100_000), ")".repeat(100_000));
+        let errors = Parser::new()
+            .max_depth(64)
+            .parse::<UserProperties>(&input)
+            .unwrap_err();
+        assert_eq!(
+            errors,
+            vec![ASTValidationError::NestingTooDeep {
+                depth: 100_000,
+                max: 64
+            }]
+        );
+    }
+
+    #[rstest]
+    #[case::between("age between 1 and 5")]
+    #[case::range("age in 1..5")]
+    #[case::negated_range("age not in 1..=5")]
+    fn test_ranges_not_allowed(#[case] input: &str) {
+        let errors = Parser::new()
+            .allow_ranges(false)
+            .parse::<UserProperties>(input)
+            .unwrap_err();
+        assert_eq!(errors, vec![ASTValidationError::RangeNotAllowed]);
+    }
+
+    #[test]
+    fn test_default_parser_only_limits_depth() {
+        let list = (0..1000)
+            .map(|i| format!("\"{}\"", i))
+            .collect::<Vec<_>>()
+            .join(",");
+        let input = format!("(((((name in [{}])))))", list);
+        assert!(Parser::new().parse::<UserProperties>(&input).is_ok());
+
+        let depth = Parser::DEFAULT_MAX_DEPTH;
+        let input = format!("{}age > 1{}", "!(".repeat(depth), ")".repeat(depth));
+        assert!(parse_query::<UserProperties>(&input).is_ok());
+        let input = format!("({})", input);
+        assert_eq!(
+            parse_query::<UserProperties>(&input).unwrap_err(),
+            vec![ASTValidationError::NestingTooDeep {
+                depth: depth + 1,
+                max: depth
+            }]
+        );
+    }
+
+    #[rstest]
+    #[case::pattern(
+        ASTValidationError::PatternNotAllowed { syntax: PatternSyntax::Glob },
+        "Operator glob is not allowed"
+    )]
+    #[case::range(ASTValidationError::RangeNotAllowed, "Ranges are not allowed")]
+    #[case::function(
+        ASTValidationError::FunctionNotAllowed { function: Function::Upper },
+        "Function upper is not allowed"
+    )]
+    fn test_limit_error_display(
+        #[case] error: ASTValidationError<UserProperties>,
+        #[case] expected: &str,
+    ) {
+        assert_eq!(error.to_string(), expected);
+    }
+}
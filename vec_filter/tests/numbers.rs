@@ -0,0 +1,115 @@
+use vec_filter::{parse_query, ASTValidationError, Filterable, Value, AST};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[derive(Debug, Clone, PartialEq, Filterable)]
+    pub struct Account {
+        pub name: String,
+        pub balance: i32,
+        pub rate: f64,
+    }
+
+    fn accounts() -> Vec<Account> {
+        vec![
+            Account {
+                name: "savings".to_string(),
+                balance: 1_500_000,
+                rate: 0.025,
+            },
+            Account {
+                name: "overdraft".to_string(),
+                balance: -250,
+                rate: 12.5,
+            },
+            Account {
+                name: "current".to_string(),
+                balance: 31,
+                rate: 0.0,
+            },
+        ]
+    }
+
+    #[rstest]
+    #[case::negative("balance < -1", vec![1])]
+    #[case::explicit_plus("balance > +100", vec![0])]
+    #[case::underscores("balance >= 1_000_000", vec![0])]
+    #[case::hex("balance == 0x1F", vec![2])]
+    #[case::negative_hex("balance == -0xFA", vec![1])]
+    #[case::negative_range("balance in -300..0", vec![1])]
+    #[case::negative_list("balance in [-250,31]", vec![1, 2])]
+    #[case::float("rate > 1.5", vec![1])]
+    #[case::scientific("rate < 3e-2", vec![0, 2])]
+    #[case::float_equals("rate == 0.0", vec![2])]
+    #[case::int_literal_on_float_field("rate >= 12", vec![1])]
+    #[case::float_between("rate between 0.01 and 1", vec![0])]
+    #[case::abs_float("abs(rate) > 10", vec![1])]
+    fn test_number_filtering(#[case] input: &str, #[case] expected_indices: Vec<usize>) {
+        let accounts = accounts();
+        let ast = parse_query(input).unwrap_or_else(|err| {
+            panic!("Failed to parse input '{}': {:?}", input, err);
+        });
+
+        let filtered_accounts: Vec<Account> = ast.apply(&accounts);
+        let expected_accounts: Vec<Account> = expected_indices
+            .iter()
+            .map(|index| accounts[*index].clone())
+            .collect();
+
+        assert_eq!(filtered_accounts, expected_accounts);
+    }
+
+    #[rstest]
+    #[case::negative("balance > -5", AST::GreaterThan { field: AccountProperties::balance, value: Value::Int(-5) })]
+    #[case::hex("balance == 0xff_ff", AST::Equals { field: AccountProperties::balance, value: Value::Int(65535) })]
+    #[case::scientific("rate < 1.5E3", AST::LessThan { field: AccountProperties::rate, value: Value::Float(1500.0) })]
+    #[case::coerced_int("rate == 2", AST::Equals { field: AccountProperties::rate, value: Value::Float(2.0) })]
+    fn test_number_ast_output(#[case] input: &str, #[case] expected_ast: AST<AccountProperties>) {
+        assert_eq!(
+            parse_query::<AccountProperties>(input).unwrap(),
+            expected_ast
+        );
+    }
+
+    #[rstest]
+    #[case::float("rate == 2.0", "rate == 2.0")]
+    #[case::exponent("rate > 1e-7", "rate > 1e-7")]
+    #[case::underscores("balance > -1_000", "balance > -1000")]
+    #[case::hex("balance == 0x10", "balance == 16")]
+    fn test_number_serialization(#[case] input: &str, #[case] expected: &str) {
+        let ast = parse_query::<AccountProperties>(input).unwrap();
+        assert_eq!(ast.to_string(), expected);
+        assert_eq!(parse_query::<AccountProperties>(expected).unwrap(), ast);
+    }
+
+    #[rstest]
+    #[case::int_overflow("balance > 99999999999", "99999999999", 10..21)]
+    #[case::negative_overflow("(name == \"a\") || (balance < -2147483649)", "-2147483649", 28..39)]
+    #[case::hex_overflow("balance == 0x1_0000_0000", "0x1_0000_0000", 11..24)]
+    #[case::float_overflow("rate > 1e400", "1e400", 7..12)]
+    fn test_number_out_of_range(
+        #[case] input: &str,
+        #[case] literal: &str,
+        #[case] span: std::ops::Range<usize>,
+    ) {
+        let errors = parse_query::<AccountProperties>(input).unwrap_err();
+        assert_eq!(
+            errors,
+            vec![ASTValidationError::NumberOutOfRange {
+                literal: literal.to_string(),
+                span,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_float_literal_on_int_field_is_incompatible() {
+        let errors = parse_query::<AccountProperties>("balance > 2.5").unwrap_err();
+        assert!(matches!(
+            errors[0],
+            ASTValidationError::CompatibilityError(_)
+        ));
+    }
+}
@@ -0,0 +1,134 @@
+use vec_filter::{parse_query, Filterable, AST};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use rstest::rstest;
+
+    #[derive(Debug, Clone, PartialEq, Filterable)]
+    pub struct Person {
+        pub name: String,
+        pub age: u32,
+        pub interests: Vec<String>,
+    }
+
+    fn parse(input: &str) -> AST<PersonProperties> {
+        parse_query::<PersonProperties>(input).unwrap()
+    }
+
+    fn person(name: &str, age: u32) -> Person {
+        Person {
+            name: name.to_string(),
+            age,
+            interests: vec![],
+        }
+    }
+
+    #[rstest]
+    #[case::regex_last(
+        "(name regexmatch \"^a.*\") && (age == 5)",
+        "(age == 5) && (name regexmatch \"^a.*\")"
+    )]
+    #[case::or(
+        "(name regexmatch \"^a.*\") || (age != 5)",
+        "(age != 5) || (name regexmatch \"^a.*\")"
+    )]
+    #[case::selective_first("(age > 5) && (name == \"a\")", "(name == \"a\") && (age > 5)")]
+    #[case::likely_first("(age > 5) || (name != \"a\")", "(name != \"a\") || (age > 5)")]
+    #[case::long_list(
+        "(age in [1,2,3,4,5,6,7,8]) && (age < 5)",
+        "(age < 5) && (age in [1,2,3,4,5,6,7,8])"
+    )]
+    #[case::call(
+        "(lower(name) == \"a\") && (name == \"b\")",
+        "(name == \"b\") && (lower(name) == \"a\")"
+    )]
+    #[case::pattern(
+        "(name like \"a%\") && (name startswith \"a\")",
+        "(name startswith \"a\") && (name like \"a%\")"
+    )]
+    #[case::nested(
+        "!((name regexmatch \"a\") || (age == 1)) && ((name like \"a%\") && (age > 1))",
+        "(age > 1) && ((name like \"a%\") && !((age == 1) || (name regexmatch \"a\")))"
+    )]
+    #[case::flattened(
+        "(((name regexmatch \"a\") && (name like \"a%\")) && (name contains \"a\")) && (age == 1)",
+        "((age == 1) && (name contains \"a\")) && ((name like \"a%\") && (name regexmatch \"a\"))"
+    )]
+    fn test_optimize(#[case] input: &str, #[case] expected: &str) {
+        let ast = parse(input);
+        let optimized = ast.optimize();
+        assert_eq!(optimized, parse(expected));
+        assert!(optimized.cost() <= ast.cost());
+    }
+
+    #[test]
+    fn test_cost() {
+        assert_eq!(parse("age == 5").cost(), 1.0);
+        assert_eq!(parse("age in [1,2,3]").cost(), 3.0);
+        assert_eq!(parse("lower(name) startswith \"a\"").cost(), 7.0);
+        // The regex is only checked for the tenth of the items whose age is 5.
+        assert_eq!(parse("(age == 5) && (name regexmatch \"a\")").cost(), 11.0);
+        assert_eq!(
+            parse("(name regexmatch \"a\") && (age == 5)").cost(),
+            100.25
+        );
+    }
+
+    #[test]
+    fn test_optimize_with_sample() {
+        // Everyone in the sample is called bob, and few are over 28.
+        let sample: Vec<Person> = (0..20).map(|age| person("bob", 20 + age / 2)).collect();
+        let ast = parse("(name == \"bob\") && (age > 28)");
+        assert_eq!(ast.optimize(), ast);
+        assert_eq!(
+            ast.optimize_with_sample(&sample),
+            parse("(age > 28) && (name == \"bob\")")
+        );
+    }
+
+    const PREDICATES: [&str; 8] = [
+        "name == \"bob\"",
+        "name regexmatch \"^a\"",
+        "name like \"%o%\"",
+        "age > 30",
+        "age in [20,30,40]",
+        "interests in [\"chess\"]",
+        "lower(name) == \"amy\"",
+        "age == $age",
+    ];
+
+    fn ast() -> impl Strategy<Value = AST<PersonProperties>> {
+        let leaf = (0..PREDICATES.len()).prop_map(|i| parse(PREDICATES[i]));
+        leaf.prop_recursive(5, 32, 2, |inner| {
+            prop_oneof![
+                inner.clone().prop_map(|ast| AST::Not(Box::new(ast))),
+                (inner.clone(), inner.clone())
+                    .prop_map(|(left, right)| AST::And(Box::new(left), Box::new(right))),
+                (inner.clone(), inner)
+                    .prop_map(|(left, right)| AST::Or(Box::new(left), Box::new(right))),
+            ]
+        })
+    }
+
+    fn people() -> impl Strategy<Value = Vec<Person>> {
+        let name = prop_oneof![Just("bob"), Just("amy"), Just("anna"), Just("Amy")];
+        let interests = proptest::collection::vec(prop_oneof![Just("chess"), Just("go")], 0..3);
+        let person = (name, 15u32..50, interests).prop_map(|(name, age, interests)| Person {
+            name: name.to_string(),
+            age,
+            interests: interests.into_iter().map(String::from).collect(),
+        });
+        proptest::collection::vec(person, 0..8)
+    }
+
+    proptest! {
+        #[test]
+        fn prop_optimize_matches_same_items(ast in ast(), people in people(), sample in people()) {
+            let expected = ast.apply(&people);
+            prop_assert_eq!(ast.optimize().apply(&people), expected.clone());
+            prop_assert_eq!(ast.optimize_with_sample(&sample).apply(&people), expected);
+        }
+    }
+}
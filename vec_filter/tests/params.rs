@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+use vec_filter::{
+    parse_query, ASTValidationError, Expr, Filterable, Operand, Operator, Parser, Value, AST,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[derive(Debug, Clone, PartialEq, Filterable)]
+    pub struct Customer {
+        pub tenant: String,
+        pub name: String,
+        pub age: u32,
+        pub score: f64,
+    }
+
+    fn customers() -> Vec<Customer> {
+        vec![
+            Customer {
+                tenant: "acme".to_string(),
+                name: "Alice".to_string(),
+                age: 34,
+                score: 0.9,
+            },
+            Customer {
+                tenant: "acme".to_string(),
+                name: "Bob".to_string(),
+                age: 19,
+                score: 0.4,
+            },
+            Customer {
+                tenant: "globex".to_string(),
+                name: "Carol".to_string(),
+                age: 41,
+                score: 0.7,
+            },
+        ]
+    }
+
+    #[rstest]
+    #[case::equals("tenant == $tenant", vec![("tenant", Value::String("acme".to_string()))], vec![0, 1])]
+    #[case::template(
+        "(tenant == $tenant) && (age > $min_age)",
+        vec![("tenant", Value::String("acme".to_string())), ("min_age", Value::Int(21))],
+        vec![0]
+    )]
+    #[case::repeated("(age > $age) || (name == \"Bob\") || (age == $age)", vec![("age", Value::Int(40))], vec![1, 2])]
+    #[case::list("name in $names", vec![("names", Value::VecString(vec!["Bob".to_string(), "Carol".to_string()]))], vec![1, 2])]
+    #[case::between("age between $low and $high", vec![("low", Value::Int(30)), ("high", Value::Int(40))], vec![0])]
+    #[case::range("age in 19..$high", vec![("high", Value::Int(41))], vec![0, 1])]
+    #[case::function("lower(name) == $name", vec![("name", Value::String("carol".to_string()))], vec![2])]
+    #[case::int_bound_to_float_field("score >= $min", vec![("min", Value::Int(0))], vec![0, 1, 2])]
+    #[case::injection_is_just_a_value(
+        "name == $name",
+        vec![("name", Value::String("x\") || (age > 0".to_string()))],
+        vec![]
+    )]
+    fn test_bind(
+        #[case] input: &str,
+        #[case] params: Vec<(&str, Value)>,
+        #[case] expected_indices: Vec<usize>,
+    ) {
+        let customers = customers();
+        let template = parse_query::<CustomerProperties>(input).unwrap();
+        let ast = template
+            .bind(&params.into_iter().collect())
+            .unwrap_or_else(|err| panic!("Failed to bind '{}': {:?}", input, err));
+
+        let filtered: Vec<Customer> = ast.apply(&customers);
+        let expected: Vec<Customer> = expected_indices
+            .iter()
+            .map(|index| customers[*index].clone())
+            .collect();
+
+        assert_eq!(filtered, expected);
+    }
+
+    #[test]
+    fn test_params_are_parsed_and_serialized() {
+        let template =
+            parse_query::<CustomerProperties>("(tenant == $tenant) && (age in $low..$high)")
+                .unwrap();
+        assert_eq!(template.params(), vec!["high", "low", "tenant"]);
+        assert_eq!(
+            template.to_string(),
+            "(tenant == $tenant) && ((age >= $low) && (age < $high))"
+        );
+        assert!(matches!(
+            template,
+            AST::And(ref left, _) if **left == AST::Deferred {
+                expr: Expr::Field(CustomerProperties::tenant),
+                operator: Operator::Equals,
+                operand: Operand::Param("tenant".to_string()),
+            }
+        ));
+    }
+
+    #[rstest]
+    #[case::comparison("age != $age", vec![])]
+    #[case::negated("!(age == $age)", vec![0, 1, 2])]
+    #[case::range("age in 20..$high", vec![])]
+    fn test_unbound_params_never_match(#[case] input: &str, #[case] expected_indices: Vec<usize>) {
+        let customers = customers();
+        let template = parse_query::<CustomerProperties>(input).unwrap();
+        let expected: Vec<Customer> = expected_indices
+            .iter()
+            .map(|index| customers[*index].clone())
+            .collect();
+        assert_eq!(template.apply(&customers), expected);
+    }
+
+    #[test]
+    fn test_params_do_not_change_other_values() {
+        let template =
+            parse_query::<CustomerProperties>("(age in 20..$high) && (score > 0.5)").unwrap();
+        let bound = template
+            .bind(&vec![("high", Value::Int(40))].into_iter().collect())
+            .unwrap();
+        assert_eq!(
+            bound,
+            parse_query::<CustomerProperties>("((age >= 20) && (age < 40)) && (score > 0.5)")
+                .unwrap()
+        );
+        assert_eq!(bound.apply(&customers()), vec![customers()[0].clone()]);
+    }
+
+    #[test]
+    fn test_bind_reports_unbound_and_extra_params() {
+        let template =
+            parse_query::<CustomerProperties>("(tenant == $tenant) && (age > $min_age)").unwrap();
+        let params: HashMap<&str, Value> = vec![
+            ("tenant", Value::String("acme".to_string())),
+            ("max_age", Value::Int(30)),
+            ("limit", Value::Int(10)),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(
+            template.bind(&params).unwrap_err(),
+            vec![ASTValidationError::ParameterMismatch {
+                unbound: vec!["min_age".to_string()],
+                extra: vec!["limit".to_string(), "max_age".to_string()],
+            }]
+        );
+    }
+
+    #[rstest]
+    #[case::string_for_int("age > $age", "age", Value::String("30".to_string()))]
+    #[case::int_for_string("name == $name", "name", Value::Int(1))]
+    #[case::list_for_comparison("age < $ages", "ages", Value::VecInt(vec![1]))]
+    #[case::range_bound("age between 1 and $high", "high", Value::String("z".to_string()))]
+    fn test_bind_type_checks_values(#[case] input: &str, #[case] name: &str, #[case] value: Value) {
+        let template = parse_query::<CustomerProperties>(input).unwrap();
+        let errors = template
+            .bind(&vec![(name, value)].into_iter().collect())
+            .unwrap_err();
+        assert!(matches!(
+            errors[0],
+            ASTValidationError::CompatibilityError(_)
+        ));
+    }
+
+    #[test]
+    fn test_bind_type_checks_expressions() {
+        let template = parse_query::<CustomerProperties>("lower(name) == $name").unwrap();
+        let errors = template
+            .bind(&vec![("name", Value::Int(3))].into_iter().collect())
+            .unwrap_err();
+        assert!(matches!(
+            errors[0],
+            ASTValidationError::IncompatibleExpression { .. }
+        ));
+    }
+
+    #[test]
+    fn test_parser_bind_checks_limits() {
+        let parser = Parser::new().max_list_length(2).max_regex_size(10_000);
+
+        let template = parser
+            .parse::<CustomerProperties>("name in $names")
+            .unwrap();
+        let names = Value::VecString(vec!["a".to_string(), "b".to_string(), "c".to_string()]);
+        let params: HashMap<&str, Value> = vec![("names", names)].into_iter().collect();
+        assert!(template.bind(&params).is_ok());
+        assert_eq!(
+            parser.bind(&template, &params).unwrap_err(),
+            vec![ASTValidationError::ListTooLong { length: 3, max: 2 }]
+        );
+
+        let template = parser
+            .parse::<CustomerProperties>("name regexmatch $re")
+            .unwrap();
+        let params: HashMap<&str, Value> = vec![("re", Value::String("a{10000}".to_string()))]
+            .into_iter()
+            .collect();
+        assert!(template.bind(&params).is_ok());
+        assert!(matches!(
+            parser.bind(&template, &params).unwrap_err()[0],
+            ASTValidationError::RegexTooLarge { max: 10_000, .. }
+        ));
+
+        let params: HashMap<&str, Value> = vec![("re", Value::String("^A".to_string()))]
+            .into_iter()
+            .collect();
+        assert!(parser.bind(&template, &params).is_ok());
+    }
+}
@@ -1,10 +1,14 @@
-use vec_filter::{parse_query, ASTValidationError, CompatibilityError, Filterable, Value, AST};
+use std::ops::Bound;
+use vec_filter::{
+    parse_query, ASTValidationError, CompatibilityError, Filterable, Pattern, Value, AST,
+};
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use rstest::rstest;
 
+    #[allow(dead_code)]
     #[derive(Debug, Clone, PartialEq, Filterable)]
     pub struct Person {
         pub name: String,
@@ -14,13 +18,17 @@ mod tests {
 
     #[rstest]
     #[rstest]
-    #[case::invalid_syntax("field1 == 'value'", ASTValidationError::InvalidSyntax)]
-    #[case::invalid_field("field1 == \"value\"", ASTValidationError::InvalidField { field_name: "field1".to_string() })]
+    #[case::invalid_syntax("field1 == \"value", ASTValidationError::InvalidSyntax)]
+    #[case::invalid_field("field1 == \"value\"", ASTValidationError::InvalidField {
+        field_name: "field1".to_string(),
+        suggestions: vec![],
+        valid_fields: vec!["name".to_string(), "age".to_string(), "interests".to_string()],
+    })]
     #[case::invalid_comparison("age == \"Alice\"", ASTValidationError::CompatibilityError(CompatibilityError {
-        ast: AST::Equals {
+        ast: Box::new(AST::Equals {
             field: PersonProperties::age,
             value: Value::String("Alice".to_string()),
-        },
+        }),
         field: PersonProperties::age,
         provided_value: Value::String("Alice".to_string()),
         valid_values: vec![Value::Int(i32::default())],
@@ -38,10 +46,10 @@ mod tests {
         ASTValidationError::InvalidSyntax
     )]
     #[case::invalid_value_type("interests == \"reading\"", ASTValidationError::CompatibilityError(CompatibilityError {
-        ast: AST::Equals {
+        ast: Box::new(AST::Equals {
             field: PersonProperties::interests,
             value: Value::String("reading".to_string()),
-        },
+        }),
         field: PersonProperties::interests,
         provided_value: Value::String("reading".to_string()),
         valid_values: vec![Value::VecString(vec![])],
@@ -66,6 +74,53 @@ mod tests {
         "(age > 25) && (name == \"Alice\") || age == 3",
         ASTValidationError::InvalidSyntax
     )]
+    #[case::between_type_mismatch("name between 1 and 2", ASTValidationError::CompatibilityError(CompatibilityError {
+        ast: Box::new(AST::Between {
+            field: PersonProperties::name,
+            lower: Bound::Included(Value::Int(1)),
+            upper: Bound::Included(Value::Int(2)),
+        }),
+        field: PersonProperties::name,
+        provided_value: Value::Int(1),
+        valid_values: vec![Value::String(String::default())],
+    }))]
+    #[case::range_on_list_field("interests in \"a\"..\"b\"", ASTValidationError::CompatibilityError(CompatibilityError {
+        ast: Box::new(AST::Between {
+            field: PersonProperties::interests,
+            lower: Bound::Included(Value::String("a".to_string())),
+            upper: Bound::Excluded(Value::String("b".to_string())),
+        }),
+        field: PersonProperties::interests,
+        provided_value: Value::String("a".to_string()),
+        valid_values: vec![],
+    }))]
+    #[case::between_missing_and("age between 1 2", ASTValidationError::InvalidSyntax)]
+    #[case::keyword_prefix(
+        "(age > 25) andy (name == \"Alice\")",
+        ASTValidationError::InvalidSyntax
+    )]
+    #[case::single_ampersand_keyword(
+        "(age > 25) & and (name == \"Alice\")",
+        ASTValidationError::InvalidSyntax
+    )]
+    #[case::unterminated_block_comment("age > 25 /* no end", ASTValidationError::InvalidSyntax)]
+    #[case::comment_inside_operator("age >/**/= 25", ASTValidationError::InvalidSyntax)]
+    #[case::unknown_escape("name == \"a\\qb\"", ASTValidationError::InvalidSyntax)]
+    #[case::invalid_unicode_escape("name == \"\\u{d800}\"", ASTValidationError::InvalidSyntax)]
+    #[case::unterminated_raw_string("name == r#\"abc\"", ASTValidationError::InvalidSyntax)]
+    #[case::unclosed_glob_class("name glob \"[ab\"", ASTValidationError::InvalidPattern {
+        pattern: "[ab".to_string(),
+        message: "unclosed character class".to_string(),
+    })]
+    #[case::like_on_int_field("age like \"3%\"", ASTValidationError::CompatibilityError(CompatibilityError {
+        ast: Box::new(AST::Like {
+            field: PersonProperties::age,
+            pattern: Pattern::like("3%").unwrap(),
+        }),
+        field: PersonProperties::age,
+        provided_value: Value::String("3%".to_string()),
+        valid_values: vec![],
+    }))]
     fn parse_query_error_handling(
         #[case] input: &str,
         #[case] expected_error: ASTValidationError<PersonProperties>,
@@ -74,4 +129,24 @@ mod tests {
         assert!(result.is_err());
         assert_eq!(result.unwrap_err()[0], expected_error);
     }
+
+    #[test]
+    fn test_trailing_input_is_reported_with_validation_errors() {
+        let result = parse_query::<PersonProperties>("(agee > 25) (name == \"Alice\")");
+        assert_eq!(
+            result.unwrap_err(),
+            vec![
+                ASTValidationError::InvalidField {
+                    field_name: "agee".to_string(),
+                    suggestions: vec!["age".to_string()],
+                    valid_fields: vec![
+                        "name".to_string(),
+                        "age".to_string(),
+                        "interests".to_string()
+                    ],
+                },
+                ASTValidationError::InvalidSyntax,
+            ]
+        );
+    }
 }
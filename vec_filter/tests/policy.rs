@@ -0,0 +1,196 @@
+use vec_filter::{
+    parse_query, AuthorizationError, Filterable, Function, Operator, PatternSyntax, QueryBuilder,
+    QueryPolicy, AST,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[derive(Debug, Clone, PartialEq, Filterable)]
+    #[filterable(builder)]
+    pub struct Employee {
+        pub name: String,
+        pub tenant: u32,
+        pub salary: u32,
+    }
+
+    fn parse(input: &str) -> AST<EmployeeProperties> {
+        parse_query::<EmployeeProperties>(input).unwrap()
+    }
+
+    fn policy() -> QueryPolicy<EmployeeProperties> {
+        QueryPolicy::new()
+            .forbid_field(EmployeeProperties::salary)
+            .forbid_operator(Operator::RegexMatch)
+            .forbid_pattern(PatternSyntax::Glob)
+            .forbid_ranges()
+            .forbid_function(Function::Abs)
+            .require(Employee::filter().tenant().eq(1))
+    }
+
+    fn employees() -> Vec<Employee> {
+        vec![
+            Employee {
+                name: "Alice".to_string(),
+                tenant: 1,
+                salary: 100,
+            },
+            Employee {
+                name: "Bob".to_string(),
+                tenant: 2,
+                salary: 200,
+            },
+            Employee {
+                name: "Carol".to_string(),
+                tenant: 1,
+                salary: 300,
+            },
+        ]
+    }
+
+    #[rstest]
+    #[case::comparison("name == \"Alice\"", "(tenant == 1) && (name == \"Alice\")")]
+    #[case::chain(
+        "(name == \"Alice\") || (tenant == 2)",
+        "(tenant == 1) && ((name == \"Alice\") || (tenant == 2))"
+    )]
+    #[case::like("name like \"A%\"", "(tenant == 1) && (name like \"A%\")")]
+    #[case::function("lower(name) == \"a\"", "(tenant == 1) && (lower(name) == \"a\")")]
+    #[case::constant("true", "(tenant == 1) && (true)")]
+    fn test_authorize(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(policy().authorize(parse(input)), Ok(parse(expected)));
+    }
+
+    #[rstest]
+    #[case::field("salary > 100", vec![AuthorizationError::ForbiddenField { field: EmployeeProperties::salary }])]
+    #[case::function_argument(
+        "abs(salary) > 100",
+        vec![
+            AuthorizationError::ForbiddenField { field: EmployeeProperties::salary },
+            AuthorizationError::ForbiddenFunction { function: Function::Abs },
+        ]
+    )]
+    #[case::operator(
+        "name regexmatch \"^A\"",
+        vec![AuthorizationError::ForbiddenOperator { operator: Operator::RegexMatch }]
+    )]
+    #[case::negated(
+        "!(name !~ \"^A\")",
+        vec![AuthorizationError::ForbiddenOperator { operator: Operator::NotRegexMatch }]
+    )]
+    #[case::negated_keyword(
+        "name not regexmatch \"^A\"",
+        vec![AuthorizationError::ForbiddenOperator { operator: Operator::NotRegexMatch }]
+    )]
+    #[case::keyword_case(
+        "name REGEXMATCH \"^A\"",
+        vec![AuthorizationError::ForbiddenOperator { operator: Operator::RegexMatch }]
+    )]
+    #[case::glob("name glob \"A*\"", vec![AuthorizationError::ForbiddenPattern { syntax: PatternSyntax::Glob }])]
+    #[case::negated_iglob(
+        "name not iglob \"a*\"",
+        vec![AuthorizationError::ForbiddenPattern { syntax: PatternSyntax::Glob }]
+    )]
+    #[case::between("tenant between 1 and 2", vec![AuthorizationError::ForbiddenRange])]
+    #[case::in_range("tenant in 1..2", vec![AuthorizationError::ForbiddenRange])]
+    #[case::function("abs(tenant) > 1", vec![AuthorizationError::ForbiddenFunction { function: Function::Abs }])]
+    #[case::nested_function(
+        "lower(substring(name, abs(tenant))) == \"a\"",
+        vec![AuthorizationError::ForbiddenFunction { function: Function::Abs }]
+    )]
+    #[case::repeated(
+        "(salary > 1) || (salary < 1)",
+        vec![AuthorizationError::ForbiddenField { field: EmployeeProperties::salary }]
+    )]
+    #[case::all(
+        "(name regexmatch \"^A\") && (salary > 1)",
+        vec![
+            AuthorizationError::ForbiddenField { field: EmployeeProperties::salary },
+            AuthorizationError::ForbiddenOperator { operator: Operator::RegexMatch },
+        ]
+    )]
+    fn test_authorize_errors(
+        #[case] input: &str,
+        #[case] expected: Vec<AuthorizationError<EmployeeProperties>>,
+    ) {
+        assert_eq!(policy().authorize(parse(input)), Err(expected));
+    }
+
+    #[rstest]
+    #[case::matching("name == \"Alice\"", vec![0])]
+    #[case::other_tenant("name == \"Bob\"", vec![])]
+    #[case::escape_attempt("(name == \"Bob\") || (true)", vec![0, 2])]
+    #[case::negated_scope("!(tenant == 1)", vec![])]
+    fn test_authorized_apply(#[case] input: &str, #[case] expected: Vec<usize>) {
+        let employees = employees();
+        let expected: Vec<Employee> = expected.iter().map(|&i| employees[i].clone()).collect();
+        let ast = policy().authorize(parse(input)).unwrap();
+        assert_eq!(ast.apply(&employees), expected);
+    }
+
+    #[test]
+    fn test_multiple_scopes() {
+        let policy = QueryPolicy::new()
+            .require(Employee::filter().tenant().eq(1))
+            .require(Employee::filter().salary().lt(200));
+        let ast = policy.authorize(parse("name != \"Carol\"")).unwrap();
+        assert_eq!(
+            ast,
+            parse("(tenant == 1) && (salary < 200) && (name != \"Carol\")")
+        );
+        assert_eq!(ast.apply(&employees()), vec![employees()[0].clone()]);
+    }
+
+    #[test]
+    fn test_empty_policy() {
+        let ast = parse(
+            "(name regexmatch \"1\") && (name glob \"*\") && (abs(salary) > 1) && (salary in 1..2)",
+        );
+        assert_eq!(QueryPolicy::new().authorize(ast.clone()), Ok(ast));
+    }
+
+    #[test]
+    fn test_scope_may_use_forbidden_field() {
+        let policy = QueryPolicy::new()
+            .forbid_field(EmployeeProperties::tenant)
+            .require(Employee::filter().tenant().eq(2));
+        let ast = policy.authorize(parse("name == \"Bob\"")).unwrap();
+        assert_eq!(ast.apply(&employees()), vec![employees()[1].clone()]);
+    }
+
+    #[rstest]
+    #[case::field(
+        AuthorizationError::ForbiddenField { field: EmployeeProperties::salary },
+        "Field salary is not allowed"
+    )]
+    #[case::operator(
+        AuthorizationError::ForbiddenOperator { operator: Operator::RegexMatch },
+        "Operator regexmatch is not allowed"
+    )]
+    #[case::pattern(
+        AuthorizationError::ForbiddenPattern { syntax: PatternSyntax::Glob },
+        "Operator glob is not allowed"
+    )]
+    #[case::range(AuthorizationError::ForbiddenRange, "Ranges are not allowed")]
+    #[case::function(
+        AuthorizationError::ForbiddenFunction { function: Function::Abs },
+        "Function abs is not allowed"
+    )]
+    fn test_error_display(
+        #[case] error: AuthorizationError<EmployeeProperties>,
+        #[case] expected: &str,
+    ) {
+        assert_eq!(error.to_string(), expected);
+        let error: Box<dyn std::error::Error> = Box::new(error);
+        assert_eq!(error.to_string(), expected);
+    }
+
+    #[test]
+    fn test_field_from_name() {
+        let field: EmployeeProperties = "salary".parse().unwrap();
+        let policy = QueryPolicy::new().forbid_field(field);
+        assert!(policy.authorize(parse("salary > 1")).is_err());
+    }
+}
@@ -0,0 +1,219 @@
+use std::ops::Range;
+use vec_filter::{
+    parse_query, ASTValidationError, Diagnostic, Filterable, ParseOutput, Parser, Value, AST,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[allow(dead_code)]
+    #[derive(Debug, Clone, PartialEq, Filterable)]
+    pub struct Person {
+        pub name: String,
+        pub age: u32,
+        pub interests: Vec<String>,
+    }
+
+    /// Returns the byte range of the first occurrence of `part` in `input`.
+    fn span(input: &str, part: &str) -> Range<usize> {
+        let start = input.find(part).unwrap();
+        start..start + part.len()
+    }
+
+    fn recover(input: &str) -> ParseOutput<PersonProperties> {
+        Parser::new().parse_with_recovery(input)
+    }
+
+    fn syntax_error(input: &str, part: &str) -> Diagnostic<PersonProperties> {
+        Diagnostic {
+            error: ASTValidationError::InvalidSyntax,
+            span: span(input, part),
+        }
+    }
+
+    fn alice() -> AST<PersonProperties> {
+        AST::Equals {
+            field: PersonProperties::name,
+            value: Value::String("Alice".to_string()),
+        }
+    }
+
+    fn over(age: i32) -> AST<PersonProperties> {
+        AST::GreaterThan {
+            field: PersonProperties::age,
+            value: Value::Int(age),
+        }
+    }
+
+    #[rstest]
+    #[case::comparison("name == \"Alice\"")]
+    #[case::chain("(name == \"Alice\") && (age > 30) || (age < 60)")]
+    #[case::keywords("not (name == \"Alice\") AND (age between 1 and 5)")]
+    #[case::nested("!((age > 1) || ((name == \"a\") && (age < 3)))")]
+    #[case::strings_and_comments("(name == \"(a) && (b\") /* || ( */ && (age > 1)")]
+    fn test_valid_queries_match_parse(#[case] input: &str) {
+        let output = recover(input);
+        assert_eq!(output.errors, vec![]);
+        assert_eq!(
+            output.ast,
+            Some(parse_query::<PersonProperties>(input).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_reports_every_clause() {
+        let input = "(name == \"Alice\") && (agee > 3) && (age > \"x\") && (lowr(name) == \"a\")";
+        let output = recover(input);
+
+        assert_eq!(output.ast, None);
+        let errors: Vec<(String, Range<usize>)> = output
+            .errors
+            .into_iter()
+            .map(|d| (d.error.to_string(), d.span))
+            .collect();
+        assert_eq!(errors.len(), 3);
+        assert_eq!(
+            errors[0],
+            (
+                "Invalid field agee, did you mean age?".to_string(),
+                span(input, "agee")
+            )
+        );
+        assert_eq!(errors[1].1, span(input, "age > \"x\""));
+        assert_eq!(
+            errors[2],
+            ("Unknown function lowr".to_string(), span(input, "lowr"))
+        );
+    }
+
+    #[test]
+    fn test_continues_after_syntax_error() {
+        let input = "(name == \"Alice\") || (age >) || (age > 10)";
+        let output = recover(input);
+        assert_eq!(output.errors, vec![syntax_error(input, "age >")]);
+        assert_eq!(
+            output.ast,
+            Some(AST::Or(Box::new(alice()), Box::new(over(10))))
+        );
+    }
+
+    #[rstest]
+    #[case::trailing_connector("(age > 1) && ", "&&", false)]
+    #[case::trailing_or("(age > 1) || ", "||", true)]
+    #[case::leading_connector("|| (age > 1)", "||", true)]
+    #[case::unclosed_group("(age > 1", "(", true)]
+    #[case::text_after_group("(age > 1) foo", "foo", true)]
+    #[case::text_after_comparison("(age > 1 foo)", "foo", false)]
+    #[case::unparenthesized_comparison("(age > 1) && name == \"Alice\"", "name == \"Alice\"", true)]
+    fn test_syntax_error_spans(#[case] input: &str, #[case] part: &str, #[case] recovered: bool) {
+        let output = recover(input);
+        assert_eq!(output.errors, vec![syntax_error(input, part)]);
+        assert_eq!(output.ast.is_some(), recovered);
+    }
+
+    #[test]
+    fn test_empty_clause_between_connectors() {
+        let input = "(age > 1) &&  || (age > 2)";
+        let output = recover(input);
+        assert_eq!(output.errors, vec![syntax_error(input, "  ")]);
+        assert_eq!(output.ast, Some(over(2)));
+    }
+
+    #[test]
+    fn test_errors_inside_groups() {
+        let input = "!((age > 1) && (nmae == \"a\")) || (age > 2)";
+        let output = recover(input);
+        assert_eq!(
+            output.errors,
+            vec![Diagnostic {
+                error: ASTValidationError::InvalidField {
+                    field_name: "nmae".to_string(),
+                    suggestions: vec!["name".to_string()],
+                    valid_fields: vec![
+                        "name".to_string(),
+                        "age".to_string(),
+                        "interests".to_string()
+                    ],
+                },
+                span: span(input, "nmae"),
+            }]
+        );
+        assert_eq!(
+            output.ast,
+            Some(AST::Or(
+                Box::new(AST::Not(Box::new(over(1)))),
+                Box::new(over(2))
+            ))
+        );
+    }
+
+    #[test]
+    fn test_number_out_of_range_span() {
+        let input = "(age > 99999999999) && (age > 1)";
+        let output = recover(input);
+        assert_eq!(
+            output.errors,
+            vec![Diagnostic {
+                error: ASTValidationError::NumberOutOfRange {
+                    literal: "99999999999".to_string(),
+                    span: span(input, "99999999999"),
+                },
+                span: span(input, "99999999999"),
+            }]
+        );
+        assert_eq!(output.ast, None);
+    }
+
+    fn not(ast: AST<PersonProperties>) -> Option<AST<PersonProperties>> {
+        Some(AST::Not(Box::new(ast)))
+    }
+
+    #[rstest]
+    #[case::and_is_dropped("(age > 1) && (agee > 2)", None)]
+    #[case::or_keeps_the_rest("(age > 1) || (agee > 2)", Some(over(1)))]
+    #[case::and_inside_or("((age > 1) && (agee > 2)) || (age > 3)", Some(over(3)))]
+    #[case::chain("(age > 1) && (agee > 2) || (age > 3)", Some(over(3)))]
+    #[case::or_then_and(
+        "(age > 1) || (agee > 2) && (age > 3)",
+        Some(AST::And(Box::new(over(1)), Box::new(over(3))))
+    )]
+    #[case::negated_and("!((age > 1) && (agee > 2))", not(over(1)))]
+    #[case::negated_or("!((age > 1) || (agee > 2))", None)]
+    #[case::not_keyword("not ((age > 1) || (agee > 2)) || (age > 3)", Some(over(3)))]
+    #[case::double_negation("!(!((age > 1) && (agee > 2)))", None)]
+    fn test_recovery_never_widens(
+        #[case] input: &str,
+        #[case] expected: Option<AST<PersonProperties>>,
+    ) {
+        let output = recover(input);
+        assert_eq!(output.errors.len(), 1);
+        assert_eq!(output.ast, expected);
+    }
+
+    #[test]
+    fn test_no_valid_clauses() {
+        let output = recover("(agee > 1) || (");
+        assert_eq!(output.ast, None);
+        assert_eq!(output.errors.len(), 3);
+    }
+
+    #[test]
+    fn test_limits_apply() {
+        let output = Parser::new()
+            .max_depth(1)
+            .parse_with_recovery::<PersonProperties>("((age > 1))");
+        assert_eq!(
+            output,
+            ParseOutput {
+                ast: None,
+                errors: vec![Diagnostic {
+                    error: ASTValidationError::NestingTooDeep { depth: 2, max: 1 },
+                    span: 0..11,
+                }],
+                warnings: vec![],
+            }
+        );
+    }
+}
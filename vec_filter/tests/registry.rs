@@ -0,0 +1,142 @@
+use vec_filter::{ASTValidationError, FieldType, Filterable, Function, Parser, Registry, Value};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[derive(Debug, Clone, PartialEq, Filterable)]
+    pub struct Host {
+        pub name: String,
+        pub ip: String,
+        pub port: u32,
+    }
+
+    fn hosts() -> Vec<Host> {
+        vec![
+            Host {
+                name: "db".to_string(),
+                ip: "10.0.3.7".to_string(),
+                port: 5432,
+            },
+            Host {
+                name: "web".to_string(),
+                ip: "192.168.1.20".to_string(),
+                port: 443,
+            },
+        ]
+    }
+
+    fn parser() -> Parser {
+        let mut registry = Registry::new();
+        registry
+            .register_operator(
+                "in_subnet",
+                vec![FieldType::String],
+                FieldType::String,
+                |ip, subnet| match (ip, subnet) {
+                    (Value::String(ip), Value::String(subnet)) => {
+                        let prefix = subnet.split('/').next().unwrap().trim_end_matches(".0");
+                        ip.starts_with(prefix)
+                    }
+                    _ => false,
+                },
+            )
+            .register_operator(
+                "divisible_by",
+                vec![FieldType::Int],
+                FieldType::Int,
+                |n, d| matches!((n, d), (Value::Int(n), Value::Int(d)) if n % d == 0),
+            )
+            .register_function(
+                "octet",
+                vec![FieldType::String, FieldType::Int],
+                FieldType::Int,
+                |args| match args {
+                    [Value::String(ip), Value::Int(i)] => ip
+                        .split('.')
+                        .nth(*i as usize)
+                        .and_then(|o| o.parse().ok())
+                        .map(Value::Int),
+                    _ => None,
+                },
+            );
+        Parser::new().registry(registry)
+    }
+
+    #[rstest]
+    #[case::custom_operator("ip in_subnet \"10.0.0.0/8\"", vec![0])]
+    #[case::custom_int_operator("port divisible_by 4", vec![0])]
+    #[case::custom_function("octet(ip, 0) == 192", vec![1])]
+    #[case::custom_function_case_insensitive("OCTET(ip, 3) > 10", vec![1])]
+    #[case::combined("(ip in_subnet \"10.0.0.0/8\") || (lower(name) == \"web\")", vec![0, 1])]
+    #[case::builtin_in_still_works("name in [\"db\"]", vec![0])]
+    fn test_custom_filtering(#[case] input: &str, #[case] expected_indices: Vec<usize>) {
+        let hosts = hosts();
+        let ast = parser()
+            .parse::<HostProperties>(input)
+            .unwrap_or_else(|err| panic!("Failed to parse input '{}': {:?}", input, err));
+
+        let filtered_hosts: Vec<Host> = ast.apply(&hosts);
+        let expected_hosts: Vec<Host> = expected_indices
+            .iter()
+            .map(|index| hosts[*index].clone())
+            .collect();
+
+        assert_eq!(filtered_hosts, expected_hosts);
+    }
+
+    #[test]
+    fn test_unregistered_operator_is_unknown() {
+        let result = Parser::new().parse::<HostProperties>("ip in_subnet \"10.0.0.0/8\"");
+        assert_eq!(
+            result.unwrap_err(),
+            vec![ASTValidationError::UnknownOperator {
+                operator: "in_subnet".to_string(),
+                suggestions: vec![],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_misspelled_custom_operator_suggestion() {
+        let errors = parser()
+            .parse::<HostProperties>("ip in_subnte \"10.0.0.0/8\"")
+            .unwrap_err();
+        assert_eq!(
+            errors[0].to_string(),
+            "Unknown operator in_subnte, did you mean in_subnet?"
+        );
+    }
+
+    #[test]
+    fn test_custom_operator_type_check() {
+        let errors = parser()
+            .parse::<HostProperties>("port in_subnet \"10.0.0.0/8\"")
+            .unwrap_err();
+        assert!(matches!(
+            errors[0],
+            ASTValidationError::IncompatibleExpression {
+                value_type: FieldType::Int,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_custom_function_type_check() {
+        let errors = parser()
+            .parse::<HostProperties>("octet(port, 0) == 1")
+            .unwrap_err();
+        match &errors[0] {
+            ASTValidationError::InvalidArguments {
+                function: Function::Custom(function),
+                arguments,
+            } => {
+                assert_eq!(function.name(), "octet");
+                assert_eq!(arguments, &vec![FieldType::Int, FieldType::Int]);
+            }
+            error => panic!("Unexpected error {:?}", error),
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use vec_filter::{parse_query, Filterable, AST};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use rstest::rstest;
+
+    #[derive(Debug, Clone, PartialEq, Filterable)]
+    pub struct Person {
+        pub name: String,
+        pub age: u32,
+        pub interests: Vec<String>,
+    }
+
+    fn parse(input: &str) -> AST<PersonProperties> {
+        parse_query::<PersonProperties>(input).unwrap()
+    }
+
+    /// Comparisons covering every way a negation can be pushed down: operators with an exact
+    /// opposite, ordering operators without one, patterns, ranges, calls and unbound parameters,
+    /// and the constants.
+    const PREDICATES: [&str; 14] = [
+        "name == \"bob\"",
+        "name != \"amy\"",
+        "name startswith \"a\"",
+        "name like \"%o%\"",
+        "age > 30",
+        "age <= 20",
+        "age between 25 and 40",
+        "age in [20,30]",
+        "interests in [\"chess\"]",
+        "lower(name) == \"amy\"",
+        "age == $age",
+        "name != $name",
+        "true",
+        "false",
+    ];
+
+    fn ast() -> impl Strategy<Value = AST<PersonProperties>> {
+        let leaf = (0..PREDICATES.len()).prop_map(|i| parse(PREDICATES[i]));
+        leaf.prop_recursive(5, 32, 2, |inner| {
+            prop_oneof![
+                inner.clone().prop_map(|ast| AST::Not(Box::new(ast))),
+                (inner.clone(), inner.clone())
+                    .prop_map(|(left, right)| AST::And(Box::new(left), Box::new(right))),
+                (inner.clone(), inner)
+                    .prop_map(|(left, right)| AST::Or(Box::new(left), Box::new(right))),
+            ]
+        })
+    }
+
+    fn person() -> impl Strategy<Value = Person> {
+        let name = prop_oneof![
+            Just("bob"),
+            Just("amy"),
+            Just("anna"),
+            Just("Amy"),
+            Just("joe")
+        ];
+        let interests = proptest::collection::vec(prop_oneof![Just("chess"), Just("go")], 0..3);
+        (name, 15u32..50, interests).prop_map(|(name, age, interests)| Person {
+            name: name.to_string(),
+            age,
+            interests: interests.into_iter().map(String::from).collect(),
+        })
+    }
+
+    /// Whether an AST is a chain of `And`s (if `outer` is set) or `Or`s of chains of the other
+    /// connective, with no connective below those.
+    fn is_normal_form(ast: &AST<PersonProperties>, outer: bool) -> bool {
+        let clauses = match outer {
+            true => ast.conjuncts(),
+            false => ast.disjuncts(),
+        };
+        clauses.iter().all(|clause| {
+            let literals = match outer {
+                true => clause.disjuncts(),
+                false => clause.conjuncts(),
+            };
+            literals
+                .iter()
+                .all(|literal| !matches!(literal, AST::And(..) | AST::Or(..)))
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn prop_simplify_matches_same_items(ast in ast(), people in proptest::collection::vec(person(), 0..8)) {
+            prop_assert_eq!(ast.simplify().apply(&people), ast.apply(&people));
+        }
+
+        #[test]
+        fn prop_cnf_matches_same_items(ast in ast(), people in proptest::collection::vec(person(), 0..8)) {
+            let cnf = ast.to_cnf();
+            prop_assert!(is_normal_form(&cnf, true), "{}", cnf);
+            prop_assert_eq!(cnf.apply(&people), ast.apply(&people));
+        }
+
+        #[test]
+        fn prop_dnf_matches_same_items(ast in ast(), people in proptest::collection::vec(person(), 0..8)) {
+            let dnf = ast.to_dnf();
+            prop_assert!(is_normal_form(&dnf, false), "{}", dnf);
+            prop_assert_eq!(dnf.apply(&people), ast.apply(&people));
+        }
+
+        #[test]
+        fn prop_simplify_is_idempotent(ast in ast()) {
+            let simplified = ast.simplify();
+            prop_assert_eq!(simplified.simplify(), simplified);
+        }
+    }
+
+    #[rstest]
+    #[case::double_negation("!(!(age > 1))", "age > 1")]
+    #[case::negated_operator("!(name == \"a\")", "name != \"a\"")]
+    #[case::negated_call("!(lower(name) startswith \"a\")", "lower(name) !startswith \"a\"")]
+    #[case::negated_pattern("!(name like \"a%\")", "name not like \"a%\"")]
+    #[case::ordering_kept("!(age > 1)", "!(age > 1)")]
+    #[case::parameter_kept("!(name == $name)", "!(name == $name)")]
+    #[case::de_morgan("!((name == \"a\") || !(age > 1))", "(name != \"a\") && (age > 1)")]
+    #[case::idempotence("(age > 1) && (age > 1)", "age > 1")]
+    #[case::absorption("(age > 1) && ((name == \"a\") || (age > 1))", "age > 1")]
+    #[case::dual_absorption("((age > 1) && (name == \"a\")) || (age > 1)", "age > 1")]
+    #[case::reordered(
+        "((age > 1) || (age < 0)) && ((age < 0) || (age > 1))",
+        "(age > 1) || (age < 0)"
+    )]
+    #[case::balanced(
+        "(((age > 1) && (age > 2)) && (age > 3)) && (age > 4)",
+        "((age > 1) && (age > 2)) && ((age > 3) && (age > 4))"
+    )]
+    #[case::identity("(age > 1) && (true)", "age > 1")]
+    #[case::absorbing("((age > 1) && (name == \"a\")) || (true)", "true")]
+    #[case::negated_constant("!(true) || (age > 1)", "age > 1")]
+    #[case::empty_chain("(true) && !(false)", "true")]
+    fn test_simplify(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(parse(input).simplify(), parse(expected));
+    }
+
+    #[rstest]
+    #[case::distribution(
+        "((age > 1) && (age > 2)) || (age > 3)",
+        "((age > 1) || (age > 3)) && ((age > 2) || (age > 3))"
+    )]
+    #[case::negation(
+        "!((age > 1) && (name == \"a\")) && (age > 3)",
+        "(!(age > 1) || (name != \"a\")) && (age > 3)"
+    )]
+    #[case::absorbed_clause("(age > 1) || ((age > 1) && (age > 2))", "age > 1")]
+    fn test_to_cnf(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(parse(input).to_cnf(), parse(expected));
+    }
+
+    #[rstest]
+    #[case::distribution(
+        "((age > 1) || (age > 2)) && (age > 3)",
+        "((age > 1) && (age > 3)) || ((age > 2) && (age > 3))"
+    )]
+    #[case::negation(
+        "!((age > 1) || (name == \"a\")) || (age > 3)",
+        "(!(age > 1) && (name != \"a\")) || (age > 3)"
+    )]
+    fn test_to_dnf(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(parse(input).to_dnf(), parse(expected));
+    }
+
+    #[test]
+    fn test_conjuncts_and_disjuncts() {
+        let ast = parse("((age > 1) && ((age > 2) || (age > 3))) && (age > 4)");
+        let conjuncts: Vec<String> = ast.conjuncts().iter().map(|ast| ast.to_string()).collect();
+        assert_eq!(
+            conjuncts,
+            vec!["age > 1", "(age > 2) || (age > 3)", "age > 4"]
+        );
+        assert_eq!(ast.disjuncts(), vec![&ast]);
+    }
+}
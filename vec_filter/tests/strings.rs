@@ -1,6 +1,7 @@
-use vec_filter::{parse_query, Filterable, StructProperties, Value, AST};
+use vec_filter::{parse_query, Filterable, Pattern, QueryStyle, StructProperties, Value, AST};
 
 use std::fmt;
+use std::ops::Bound;
 use std::str::FromStr;
 
 #[cfg(test)]
@@ -39,6 +40,57 @@ mod tests {
     #[case::name_endswith_ce("name endswith \"ce\"", vec![0])]
     #[case::name_regexmatch_alice_bob("name regexmatch \"^(Alice|Bob)$\"", vec![0, 1])]
     #[case::not_operation("!(age == 30)", vec![1, 2])]
+    #[case::age_between_20_and_25("age between 20 and 25", vec![1, 2])]
+    #[case::age_in_exclusive_range("age in 20..25", vec![1])]
+    #[case::age_in_inclusive_range("age in 20..=25", vec![1, 2])]
+    #[case::name_between("name between \"A\" and \"Bz\"", vec![0, 1])]
+    #[case::between_in_brackets("(age between 21 and 30) && (name != \"Alice\")", vec![2])]
+    #[case::name_not_in("name not in [\"Alice\",\"Bob\"]", vec![2])]
+    #[case::name_bang_in("name !in [\"Alice\"]", vec![1, 2])]
+    #[case::name_not_contains("name not contains \"l\"", vec![1])]
+    #[case::name_bang_contains("name !contains \"o\"", vec![0])]
+    #[case::name_not_startswith("name not startswith \"Al\"", vec![1, 2])]
+    #[case::name_not_endswith("name !endswith \"ol\"", vec![0, 1])]
+    #[case::name_not_regexmatch("name !~ \"^(Alice|Bob)$\"", vec![2])]
+    #[case::age_not_in_range("age not in 20..25", vec![0, 2])]
+    #[case::name_like("name like \"%o%\"", vec![1, 2])]
+    #[case::name_like_single_char("name like \"B_b\"", vec![1])]
+    #[case::name_like_is_case_sensitive("name like \"al%\"", vec![])]
+    #[case::name_ilike("name ilike \"al%\"", vec![0])]
+    #[case::name_not_like("name not like \"%l%\"", vec![1])]
+    #[case::name_like_escape("name like \"Al!%\" escape \"!\"", vec![])]
+    #[case::name_glob("name glob \"?o*\"", vec![1])]
+    #[case::name_glob_class("name glob \"[AB]*\"", vec![0, 1])]
+    #[case::name_iglob("name iglob \"*OL\"", vec![2])]
+    #[case::interests_like("interests like \"h%\"", vec![0, 2])]
+    #[case::single_quoted_string("name == 'Alice'", vec![0])]
+    #[case::single_quoted_list("interests in ['cooking','reading']", vec![0, 1])]
+    #[case::raw_string("name == r\"Bob\"", vec![1])]
+    #[case::unicode_escape("name contains \"\\u{63}\"", vec![0])]
+    #[case::empty_string("name != \"\"", vec![0, 1, 2])]
+    #[case::empty_string_contains("name contains ''", vec![0, 1, 2])]
+    #[case::keyword_and("(name = \"Alice\") and (age = 30)", vec![0])]
+    #[case::keyword_or_uppercase("(name == \"Alice\") OR (name == \"Bob\")", vec![0, 1])]
+    #[case::sql_not_equals("name <> \"Alice\"", vec![1, 2])]
+    #[case::keyword_not("not (age = 30)", vec![1, 2])]
+    #[case::keyword_not_without_space("NOT(age = 30)", vec![1, 2])]
+    #[case::mixed_spellings("(age >= 25) And ((name = \"Carol\") || (name <> \"Alice\"))", vec![2])]
+    #[case::uppercase_operators("name NOT IN [\"Alice\"]", vec![1, 2])]
+    #[case::uppercase_contains("name CONTAINS \"o\"", vec![1, 2])]
+    #[case::uppercase_between("age BETWEEN 20 AND 25", vec![1, 2])]
+    #[case::uppercase_like("name LIKE \"Al%\"", vec![0])]
+    #[case::multiline("(name == \"Alice\")\n    || (name == \"Bob\")\n", vec![0, 1])]
+    #[case::crlf_and_tabs("(\r\n\tage >= 25\r\n)\r\n&&\r\n(\tname\t!=\t\"Alice\"\t)", vec![2])]
+    #[case::unicode_whitespace("age\u{a0}==\u{3000}30", vec![0])]
+    #[case::line_comment("// people in their twenties\nage between 20 and 29 // inclusive", vec![1, 2])]
+    #[case::block_comment("(age > 20) /* adults */ && /* not Carol */ (name != \"Carol\")", vec![0])]
+    #[case::comment_between_tokens("name /* who */ in /* any of */ [\"Bob\",\"Carol\"]", vec![1, 2])]
+    #[case::multiline_block_comment("/*\n * Saved filter\n */\nnot (\n    interests in [\"hiking\"] // outdoors\n)", vec![1])]
+    #[case::list_with_spaces("name in [\"Alice\", \"Bob\"]", vec![0, 1])]
+    #[case::int_list_with_spaces("age in [ 20, 25 ]", vec![1, 2])]
+    #[case::multiline_list("name in [\n    \"Alice\", // first\n    \"Carol\"\n]", vec![0, 2])]
+    #[case::multiline_int_list("age in [20,\n25]", vec![1, 2])]
+    #[case::comment_markers_in_string("name != \"/* not a comment */\"", vec![0, 1, 2])]
     fn test_filtering(#[case] input: &str, #[case] expected_indices: Vec<usize>) {
         let alice = Person {
             name: "Alice".to_string(),
@@ -131,11 +183,101 @@ mod tests {
         )),
         Box::new(AST::In { field: PersonProperties::interests, value: Value::String("hiking".to_string()) })
     ))]
-    fn test_parse_query_ast_output(#[case] input: &str, #[case] expected_ast: AST<PersonProperties>) {
+    #[case::between_operation("age between 20 and 30", AST::Between {
+        field: PersonProperties::age,
+        lower: Bound::Included(Value::Int(20)),
+        upper: Bound::Included(Value::Int(30)),
+    })]
+    #[case::exclusive_range_operation("age in 20..30", AST::Between {
+        field: PersonProperties::age,
+        lower: Bound::Included(Value::Int(20)),
+        upper: Bound::Excluded(Value::Int(30)),
+    })]
+    #[case::not_in_operation("name not in [\"Alice\"]", AST::NotIn { field: PersonProperties::name, value: Value::VecString(vec!["Alice".to_string()]) })]
+    #[case::not_contains_operation("name !contains \"A\"", AST::NotContains { field: PersonProperties::name, value: Value::String("A".to_string()) })]
+    #[case::not_regexmatch_operation("name !~ \"A\"", AST::NotRegexMatch { field: PersonProperties::name, value: Value::String("A".to_string()) })]
+    #[case::escaped_quote("name == \"say \\\"hi\\\"\"", AST::Equals { field: PersonProperties::name, value: Value::String("say \"hi\"".to_string()) })]
+    #[case::escaped_newline("name == 'a\\nb\\\\c'", AST::Equals { field: PersonProperties::name, value: Value::String("a\nb\\c".to_string()) })]
+    #[case::raw_string_with_hashes("name == r#\"say \"hi\"\"#", AST::Equals { field: PersonProperties::name, value: Value::String("say \"hi\"".to_string()) })]
+    #[case::empty_string("name == \"\"", AST::Equals { field: PersonProperties::name, value: Value::String(String::new()) })]
+    #[case::like_operation("name like \"A%\"", AST::Like { field: PersonProperties::name, pattern: Pattern::like("A%").unwrap() })]
+    #[case::not_glob_operation("name !glob \"A*\"", AST::NotLike { field: PersonProperties::name, pattern: Pattern::glob("A*").unwrap() })]
+    fn test_parse_query_ast_output(
+        #[case] input: &str,
+        #[case] expected_ast: AST<PersonProperties>,
+    ) {
         let ast = parse_query::<PersonProperties>(input).unwrap();
         assert_eq!(ast, expected_ast);
     }
 
+    #[rstest]
+    #[case::equals("name == \"Alice\"", "name == \"Alice\"")]
+    #[case::list(
+        "interests in [\"reading\",\"hiking\"]",
+        "interests in [\"reading\",\"hiking\"]"
+    )]
+    #[case::int_list("age in [20,30]", "age in [20,30]")]
+    #[case::chain(
+        "(name == \"Alice\") || (name == \"Bob\") || (name == \"Eve\")",
+        "(name == \"Alice\") || (name == \"Bob\") || (name == \"Eve\")"
+    )]
+    #[case::nested(
+        "(interests in [\"hiking\"]) && ((age == 20) || (age == 25))",
+        "(interests in [\"hiking\"]) && ((age == 20) || (age == 25))"
+    )]
+    #[case::not("!(age == 30)", "!(age == 30)")]
+    #[case::not_chain(
+        "!((age == 30) && (name == \"Bob\"))",
+        "!((age == 30) && (name == \"Bob\"))"
+    )]
+    #[case::negated_operators(
+        "(name not in [\"Alice\"]) && (name !contains \"o\")",
+        "(name not in [\"Alice\"]) && (name not contains \"o\")"
+    )]
+    #[case::not_startswith("name !startswith \"A\"", "name not startswith \"A\"")]
+    #[case::not_regexmatch("name !~ \"^A\"", "name !~ \"^A\"")]
+    #[case::between("age between 20 and 30", "age between 20 and 30")]
+    #[case::escaped_quote("name == \"say \\\"hi\\\"\"", "name == \"say \\\"hi\\\"\"")]
+    #[case::single_quoted("name == 'it\\'s'", "name == \"it's\"")]
+    #[case::raw_string("name == r\"C:\\temp\"", "name == \"C:\\\\temp\"")]
+    #[case::control_characters("name == \"a\\tb\\u{7}\"", "name == \"a\\tb\\u{7}\"")]
+    #[case::empty_list_element("interests in [\"\",'a\"b']", "interests in [\"\",\"a\\\"b\"]")]
+    #[case::like("name like \"A%\"", "name like \"A%\"")]
+    #[case::not_ilike(
+        "name !ilike \"a#%%\" escape \"#\"",
+        "name not ilike \"a#%%\" escape \"#\""
+    )]
+    #[case::range("age in 20..30", "age in 20..30")]
+    #[case::inclusive_range("age in 20..=30", "age between 20 and 30")]
+    #[case::function("lower(name) startswith \"al\"", "lower(name) startswith \"al\"")]
+    fn test_serialize_round_trip(#[case] input: &str, #[case] expected: &str) {
+        let ast = parse_query::<PersonProperties>(input).unwrap();
+        let serialized = ast.to_string();
+        assert_eq!(serialized, expected);
+        assert_eq!(parse_query::<PersonProperties>(&serialized).unwrap(), ast);
+    }
+
+    #[rstest]
+    #[case::equals("age == 30", "age = 30")]
+    #[case::not_equals("name != \"Bob\"", "name <> \"Bob\"")]
+    #[case::chain(
+        "(age == 30) && (name != \"Bob\") || (age < 20)",
+        "(age = 30) and (name <> \"Bob\") or (age < 20)"
+    )]
+    #[case::not(
+        "!((age == 30) || (name contains \"o\"))",
+        "not ((age = 30) or (name contains \"o\"))"
+    )]
+    #[case::range("age in 20..30", "age in 20..30")]
+    fn test_serialize_keywords(#[case] input: &str, #[case] expected: &str) {
+        let ast = parse_query::<PersonProperties>(input).unwrap();
+        let serialized = ast.to_query(QueryStyle::Keywords);
+        assert_eq!(serialized, expected);
+        assert_eq!(parse_query::<PersonProperties>(&serialized).unwrap(), ast);
+        assert_eq!(ast.to_query(QueryStyle::Symbols), ast.to_string());
+    }
+
+    #[allow(dead_code)]
     #[derive(Clone, Debug)]
     struct DummyProperties;
 
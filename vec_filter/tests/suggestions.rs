@@ -0,0 +1,95 @@
+use vec_filter::{parse_query, ASTValidationError, Filterable, Parser};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[allow(dead_code)]
+    #[derive(Debug, Clone, PartialEq, Filterable)]
+    pub struct Person {
+        pub name: String,
+        pub age: u32,
+        pub interests: Vec<String>,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Debug, Clone, PartialEq, Filterable)]
+    pub struct Node {
+        pub code: String,
+        pub mode: String,
+        pub node: String,
+        pub nodes: Vec<String>,
+    }
+
+    #[rstest]
+    #[case::transposed_field("nmae == \"a\"", "Invalid field nmae, did you mean name?")]
+    #[case::extra_letter("agee > 1", "Invalid field agee, did you mean age?")]
+    #[case::missing_letter(
+        "intrests in [\"a\"]",
+        "Invalid field intrests, did you mean interests?"
+    )]
+    #[case::function_argument("lower(nam) == \"a\"", "Invalid field nam, did you mean name?")]
+    #[case::no_close_field("salary > 1", "Invalid field salary")]
+    #[case::operator(
+        "name contians \"a\"",
+        "Unknown operator contians, did you mean contains?"
+    )]
+    #[case::negated_operator(
+        "name not startwith \"a\"",
+        "Unknown operator startwith, did you mean startswith?"
+    )]
+    #[case::pattern_operator("name lkie \"a%\"", "Unknown operator lkie, did you mean like?")]
+    #[case::range_operator("age betwen 1 and 2", "Unknown operator betwen, did you mean between?")]
+    #[case::no_close_operator("name resembles \"a\"", "Unknown operator resembles")]
+    fn test_suggestion_messages(#[case] input: &str, #[case] expected: &str) {
+        let errors = parse_query::<PersonProperties>(input).unwrap_err();
+        assert_eq!(errors[0].to_string(), expected);
+    }
+
+    #[test]
+    fn test_invalid_field_lists_valid_fields() {
+        let errors = parse_query::<NodeProperties>("ode == \"a\"").unwrap_err();
+        assert_eq!(
+            errors,
+            vec![ASTValidationError::InvalidField {
+                field_name: "ode".to_string(),
+                suggestions: vec!["code".to_string(), "mode".to_string(), "node".to_string()],
+                valid_fields: vec![
+                    "code".to_string(),
+                    "mode".to_string(),
+                    "node".to_string(),
+                    "nodes".to_string()
+                ],
+            }]
+        );
+        assert_eq!(
+            errors[0].to_string(),
+            "Invalid field ode, did you mean code, mode or node?"
+        );
+    }
+
+    #[test]
+    fn test_suggestions_are_ranked() {
+        let errors = parse_query::<NodeProperties>("nodess == \"a\"").unwrap_err();
+        assert!(matches!(
+            &errors[0],
+            ASTValidationError::InvalidField { suggestions, .. }
+                if suggestions == &vec!["nodes".to_string(), "node".to_string()]
+        ));
+    }
+
+    #[test]
+    fn test_unknown_operator_span() {
+        let input = "(age > 1) && (name contians \"a\")";
+        let output = Parser::new().parse_with_recovery::<PersonProperties>(input);
+        assert_eq!(output.errors.len(), 1);
+        assert_eq!(output.errors[0].span, 19..27);
+    }
+
+    #[test]
+    fn test_operator_in_value_position_is_a_syntax_error() {
+        let errors = parse_query::<PersonProperties>("age 5").unwrap_err();
+        assert_eq!(errors, vec![ASTValidationError::InvalidSyntax]);
+    }
+}
@@ -0,0 +1,241 @@
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+use vec_filter::{
+    complete, parse_query, ASTValidationError, Anchor, Filterable, Operand, Parser, QueryBuilder,
+    Value, AST,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[derive(Debug, Clone, PartialEq, Filterable)]
+    #[filterable(builder)]
+    pub struct Event {
+        pub name: String,
+        pub created_at: DateTime<Utc>,
+        pub day: NaiveDate,
+        pub length: Duration,
+    }
+
+    fn events() -> Vec<Event> {
+        vec![
+            Event {
+                name: "launch".to_string(),
+                created_at: Utc.with_ymd_and_hms(2024, 1, 1, 9, 30, 0).unwrap(),
+                day: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+                length: Duration::try_minutes(90).unwrap(),
+            },
+            Event {
+                name: "retro".to_string(),
+                created_at: Utc.with_ymd_and_hms(2024, 3, 10, 16, 0, 0).unwrap(),
+                day: NaiveDate::from_ymd_opt(2024, 3, 10).unwrap(),
+                length: Duration::try_minutes(30).unwrap(),
+            },
+            Event {
+                name: "standup".to_string(),
+                created_at: Utc.with_ymd_and_hms(2024, 3, 14, 8, 0, 0).unwrap(),
+                day: NaiveDate::from_ymd_opt(2024, 3, 14).unwrap(),
+                length: Duration::try_minutes(15).unwrap(),
+            },
+        ]
+    }
+
+    fn parser() -> Parser {
+        Parser::new().clock(|| Utc.with_ymd_and_hms(2024, 3, 15, 12, 0, 0).unwrap())
+    }
+
+    #[rstest]
+    #[case::datetime("created_at > 2024-01-01T00:00:00Z", vec![0, 1, 2])]
+    #[case::datetime_with_offset("created_at == 2024-01-01T11:30:00+02:00", vec![0])]
+    #[case::datetime_fraction("created_at < 2024-01-01T09:30:00.001Z", vec![0])]
+    #[case::date_against_datetime("created_at >= 2024-03-10", vec![1, 2])]
+    #[case::date("day == 2024-03-14", vec![2])]
+    #[case::now_minus_days("created_at > now() - 7d", vec![1, 2])]
+    #[case::now_minus_compound("created_at > now() - 1d5h", vec![2])]
+    #[case::today("day < today() - 1w", vec![0])]
+    #[case::duration("length >= 30m", vec![0, 1])]
+    #[case::compound_duration("length > 1h15m", vec![0])]
+    #[case::duration_sum("length < 10m + 10m", vec![2])]
+    #[case::between("created_at between 2024-03-01 and now()", vec![1, 2])]
+    #[case::range("day in 2024-03-10..2024-03-14", vec![1])]
+    #[case::combined("(name == \"retro\") || (created_at > now() - 2d)", vec![1, 2])]
+    fn test_temporal_filtering(#[case] input: &str, #[case] expected_indices: Vec<usize>) {
+        let events = events();
+        let ast = parser()
+            .parse::<EventProperties>(input)
+            .unwrap_or_else(|err| panic!("Failed to parse input '{}': {:?}", input, err));
+
+        let filtered_events: Vec<Event> = ast.apply(&events);
+        let expected_events: Vec<Event> = expected_indices
+            .iter()
+            .map(|index| events[*index].clone())
+            .collect();
+
+        assert_eq!(filtered_events, expected_events);
+    }
+
+    #[test]
+    fn test_temporal_builder() {
+        let ast = Event::filter()
+            .day()
+            .ge(NaiveDate::from_ymd_opt(2024, 3, 10).unwrap())
+            .and(
+                Event::filter()
+                    .length()
+                    .lt(Duration::try_minutes(60).unwrap()),
+            );
+        assert_eq!(
+            ast,
+            parse_query::<EventProperties>("(day >= 2024-03-10) && (length < 1h)").unwrap()
+        );
+        assert_eq!(ast.apply(&events()), events()[1..].to_vec());
+    }
+
+    #[test]
+    fn test_now_is_resolved_with_the_clock() {
+        let ast = parser()
+            .parse::<EventProperties>("created_at > now() - 7d")
+            .unwrap();
+        let time = match &ast {
+            AST::Deferred {
+                operand: Operand::Relative(time),
+                ..
+            } => time,
+            ast => panic!("Expected a relative time, got {:?}", ast),
+        };
+        assert_eq!(time.anchor(), Anchor::Now);
+        assert_eq!(time.offsets(), &[-Duration::try_days(7).unwrap()]);
+        assert_eq!(
+            time.resolve(),
+            Some(Value::DateTime(
+                Utc.with_ymd_and_hms(2024, 3, 8, 12, 0, 0).unwrap()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_stored_query_follows_the_clock() {
+        let now = Arc::new(AtomicI64::new(
+            Utc.with_ymd_and_hms(2024, 3, 15, 12, 0, 0)
+                .unwrap()
+                .timestamp(),
+        ));
+        let clock = now.clone();
+        let parser = Parser::new()
+            .clock(move || DateTime::from_timestamp(clock.load(Ordering::SeqCst), 0).unwrap());
+        let ast = parser
+            .parse::<EventProperties>("(created_at > now() - 7d) && (day <= today())")
+            .unwrap();
+        assert_eq!(ast.apply(&events()), events()[1..].to_vec());
+
+        now.store(
+            Utc.with_ymd_and_hms(2024, 3, 20, 12, 0, 0)
+                .unwrap()
+                .timestamp(),
+            Ordering::SeqCst,
+        );
+        assert_eq!(ast.apply(&events()), events()[2..].to_vec());
+    }
+
+    #[test]
+    fn test_apply_reads_the_clock_once() {
+        let now = Arc::new(AtomicI64::new(
+            Utc.with_ymd_and_hms(2024, 3, 10, 0, 0, 0)
+                .unwrap()
+                .timestamp(),
+        ));
+        let clock = now.clone();
+        let day = Duration::try_days(1).unwrap().num_seconds();
+        // Every read moves the clock on by a day.
+        let parser = Parser::new().clock(move || {
+            DateTime::from_timestamp(clock.fetch_add(day, Ordering::SeqCst), 0).unwrap()
+        });
+        let ast = parser
+            .parse::<EventProperties>("(created_at < now() + 5d) && (day <= today() + 5d)")
+            .unwrap();
+        let before = now.load(Ordering::SeqCst);
+        assert_eq!(ast.apply(&events()), events());
+        assert_eq!(now.load(Ordering::SeqCst), before + day);
+    }
+
+    #[rstest]
+    #[case::now("created_at > now()", "created_at > now()")]
+    #[case::now_minus("created_at > now() - 7d", "created_at > now() - 7d")]
+    #[case::today_plus("day <= today()+1w - 12h", "day <= today() + 7d - 12h")]
+    #[case::negative_offset("day <= today() + -1d", "day <= today() - 1d")]
+    #[case::between(
+        "created_at between 2024-03-01 and now()",
+        "(created_at >= 2024-03-01T00:00:00Z) && (created_at <= now())"
+    )]
+    #[case::negated("!(created_at == now())", "created_at != now()")]
+    fn test_relative_serialization(#[case] input: &str, #[case] expected: &str) {
+        let ast = parser().parse::<EventProperties>(input).unwrap();
+        let ast = match ast {
+            AST::Not(_) => ast.simplify(),
+            ast => ast,
+        };
+        assert_eq!(ast.to_string(), expected);
+        assert_eq!(parser().parse::<EventProperties>(expected).unwrap(), ast);
+    }
+
+    #[rstest]
+    #[case::datetime(
+        "created_at > 2024-01-01T00:00:00Z",
+        "created_at > 2024-01-01T00:00:00Z"
+    )]
+    #[case::offset(
+        "created_at > 2024-01-01T02:00:00.5+02:00",
+        "created_at > 2024-01-01T00:00:00.500Z"
+    )]
+    #[case::date("day == 2024-03-14", "day == 2024-03-14")]
+    #[case::duration("length > 90m", "length > 1h30m")]
+    #[case::negative_duration("length > -1500ms", "length > -1s500ms")]
+    #[case::zero_duration("length > 0s", "length > 0s")]
+    fn test_temporal_serialization(#[case] input: &str, #[case] expected: &str) {
+        let ast = parse_query::<EventProperties>(input).unwrap();
+        assert_eq!(ast.to_string(), expected);
+        assert_eq!(parse_query::<EventProperties>(expected).unwrap(), ast);
+    }
+
+    #[rstest]
+    #[case::offset_on_number("name == \"a\" - 1d", ASTValidationError::InvalidSyntax)]
+    #[case::duration_overflow("length > 99999999999999999w", ASTValidationError::NumberOutOfRange {
+        literal: "99999999999999999w".to_string(),
+        span: 9..27,
+    })]
+    fn test_temporal_errors(
+        #[case] input: &str,
+        #[case] expected_error: ASTValidationError<EventProperties>,
+    ) {
+        let result = parse_query::<EventProperties>(input);
+        assert_eq!(result.unwrap_err()[0], expected_error);
+    }
+
+    #[rstest]
+    #[case::duration_on_datetime_field("created_at > 7d")]
+    #[case::invalid_date("day == 2024-02-30")]
+    #[case::unknown_unit("length > 5y")]
+    #[case::date_on_duration_field("length > 2024-01-01")]
+    #[case::now_on_duration_field("length > now() - 1d")]
+    #[case::today_on_string_field("name == today()")]
+    fn test_temporal_type_errors(#[case] input: &str) {
+        let errors = parse_query::<EventProperties>(input).unwrap_err();
+        assert!(matches!(
+            errors[0],
+            ASTValidationError::CompatibilityError(_)
+        ));
+    }
+
+    #[rstest]
+    #[case::datetime("created_at > ", vec!["now()", "today()"])]
+    #[case::date("day == to", vec!["today()"])]
+    #[case::duration("length > ", vec![])]
+    fn test_temporal_value_completions(#[case] input: &str, #[case] expected: Vec<&str>) {
+        let completions = complete::<EventProperties>(input, input.len());
+        let texts: Vec<&str> = completions.iter().map(|c| c.text.as_str()).collect();
+        assert_eq!(texts, expected);
+    }
+}
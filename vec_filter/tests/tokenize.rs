@@ -0,0 +1,159 @@
+use vec_filter::{tokenize, FieldType, Filterable, Parser, Registry, Token, TokenKind};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use TokenKind::{
+        Comment, Error, Field, Function, Logical, Number, Operator, Parameter, Punctuation,
+        UnknownField, Whitespace,
+    };
+
+    #[allow(dead_code)]
+    #[derive(Debug, Clone, PartialEq, Filterable)]
+    pub struct Person {
+        pub name: String,
+        pub age: u32,
+        pub interests: Vec<String>,
+    }
+
+    /// The text and kind of each token, leaving out whitespace.
+    fn significant<'a>(input: &'a str, tokens: &[Token]) -> Vec<(&'a str, TokenKind)> {
+        tokens
+            .iter()
+            .filter(|token| token.kind != Whitespace)
+            .map(|token| (&input[token.span.clone()], token.kind))
+            .collect()
+    }
+
+    #[rstest]
+    #[case::comparison("name == \"Alice\"", vec![("name", Field), ("==", Operator), ("\"Alice\"", TokenKind::String)])]
+    #[case::connectors("(age > 1) AND (age < 5)", vec![
+        ("(", Punctuation), ("age", Field), (">", Operator), ("1", Number), (")", Punctuation),
+        ("AND", Logical),
+        ("(", Punctuation), ("age", Field), ("<", Operator), ("5", Number), (")", Punctuation),
+    ])]
+    #[case::negated_group("!(age >= 1)", vec![
+        ("!", Logical), ("(", Punctuation), ("age", Field), (">=", Operator), ("1", Number),
+        (")", Punctuation),
+    ])]
+    #[case::negated_operator("name not in [\"a\",\"b\"]", vec![
+        ("name", Field), ("not", Operator), ("in", Operator), ("[", Punctuation), ("\"a\"", TokenKind::String),
+        (",", Punctuation), ("\"b\"", TokenKind::String), ("]", Punctuation),
+    ])]
+    #[case::negated_symbol("name !contains 'a'", vec![("name", Field), ("!", Operator), ("contains", Operator), ("'a'", TokenKind::String)])]
+    #[case::between("age between -1 and 2.5", vec![
+        ("age", Field), ("between", Operator), ("-1", Number), ("and", Operator), ("2.5", Number),
+    ])]
+    #[case::range("age in 1..=5", vec![("age", Field), ("in", Operator), ("1", Number), ("..=", Punctuation), ("5", Number)])]
+    #[case::call("lower(name) like r\"a%\" escape \"!\"", vec![
+        ("lower", Function), ("(", Punctuation), ("name", Field), (")", Punctuation),
+        ("like", Operator), ("r\"a%\"", TokenKind::String), ("escape", Operator), ("\"!\"", TokenKind::String),
+    ])]
+    #[case::custom_operator("name sounds_like \"a\"", vec![("name", Field), ("sounds_like", Operator), ("\"a\"", TokenKind::String)])]
+    #[case::parameter("age > $min_age", vec![("age", Field), (">", Operator), ("$min_age", Parameter)])]
+    #[case::temporal("created_at > now() - 7d", vec![
+        ("created_at", Field), (">", Operator), ("now", Function), ("(", Punctuation),
+        (")", Punctuation), ("-", Punctuation), ("7d", Number),
+    ])]
+    #[case::date("day == 2024-01-01T00:00:00+05:00", vec![("day", Field), ("==", Operator), ("2024-01-01T00:00:00+05:00", Number)])]
+    #[case::comments("age // line\n/* block */ > 1", vec![
+        ("age", Field), ("// line", Comment), ("/* block */", Comment), (">", Operator), ("1", Number),
+    ])]
+    fn test_token_kinds(#[case] input: &str, #[case] expected: Vec<(&str, TokenKind)>) {
+        assert_eq!(significant(input, &tokenize(input)), expected);
+    }
+
+    #[rstest]
+    #[case::unterminated_string("name == \"Ali", vec![("name", Field), ("==", Operator), ("\"Ali", Error)])]
+    #[case::invalid_escape("name == \"a\\qb\" && x", vec![
+        ("name", Field), ("==", Operator), ("\"a\\qb\"", Error), ("&&", Logical), ("x", Field),
+    ])]
+    #[case::unterminated_raw_string("name == r#\"a\"", vec![("name", Field), ("==", Operator), ("r#\"a\"", Error)])]
+    #[case::unterminated_comment("age > 1 /* note", vec![("age", Field), (">", Operator), ("1", Number), ("/* note", Error)])]
+    #[case::stray_symbols("age @#% 1", vec![("age", Field), ("@#%", Error), ("1", Number)])]
+    #[case::single_ampersand("(a) & (b)", vec![
+        ("(", Punctuation), ("a", Field), (")", Punctuation), ("&", Error), ("(", Punctuation),
+        ("b", Field), (")", Punctuation),
+    ])]
+    #[case::lone_dollar("age > $", vec![("age", Field), (">", Operator), ("$", Error)])]
+    fn test_error_tokens(#[case] input: &str, #[case] expected: Vec<(&str, TokenKind)>) {
+        assert_eq!(significant(input, &tokenize(input)), expected);
+    }
+
+    #[rstest]
+    #[case::empty("")]
+    #[case::whitespace(" \n\t")]
+    #[case::garbage("¿¡\"'(]]&|")]
+    #[case::query("((age > 1) || !(name ilike \"a%\"))\n&& (interests == [\"x\"])")]
+    fn test_tokens_cover_input(#[case] input: &str) {
+        let tokens = tokenize(input);
+        let mut end = 0;
+        for token in &tokens {
+            assert_eq!(token.span.start, end);
+            assert!(token.span.end > token.span.start);
+            end = token.span.end;
+        }
+        assert_eq!(end, input.len());
+    }
+
+    #[test]
+    fn test_spans() {
+        assert_eq!(
+            tokenize("age>1"),
+            vec![
+                Token {
+                    kind: Field,
+                    span: 0..3
+                },
+                Token {
+                    kind: Operator,
+                    span: 3..4
+                },
+                Token {
+                    kind: Number,
+                    span: 4..5
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolved_tokens() {
+        let mut registry = Registry::new();
+        registry.register_operator(
+            "sounds_like",
+            vec![FieldType::String],
+            FieldType::String,
+            |_, _| true,
+        );
+        let parser = Parser::new().registry(registry);
+
+        let input = "(Name sounds_like \"a\") && (agee resembles 1) && (lower(nmae) == \"a\")";
+        assert_eq!(
+            significant(input, &parser.tokenize::<PersonProperties>(input)),
+            vec![
+                ("(", Punctuation),
+                ("Name", Field),
+                ("sounds_like", Operator),
+                ("\"a\"", TokenKind::String),
+                (")", Punctuation),
+                ("&&", Logical),
+                ("(", Punctuation),
+                ("agee", UnknownField),
+                ("resembles", Error),
+                ("1", Number),
+                (")", Punctuation),
+                ("&&", Logical),
+                ("(", Punctuation),
+                ("lower", Function),
+                ("(", Punctuation),
+                ("nmae", UnknownField),
+                (")", Punctuation),
+                ("==", Operator),
+                ("\"a\"", TokenKind::String),
+                (")", Punctuation),
+            ]
+        );
+    }
+}
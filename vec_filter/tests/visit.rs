@@ -0,0 +1,190 @@
+use vec_filter::{
+    parse_query, walk_ast, walk_predicate, Expr, Filterable, Fold, Value, Visitor, VisitorMut, AST,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    /// The struct users write queries against.
+    #[allow(dead_code)]
+    #[derive(Debug, Clone, PartialEq, Filterable)]
+    pub struct Person {
+        pub name: String,
+        pub age: u32,
+        pub interests: Vec<String>,
+    }
+
+    /// The struct the queries are run on.
+    #[derive(Debug, Clone, PartialEq, Filterable)]
+    pub struct Employee {
+        pub full_name: String,
+        pub years: u32,
+        pub hobbies: Vec<String>,
+    }
+
+    fn to_employee(field: PersonProperties) -> EmployeeProperties {
+        match field {
+            PersonProperties::name => EmployeeProperties::full_name,
+            PersonProperties::age => EmployeeProperties::years,
+            PersonProperties::interests => EmployeeProperties::hobbies,
+        }
+    }
+
+    fn parse(input: &str) -> AST<PersonProperties> {
+        parse_query::<PersonProperties>(input).unwrap()
+    }
+
+    #[rstest]
+    #[case::comparison("age > 1", vec!["age"])]
+    #[case::logical("((name == \"a\") && !(age > 1)) || (interests in [\"x\"])", vec!["name", "age", "interests"])]
+    #[case::range_and_pattern("(age between 1 and 2) && (name like \"a%\")", vec!["age", "name"])]
+    #[case::call_arguments("coalesce(name, lower(name)) == \"a\"", vec!["name", "name"])]
+    fn test_fields(#[case] input: &str, #[case] expected: Vec<&str>) {
+        let ast = parse(input);
+        let fields: Vec<String> = ast.fields().map(|field| field.to_string()).collect();
+        assert_eq!(fields, expected);
+    }
+
+    #[test]
+    fn test_predicates() {
+        let ast = parse("((name == \"a\") && !(age between 1 and 2)) || (name like \"a%\")");
+        let predicates: Vec<String> = ast.predicates().map(|ast| ast.to_string()).collect();
+        assert_eq!(
+            predicates,
+            vec!["name == \"a\"", "age between 1 and 2", "name like \"a%\""]
+        );
+    }
+
+    #[test]
+    fn test_visitor() {
+        /// Counts the nodes of each kind, and the depth of the deepest `Not`.
+        #[derive(Default)]
+        struct Counter {
+            nodes: usize,
+            predicates: usize,
+            values: usize,
+            negations: usize,
+            depth: usize,
+        }
+
+        impl<'ast> Visitor<'ast, PersonProperties> for Counter {
+            fn visit_ast(&mut self, ast: &'ast AST<PersonProperties>) {
+                self.nodes += 1;
+                if let AST::Not(_) = ast {
+                    self.depth += 1;
+                    self.negations = self.negations.max(self.depth);
+                    walk_ast(self, ast);
+                    self.depth -= 1;
+                } else {
+                    walk_ast(self, ast);
+                }
+            }
+
+            fn visit_predicate(&mut self, ast: &'ast AST<PersonProperties>) {
+                self.predicates += 1;
+                walk_predicate(self, ast);
+            }
+
+            fn visit_value(&mut self, _: &'ast Value) {
+                self.values += 1;
+            }
+        }
+
+        let ast = parse("!((age in 1..5) && !(substring(name, 0, 1) == \"a\"))");
+        let mut counter = Counter::default();
+        counter.visit_ast(&ast);
+        assert_eq!(counter.nodes, 5);
+        assert_eq!(counter.predicates, 2);
+        // The bounds of the range, the arguments of `substring` and the compared value.
+        assert_eq!(counter.values, 5);
+        assert_eq!(counter.negations, 2);
+    }
+
+    #[test]
+    fn test_visitor_mut() {
+        struct Lowercase;
+
+        impl VisitorMut<PersonProperties> for Lowercase {
+            fn visit_value_mut(&mut self, value: &mut Value) {
+                match value {
+                    Value::String(s) => *s = s.to_lowercase(),
+                    Value::VecString(values) => {
+                        for s in values {
+                            *s = s.to_lowercase();
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let mut ast = parse("(name == \"Alice\") || (interests in [\"Chess\",\"GO\"])");
+        Lowercase.visit_ast_mut(&mut ast);
+        assert_eq!(
+            ast,
+            parse("(name == \"alice\") || (interests in [\"chess\",\"go\"])")
+        );
+    }
+
+    #[test]
+    fn test_map_fields() {
+        let ast = parse(
+            "(name startswith \"A\") && ((age between 30 and 40) || (lower(name) == \"bob\"))",
+        )
+        .map_fields(to_employee);
+        assert_eq!(
+            ast.to_string(),
+            "(full_name startswith \"A\") && ((years between 30 and 40) || (lower(full_name) == \"bob\"))"
+        );
+
+        let employees = vec![
+            Employee {
+                full_name: "Alice".to_string(),
+                years: 35,
+                hobbies: vec![],
+            },
+            Employee {
+                full_name: "Andrew".to_string(),
+                years: 25,
+                hobbies: vec![],
+            },
+            Employee {
+                full_name: "Bob".to_string(),
+                years: 35,
+                hobbies: vec![],
+            },
+        ];
+        assert_eq!(ast.apply(&employees), vec![employees[0].clone()]);
+    }
+
+    #[test]
+    fn test_fold() {
+        /// Maps the fields and doubles every integer.
+        struct Double;
+
+        impl Fold<PersonProperties, EmployeeProperties> for Double {
+            fn fold_field(&mut self, field: PersonProperties) -> EmployeeProperties {
+                to_employee(field)
+            }
+
+            fn fold_value(&mut self, value: Value) -> Value {
+                match value {
+                    Value::Int(n) => Value::Int(n * 2),
+                    value => value,
+                }
+            }
+        }
+
+        let ast = Double.fold_ast(parse("(age > 10) && ((abs(age) == 5) || (age in 1..=5))"));
+        assert_eq!(
+            ast.to_string(),
+            "(years > 20) && ((abs(years) == 10) || (years between 2 and 10))"
+        );
+        assert_eq!(
+            Double.fold_expr(Expr::Field(PersonProperties::age)),
+            Expr::Field(EmployeeProperties::years)
+        );
+    }
+}
@@ -0,0 +1,107 @@
+use vec_filter::{parse_query, Filterable, Parser, Warning, AST};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[allow(dead_code)]
+    #[derive(Debug, Clone, PartialEq, Filterable)]
+    pub struct Person {
+        pub name: String,
+        pub age: u32,
+        pub height: f64,
+        pub interests: Vec<String>,
+    }
+
+    fn parse(input: &str) -> AST<PersonProperties> {
+        parse_query::<PersonProperties>(input).unwrap()
+    }
+
+    #[rstest]
+    #[case::disjoint_ranges("(age > 30) && (age < 20)")]
+    #[case::different_values("(name == \"a\") && (name == \"b\")")]
+    #[case::value_outside_range("(age == 5) && (age > 10)")]
+    #[case::empty_between("age between 5 and 1")]
+    #[case::no_integer_between("(age > 1) && (age < 2)")]
+    #[case::excluded_endpoint("(height >= 1.5) && (height < 1.5)")]
+    #[case::string_range("name between \"m\" and \"c\"")]
+    #[case::not_in_list("(age in [1,2]) && (age not in [1,2])")]
+    #[case::list_outside_range("(age in [1,2,3]) && (age >= 4)")]
+    #[case::every_integer_excluded(
+        "(age >= 1) && (age <= 3) && (age != 1) && (age != 2) && (age != 3)"
+    )]
+    #[case::value_fails_pattern("(name == \"bob\") && (name like \"a%\")")]
+    #[case::value_fails_prefix("(name startswith \"a\") && (name == \"bob\")")]
+    #[case::negated_ordering("!(age <= 30) && (age < 20)")]
+    #[case::predicate_and_negation("(lower(name) == \"a\") && !(lower(name) == \"a\")")]
+    #[case::every_branch("((age > 30) || (age == 1)) && (age < 0)")]
+    fn test_contradiction(#[case] input: &str) {
+        let ast = parse(input);
+        assert_eq!(ast.warnings(), vec![Warning::Contradiction(ast)]);
+    }
+
+    #[rstest]
+    #[case::value_or_not("(name == \"a\") || (name != \"a\")")]
+    #[case::excluded_or_not("!(age in [1,2]) || (age in [1,2])")]
+    #[case::predicate_or_negation("(name like \"a%\") || !(name like \"a%\")")]
+    #[case::negated_contradiction("!((age > 30) && (age < 20))")]
+    fn test_tautology(#[case] input: &str) {
+        let ast = parse(input);
+        assert_eq!(ast.warnings(), vec![Warning::Tautology(ast)]);
+    }
+
+    #[rstest]
+    #[case::overlapping_ranges("(age > 20) && (age < 30)")]
+    #[case::value_in_range("(age == 25) && (age between 20 and 30)")]
+    #[case::different_fields("(name == \"a\") && (age == 1)")]
+    #[case::some_branch("((age > 30) || (age == 1)) && (age < 5)")]
+    #[case::value_matches_pattern("(name == \"alice\") && (name like \"a%\")")]
+    #[case::same_value_excluded_twice("(name != \"a\") && (name != \"a\")")]
+    #[case::missing_value("(age > 30) || (age <= 30)")]
+    #[case::nan("!(height > 1.5) && !(height <= 1.5)")]
+    #[case::parameters("(age == $min) && (age == $max)")]
+    #[case::list_field("(interests in [\"a\"]) && (interests not in [\"b\"])")]
+    fn test_no_warnings(#[case] input: &str) {
+        assert_eq!(parse(input).warnings(), vec![]);
+    }
+
+    #[test]
+    fn test_warnings_for_parts() {
+        let ast =
+            parse("((age > 30) && (age < 20)) || ((name == \"a\") && ((age == 1) || (age != 1)))");
+        assert_eq!(
+            ast.warnings(),
+            vec![
+                Warning::Contradiction(parse("(age > 30) && (age < 20)")),
+                Warning::Tautology(parse("(age == 1) || (age != 1)")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        let ast = parse("(age > 30) && (age < 20)");
+        assert_eq!(
+            ast.warnings()[0].to_string(),
+            "(age > 30) && (age < 20) never matches"
+        );
+        let ast = parse("(age == 1) || (age != 1)");
+        assert_eq!(
+            ast.warnings()[0].to_string(),
+            "(age == 1) || (age != 1) always matches"
+        );
+    }
+
+    #[test]
+    fn test_parse_output_warnings() {
+        let output = Parser::new().parse_with_recovery::<PersonProperties>(
+            "(agee > 1) || ((name == \"a\") && (name == \"b\"))",
+        );
+        assert_eq!(output.errors.len(), 1);
+        assert_eq!(
+            output.warnings,
+            vec![Warning::Contradiction(output.ast.clone().unwrap())]
+        );
+    }
+}
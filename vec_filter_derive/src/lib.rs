@@ -1,16 +1,85 @@
 extern crate proc_macro;
 
+mod pattern;
+mod query;
+
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{Data, DataStruct, DeriveInput, Fields};
+use syn::{Data, DataStruct, DeriveInput, Fields, Lit, Meta, MetaNameValue, NestedMeta};
 
-#[proc_macro_derive(Filterable)]
+/// Makes a struct filterable. With `#[filterable(builder)]` this also generates a typed builder
+/// named after the struct, e.g. `PersonFilter`, and implements `vec_filter::QueryBuilder` to
+/// return it. `#[filterable(builder = "PersonQuery")]` names the builder.
+#[proc_macro_derive(Filterable, attributes(filterable))]
 pub fn filterable_derive(input: TokenStream) -> TokenStream {
     let ast = syn::parse(input).unwrap();
-    impl_filterable(&ast)
+    match builder_name(&ast) {
+        Ok(builder) => impl_filterable(&ast, builder),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+/// Builds an `AST` from a query written in the query syntax and checked at compile time:
+///
+/// ```ignore
+/// let ast = filter!(Person, age > 30 && name == "Alice");
+/// ```
+///
+/// The query expands to calls on the builder generated by `#[derive(Filterable)]` with
+/// `#[filterable(builder)]`, so a misspelled field, an operator that does not suit the field's
+/// type or a value of the wrong type is a compile error at the offending token. Unlike in
+/// `parse_query`, comparisons need no parentheses, but a chain cannot mix `&&` and `||` without
+/// them. A value is a literal, a path to a constant or variable,
+/// or a `{ ... }` block of Rust code. Functions and `$` parameters are not supported. Patterns,
+/// and regular expressions given as string literals, are checked when the macro is expanded.
+#[proc_macro]
+pub fn filter(input: TokenStream) -> TokenStream {
+    match syn::parse::<query::Query>(input) {
+        Ok(query) => query.expand().into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+/// Returns the name of the builder to generate, if `#[filterable(builder)]` asks for one.
+fn builder_name(ast: &DeriveInput) -> syn::Result<Option<syn::Ident>> {
+    let mut builder = None;
+    for attr in ast
+        .attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("filterable"))
+    {
+        let nested = match attr.parse_meta()? {
+            Meta::List(list) => list.nested,
+            meta => {
+                return Err(syn::Error::new_spanned(
+                    meta,
+                    "expected `filterable(builder)`",
+                ))
+            }
+        };
+        for meta in nested {
+            builder = Some(match meta {
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("builder") => {
+                    syn::Ident::new(&format!("{}Filter", ast.ident), ast.ident.span())
+                }
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                    path,
+                    lit: Lit::Str(name),
+                    ..
+                })) if path.is_ident("builder") => name.parse()?,
+                meta => {
+                    return Err(syn::Error::new_spanned(
+                        meta,
+                        "expected `builder` or `builder = \"Name\"`",
+                    ))
+                }
+            });
+        }
+    }
+    Ok(builder)
 }
 
-fn impl_filterable(ast: &DeriveInput) -> TokenStream {
+fn impl_filterable(ast: &DeriveInput, builder: Option<syn::Ident>) -> TokenStream {
     let name = &ast.ident;
     let struct_name = &ast.ident;
     let struct_name_str = struct_name.to_string();
@@ -18,6 +87,7 @@ fn impl_filterable(ast: &DeriveInput) -> TokenStream {
         &format!("{}Properties", &struct_name_str),
         struct_name.span(),
     );
+    let vis = &ast.vis;
 
     let fields = match &ast.data {
         Data::Struct(DataStruct {
@@ -32,6 +102,7 @@ fn impl_filterable(ast: &DeriveInput) -> TokenStream {
     let mut get_property_value_match_arms = Vec::new();
     let mut get_property_enum_match_arms = Vec::new();
     let mut get_value_type_match_arms = Vec::new();
+    let mut filter_methods = Vec::new();
 
     for field in fields {
         let field_name = field.ident.as_ref().unwrap();
@@ -51,6 +122,14 @@ fn impl_filterable(ast: &DeriveInput) -> TokenStream {
             quote! { #field_name_str_lower => Ok(#properties_name::#variant_ident), };
         get_property_enum_match_arms.push(property_enum_match_arm);
 
+        let ty = &field.ty;
+        let filter_method = quote! {
+            #vis fn #field_name(&self) -> vec_filter::Field<#properties_name, #ty> {
+                vec_filter::Field::new(#properties_name::#variant_ident)
+            }
+        };
+        filter_methods.push(filter_method);
+
         let field_ty = match &field.ty {
             syn::Type::Path(type_path) => {
                 let segment = &type_path.path.segments.last().unwrap();
@@ -137,12 +216,29 @@ fn impl_filterable(ast: &DeriveInput) -> TokenStream {
         }
     };
 
-    use std::io::Write;
-    let my_string_output = &gen.to_string();
-    let mut file = std::fs::File::create("/Users/andrewmcclenaghan/temp/my_macro_output.rs")
-        .expect("Unable to create file");
-    file.write_all(my_string_output.as_bytes())
-        .expect("Unable to write file");
+    let builder = builder.map(|builder| {
+        quote! {
+            /// Builds typed queries on the fields of the struct.
+            #vis struct #builder;
+
+            impl #builder {
+                #(#filter_methods)*
+            }
+
+            impl vec_filter::QueryBuilder for #name {
+                type Builder = #builder;
+
+                fn filter() -> #builder {
+                    #builder
+                }
+            }
+        }
+    });
+
+    let gen = quote! {
+        #gen
+        #builder
+    };
 
     gen.into()
 }
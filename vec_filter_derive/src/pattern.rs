@@ -0,0 +1,84 @@
+use regex::RegexBuilder;
+
+/// The wildcard syntax of a pattern in `filter!`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Syntax {
+    Like,
+    Glob,
+}
+
+/// The escape character used when none is given, as `vec_filter::Pattern::DEFAULT_ESCAPE`.
+pub(crate) const DEFAULT_ESCAPE: char = '\\';
+
+/// Checks that a pattern compiles, returning the message that `vec_filter::Pattern::new` would
+/// fail with if it does not. This follows `Pattern::new`, which this crate cannot call because it
+/// cannot depend on `vec_filter`.
+pub(crate) fn check(
+    syntax: Syntax,
+    source: &str,
+    escape: char,
+    case_insensitive: bool,
+) -> Result<(), String> {
+    let translated = match syntax {
+        Syntax::Like => translate_like(source, escape)?,
+        Syntax::Glob => translate_glob(source, escape)?,
+    };
+    RegexBuilder::new(&format!("^{}$", translated))
+        .case_insensitive(case_insensitive)
+        .dot_matches_new_line(true)
+        .build()
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+fn translate_like(source: &str, escape: char) -> Result<String, String> {
+    let mut regex = String::new();
+    let mut chars = source.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c == escape => match chars.next() {
+                Some(escaped) => regex.push_str(&regex::escape(&escaped.to_string())),
+                None => return Err("pattern ends with the escape character".to_string()),
+            },
+            '%' => regex.push_str(".*"),
+            '_' => regex.push('.'),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    Ok(regex)
+}
+
+fn translate_glob(source: &str, escape: char) -> Result<String, String> {
+    let mut regex = String::new();
+    let mut chars = source.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c == escape => match chars.next() {
+                Some(escaped) => regex.push_str(&regex::escape(&escaped.to_string())),
+                None => return Err("pattern ends with the escape character".to_string()),
+            },
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            '[' => {
+                regex.push('[');
+                if let Some('!') | Some('^') = chars.peek() {
+                    chars.next();
+                    regex.push('^');
+                }
+                let mut first = true;
+                loop {
+                    match chars.next() {
+                        Some(']') if !first => break,
+                        Some('-') if !first && chars.peek() != Some(&']') => regex.push('-'),
+                        Some(c) => regex.push_str(&regex::escape(&c.to_string())),
+                        None => return Err("unclosed character class".to_string()),
+                    }
+                    first = false;
+                }
+                regex.push(']');
+            }
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    Ok(regex)
+}
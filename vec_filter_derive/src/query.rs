@@ -0,0 +1,350 @@
+use crate::pattern::{self, Syntax};
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use regex::Regex;
+use syn::ext::IdentExt;
+use syn::parse::{Parse, ParseStream};
+use syn::{Ident, Lit, LitStr, Path, Token, Type};
+
+/// The input of `filter!`: the struct to query, then the query.
+pub(crate) struct Query {
+    ast: TokenStream,
+}
+
+impl Parse for Query {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let ty = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let ast = Parser { ty: &ty }.chain(input)?;
+        if input.peek(Token![,]) {
+            input.parse::<Token![,]>()?;
+        }
+        if !input.is_empty() {
+            return Err(input.error("expected `&&`, `||` or the end of the query"));
+        }
+        Ok(Query { ast })
+    }
+}
+
+impl Query {
+    pub(crate) fn expand(self) -> TokenStream {
+        self.ast
+    }
+}
+
+/// Parses the query into calls on the typed builder generated by `#[derive(Filterable)]`, so
+/// that the compiler checks that each field exists and that the operator and value suit its type,
+/// and reports any error at the field, operator or value.
+struct Parser<'a> {
+    ty: &'a Type,
+}
+
+/// The negatable operators written as words. This copies the parser's list, which this crate
+/// cannot depend on; `vec_filter/tests/derive_copies.rs` checks that the two agree.
+const KEYWORDS: [&str; 9] = [
+    "contains",
+    "startswith",
+    "endswith",
+    "regexmatch",
+    "in",
+    "like",
+    "ilike",
+    "glob",
+    "iglob",
+];
+
+impl Parser<'_> {
+    /// `a && b && ...` or `a || b || ...`. `parse_query` reads a chain from left to right, so a
+    /// chain that mixes `&&` and `||` without parentheses is rejected instead of being given a
+    /// precedence that `parse_query` would not give it.
+    fn chain(&self, input: ParseStream) -> syn::Result<TokenStream> {
+        let mut ast = self.unary(input)?;
+        let mut first: Option<Ident> = None;
+        while let Some(connector) = connector(input)? {
+            if first.as_ref().is_some_and(|first| *first != connector) {
+                return Err(syn::Error::new(
+                    connector.span(),
+                    "`&&` and `||` cannot be mixed without parentheses",
+                ));
+            }
+            let right = self.unary(input)?;
+            ast = quote! { (#ast).#connector(#right) };
+            first = Some(connector);
+        }
+        Ok(ast)
+    }
+
+    /// `!a`, `not (a)`, `(a)` or a comparison.
+    fn unary(&self, input: ParseStream) -> syn::Result<TokenStream> {
+        if input.peek(Token![!]) && !input.peek(Token![!=]) {
+            input.parse::<Token![!]>()?;
+            let ast = self.unary(input)?;
+            return Ok(quote! { (#ast).negate() });
+        }
+        if peek_keyword(input, "not") && input.peek2(syn::token::Paren) {
+            input.parse::<Ident>()?;
+            let ast = self.unary(input)?;
+            return Ok(quote! { (#ast).negate() });
+        }
+        if input.peek(syn::token::Paren) {
+            let content;
+            syn::parenthesized!(content in input);
+            let ast = self.chain(&content)?;
+            if !content.is_empty() {
+                return Err(content.error("expected `&&`, `||` or `)`"));
+            }
+            return Ok(ast);
+        }
+        self.comparison(input)
+    }
+
+    /// `field operator value`
+    fn comparison(&self, input: ParseStream) -> syn::Result<TokenStream> {
+        let ty = self.ty;
+        let field = match input.parse::<Ident>() {
+            Ok(field) => field,
+            Err(_) => return Err(input.error("expected a field name")),
+        };
+        let target = quote! { <#ty as vec_filter::QueryBuilder>::filter().#field() };
+
+        // Each entry parses the operator if it is next, returning its span.
+        type Symbol = fn(ParseStream) -> syn::Result<Option<Span>>;
+        let symbols: [(Symbol, &str); 6] = [
+            (
+                |i| Ok(i.parse::<Option<Token![==]>>()?.map(|t| t.spans[0])),
+                "eq",
+            ),
+            (
+                |i| Ok(i.parse::<Option<Token![!=]>>()?.map(|t| t.spans[0])),
+                "ne",
+            ),
+            (
+                |i| Ok(i.parse::<Option<Token![>=]>>()?.map(|t| t.spans[0])),
+                "ge",
+            ),
+            (
+                |i| Ok(i.parse::<Option<Token![<=]>>()?.map(|t| t.spans[0])),
+                "le",
+            ),
+            (
+                |i| Ok(i.parse::<Option<Token![>]>>()?.map(|t| t.spans[0])),
+                "gt",
+            ),
+            (
+                |i| Ok(i.parse::<Option<Token![<]>>()?.map(|t| t.spans[0])),
+                "lt",
+            ),
+        ];
+        for (symbol, method) in symbols.iter() {
+            if let Some(span) = symbol(input)? {
+                let method = Ident::new(method, span);
+                let value = operand(input)?;
+                return Ok(quote! { #target.#method(#value) });
+            }
+        }
+
+        let negated = if peek_keyword(input, "not") {
+            input.parse::<Ident>()?;
+            true
+        } else if input.peek(Token![!]) {
+            input.parse::<Token![!]>()?;
+            true
+        } else {
+            false
+        };
+        let keyword = match input.fork().call(Ident::parse_any) {
+            Ok(keyword) => keyword,
+            Err(_) => return Err(unknown_operator(input.span(), negated)),
+        };
+        let name = keyword.to_string().to_lowercase();
+        if !KEYWORDS.contains(&name.as_str()) && (negated || name != "between") {
+            return Err(unknown_operator(keyword.span(), negated));
+        }
+        input.call(Ident::parse_any)?;
+        let span = keyword.span();
+
+        let method = |positive: &str| match negated {
+            true => Ident::new(&format!("not_{}", positive), span),
+            false => Ident::new(positive, span),
+        };
+        match name.as_str() {
+            "contains" | "startswith" | "endswith" | "regexmatch" => {
+                let method = method(match name.as_str() {
+                    "contains" => "contains",
+                    "startswith" => "starts_with",
+                    "endswith" => "ends_with",
+                    _ => "regex_match",
+                });
+                if name == "regexmatch" && input.peek(LitStr) {
+                    let regex = input.fork().parse::<LitStr>()?;
+                    if let Err(e) = Regex::new(&regex.value()) {
+                        let message = format!("invalid regular expression: {}", e);
+                        return Err(syn::Error::new(regex.span(), message));
+                    }
+                }
+                let value = operand(input)?;
+                Ok(quote! { #target.#method(#value) })
+            }
+            "in" if input.peek(syn::token::Bracket) => {
+                let content;
+                syn::bracketed!(content in input);
+                let mut values = Vec::new();
+                while !content.is_empty() {
+                    values.push(operand(&content)?);
+                    if content.is_empty() {
+                        break;
+                    }
+                    content.parse::<Token![,]>()?;
+                }
+                let method = match negated {
+                    true => Ident::new("not_in", span),
+                    false => Ident::new("is_in", span),
+                };
+                Ok(quote! { #target.#method([#(#values),*]) })
+            }
+            "in" => {
+                let lower = operand(input)?;
+                let range = if input.peek(Token![..=]) {
+                    let dots = input.parse::<Token![..=]>()?;
+                    let upper = operand(input)?;
+                    quote! { #lower #dots #upper }
+                } else if input.peek(Token![..]) {
+                    let dots = input.parse::<Token![..]>()?;
+                    let upper = operand(input)?;
+                    quote! { #lower #dots #upper }
+                } else {
+                    let message = "expected a list `[...]` or a range `a..b`";
+                    return Err(syn::Error::new_spanned(lower, message));
+                };
+                let method = Ident::new("in_range", span);
+                let ast = quote! { #target.#method(#range) };
+                Ok(match negated {
+                    true => quote! { (#ast).negate() },
+                    false => ast,
+                })
+            }
+            "between" => {
+                let lower = operand(input)?;
+                if !peek_keyword(input, "and") {
+                    return Err(input.error("expected `and`"));
+                }
+                input.parse::<Ident>()?;
+                let upper = operand(input)?;
+                let method = Ident::new("between", span);
+                Ok(quote! { #target.#method(#lower, #upper) })
+            }
+            _ => {
+                let syntax = match name.trim_start_matches('i') {
+                    "like" => Syntax::Like,
+                    _ => Syntax::Glob,
+                };
+                let case_insensitive = name.starts_with('i');
+                if !input.peek(LitStr) {
+                    return Err(input.error("expected a pattern string"));
+                }
+                let source = input.parse::<LitStr>()?;
+                let escape = if peek_keyword(input, "escape") {
+                    input.parse::<Ident>()?;
+                    let message = "expected a single character escape string";
+                    if !input.peek(LitStr) {
+                        return Err(input.error(message));
+                    }
+                    let escape = input.parse::<LitStr>()?;
+                    let value = escape.value();
+                    let mut chars = value.chars();
+                    match (chars.next(), chars.next()) {
+                        (Some(escape), None) => escape,
+                        _ => return Err(syn::Error::new(escape.span(), message)),
+                    }
+                } else {
+                    pattern::DEFAULT_ESCAPE
+                };
+                if let Err(message) =
+                    pattern::check(syntax, &source.value(), escape, case_insensitive)
+                {
+                    let message = format!("invalid pattern: {}", message);
+                    return Err(syn::Error::new(source.span(), message));
+                }
+                let syntax = match syntax {
+                    Syntax::Like => quote! { vec_filter::PatternSyntax::Like },
+                    Syntax::Glob => quote! { vec_filter::PatternSyntax::Glob },
+                };
+                let method = method("like");
+                Ok(quote! {
+                    #target.#method(
+                        vec_filter::Pattern::new(#syntax, #source, #escape, #case_insensitive)
+                            .expect("checked by `filter!`"),
+                    )
+                })
+            }
+        }
+    }
+}
+
+/// A value: a literal, a path to a constant or variable, or a `{ ... }` block of Rust code.
+fn operand(input: ParseStream) -> syn::Result<TokenStream> {
+    if input.peek(syn::token::Brace) {
+        // Rebuilt so that the braces belong to the expansion and are not linted as unnecessary.
+        let content;
+        syn::braced!(content in input);
+        let block: TokenStream = content.parse()?;
+        return Ok(quote! { { #block } });
+    }
+    if input.peek(Token![-]) {
+        let minus = input.parse::<Token![-]>()?;
+        let lit = input.parse::<Lit>()?;
+        return Ok(quote! { #minus #lit });
+    }
+    if input.peek(Lit) {
+        let lit = input.parse::<Lit>()?;
+        return Ok(quote! { #lit });
+    }
+    if input.peek(Token![$]) {
+        return Err(input.error(
+            "parameters cannot be used in `filter!`, insert the value with `{ ... }` instead",
+        ));
+    }
+    if input.peek(Ident) || input.peek(Token![::]) {
+        let path = Path::parse_mod_style(input)?;
+        return Ok(quote! { #path });
+    }
+    Err(input.error("expected a value"))
+}
+
+/// Parses `&&`, `and`, `||` or `or` if it is next, returning the `AST` method that joins the two
+/// sides.
+fn connector(input: ParseStream) -> syn::Result<Option<Ident>> {
+    let method = if input.peek(Token![&&]) {
+        Ident::new("and", input.parse::<Token![&&]>()?.spans[0])
+    } else if input.peek(Token![||]) {
+        Ident::new("or", input.parse::<Token![||]>()?.spans[0])
+    } else if peek_keyword(input, "and") || peek_keyword(input, "or") {
+        let word = input.parse::<Ident>()?;
+        Ident::new(&word.to_string().to_lowercase(), word.span())
+    } else {
+        return Ok(None);
+    };
+    Ok(Some(method))
+}
+
+fn peek_keyword(input: ParseStream, keyword: &str) -> bool {
+    input
+        .fork()
+        .call(Ident::parse_any)
+        .is_ok_and(|ident| ident.to_string().eq_ignore_ascii_case(keyword))
+}
+
+fn unknown_operator(span: Span, negated: bool) -> syn::Error {
+    let message = match negated {
+        true => {
+            "expected `contains`, `startswith`, `endswith`, `regexmatch`, `in`, `like`, \
+                 `ilike`, `glob` or `iglob`"
+        }
+        false => {
+            "expected an operator: `==`, `!=`, `>`, `>=`, `<`, `<=`, `contains`, \
+                  `startswith`, `endswith`, `regexmatch`, `in`, `between`, `like`, `ilike`, \
+                  `glob`, `iglob`, or one of these negated with `not`"
+        }
+    };
+    syn::Error::new(span, message)
+}